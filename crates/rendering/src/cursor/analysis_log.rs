@@ -0,0 +1,117 @@
+use crate::cursor::detection::CursorAnalysis;
+use crate::cursor::error::CursorError;
+use std::path::Path;
+
+/// Version of the log layout, bumped whenever [`CursorAnalysis`] or the order
+/// of the cursor type variants changes, since both are encoded positionally
+pub const ANALYSIS_LOG_VERSION: u32 = 6;
+
+/// Leading bytes of every analysis log, so other files are rejected outright
+const MAGIC: &[u8; 4] = b"CCAL";
+
+/// Save the analyses of a recording's frames, in order, as a compact binary
+/// log that [`read_analysis_log`] loads back exactly. Lets the analysis of a
+/// problematic recording be captured once and replayed while working on
+/// detection.
+pub fn write_analysis_log(path: &Path, analyses: &[CursorAnalysis]) -> Result<(), CursorError> {
+    let write_error = |message: String| CursorError::Write {
+        path: path.to_path_buf(),
+        message,
+    };
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&ANALYSIS_LOG_VERSION.to_le_bytes());
+    bincode::serialize_into(&mut bytes, analyses).map_err(|e| write_error(e.to_string()))?;
+    std::fs::write(path, bytes).map_err(|e| write_error(e.to_string()))
+}
+
+/// Load a log written by [`write_analysis_log`]. Logs of another
+/// [`ANALYSIS_LOG_VERSION`] are refused rather than misread
+pub fn read_analysis_log(path: &Path) -> Result<Vec<CursorAnalysis>, CursorError> {
+    let bytes = std::fs::read(path).map_err(|e| CursorError::Read {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    })?;
+
+    let payload = bytes
+        .strip_prefix(MAGIC)
+        .ok_or_else(|| CursorError::InvalidAnalysisLog("not an analysis log".into()))?;
+    let (version, payload) = payload
+        .split_first_chunk::<4>()
+        .ok_or_else(|| CursorError::InvalidAnalysisLog("missing version".into()))?;
+    let version = u32::from_le_bytes(*version);
+    if version != ANALYSIS_LOG_VERSION {
+        return Err(CursorError::InvalidAnalysisLog(format!(
+            "version {}, expected {}",
+            version, ANALYSIS_LOG_VERSION
+        )));
+    }
+
+    bincode::deserialize(payload).map_err(|e| CursorError::InvalidAnalysisLog(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::detection::{analyze_cursor, CommonCursorType};
+    use crate::cursor::rendering::rasterize_cursor;
+
+    fn log_path(label: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "cursor-analysis-{}-{}.log",
+            label,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_analysis_log_round_trips() {
+        let mut analyses: Vec<_> = CommonCursorType::ALL
+            .iter()
+            .map(|cursor_type| {
+                let image = rasterize_cursor(cursor_type, 32).unwrap();
+                analyze_cursor(image.as_raw(), 32, 32)
+            })
+            .collect();
+        analyses.push(analyze_cursor(&[0; 32 * 32 * 4], 32, 32));
+
+        let path = log_path("round-trip");
+        write_analysis_log(&path, &analyses).unwrap();
+        assert_eq!(read_analysis_log(&path).unwrap(), analyses);
+
+        write_analysis_log(&path, &[]).unwrap();
+        assert!(read_analysis_log(&path).unwrap().is_empty());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_analysis_log_rejects_other_versions() {
+        let path = log_path("version");
+        let image = rasterize_cursor(&CommonCursorType::Arrow, 32).unwrap();
+        write_analysis_log(&path, &[analyze_cursor(image.as_raw(), 32, 32)]).unwrap();
+
+        let log = std::fs::read(&path).unwrap();
+        let mut bytes = log.clone();
+        bytes[MAGIC.len()..][..4].copy_from_slice(&(ANALYSIS_LOG_VERSION + 1).to_le_bytes());
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(matches!(
+            read_analysis_log(&path),
+            Err(CursorError::InvalidAnalysisLog(_))
+        ));
+
+        // Neither a truncated log nor some other file is misread
+        std::fs::write(&path, &log[..log.len() - 1]).unwrap();
+        assert!(read_analysis_log(&path).is_err());
+        std::fs::write(&path, &MAGIC[..2]).unwrap();
+        assert!(read_analysis_log(&path).is_err());
+        std::fs::write(&path, b"{\"cursors\": []}").unwrap();
+        assert!(read_analysis_log(&path).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            read_analysis_log(&path),
+            Err(CursorError::Read { .. })
+        ));
+    }
+}
@@ -0,0 +1,398 @@
+use crate::cursor::error::CursorError;
+use crate::cursor::rendering::CursorRenderSource;
+use image::RgbaImage;
+use std::path::Path;
+use std::time::Duration;
+
+/// Unit of ANI frame timings, 1/60 of a second
+pub const JIFFY: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Size of the `anih` header chunk
+const ANIH_SIZE: usize = 36;
+
+/// `anih` flag saying frames are ICO/CUR files rather than raw bitmaps
+const AF_ICON: u32 = 0x1;
+
+/// Most frames or steps an animation may declare, far more than any real
+/// cursor has, so a corrupt header can't make parsing allocate without bound
+const MAX_ANI_STEPS: u32 = 4096;
+
+/// One image of an animated cursor, with its hotspot relative to its size
+/// like [`crate::cursor::detection::CommonCursorType::hotspot`]
+#[derive(Debug, Clone, PartialEq)]
+struct AniFrame {
+    image: RgbaImage,
+    hotspot: (f32, f32),
+}
+
+/// A step of the animation: which frame is shown and for how many jiffies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct AniStep {
+    frame: usize,
+    jiffies: u32,
+}
+
+/// A Windows animated cursor (`.ani`) with all its frames decoded.
+///
+/// An ANI file is a RIFF `ACON` container: an `anih` header, the frames as
+/// embedded CUR files, and optionally a `seq ` chunk ordering them into
+/// steps and a `rate` chunk timing each step. Without `seq ` the frames play
+/// in order, without `rate` every step lasts the header's default rate. The
+/// animation loops forever.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AniCursor {
+    frames: Vec<AniFrame>,
+    steps: Vec<AniStep>,
+    /// Sum of the steps' jiffies, never zero
+    loop_jiffies: u64,
+}
+
+impl AniCursor {
+    /// Read and parse an `.ani` file
+    pub fn load(path: &Path) -> Result<Self, CursorError> {
+        let bytes = std::fs::read(path).map_err(|e| CursorError::Read {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+        Self::parse(&bytes)
+    }
+
+    /// Parse the contents of an `.ani` file
+    pub fn parse(bytes: &[u8]) -> Result<Self, CursorError> {
+        let invalid = |message: &str| CursorError::InvalidAni(message.to_string());
+
+        let body = match bytes {
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'A', b'C', b'O', b'N', body @ ..] => body,
+            _ => return Err(invalid("not a RIFF ACON file")),
+        };
+
+        let mut header = None;
+        let (mut rates, mut sequence, mut frames) = (None, None, Vec::new());
+        for (id, data) in riff_chunks(body) {
+            match id {
+                b"anih" => {
+                    header = Some(AniHeader::parse(data).ok_or_else(|| invalid("bad anih"))?)
+                }
+                b"rate" => rates = Some(le_u32s(data)),
+                b"seq " => sequence = Some(le_u32s(data)),
+                b"LIST" if data.starts_with(b"fram") => {
+                    for (id, icon) in riff_chunks(&data[4..]) {
+                        if id == b"icon" {
+                            frames.push(decode_cursor_frame(icon)?);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let header = header.ok_or_else(|| invalid("missing anih header"))?;
+        if header.flags & AF_ICON == 0 {
+            return Err(invalid("raw bitmap frames aren't supported"));
+        }
+        if frames.is_empty() {
+            return Err(invalid("no frames"));
+        }
+
+        let step_count = sequence.as_ref().map_or(frames.len(), Vec::len);
+        let steps = (0..step_count)
+            .map(|i| {
+                let frame = sequence.as_ref().map_or(i, |sequence| sequence[i] as usize);
+                if frame >= frames.len() {
+                    return Err(invalid("sequence refers to a missing frame"));
+                }
+                let jiffies = rates
+                    .as_ref()
+                    .and_then(|rates| rates.get(i).copied())
+                    .unwrap_or(header.display_rate);
+                // A zero rate still shows the frame, for a jiffy, so the
+                // animation always advances
+                Ok(AniStep {
+                    frame,
+                    jiffies: jiffies.max(1),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if steps.is_empty() {
+            return Err(invalid("no steps"));
+        }
+
+        let loop_jiffies = steps.iter().map(|step| step.jiffies as u64).sum();
+        Ok(Self {
+            frames,
+            steps,
+            loop_jiffies,
+        })
+    }
+
+    /// Number of distinct images, some of which may be shown in several steps
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// How long one loop of the animation takes
+    pub fn loop_duration(&self) -> Duration {
+        JIFFY.saturating_mul(self.loop_jiffies.try_into().unwrap_or(u32::MAX))
+    }
+
+    /// The image shown `t` after the animation started, with its hotspot
+    /// relative to the image size. Loops back to the first step after
+    /// [`Self::loop_duration`]
+    pub fn frame_at(&self, t: Duration) -> (&RgbaImage, (f32, f32)) {
+        let elapsed = (t.as_nanos() / JIFFY.as_nanos()) as u64 % self.loop_jiffies;
+
+        let mut start = 0;
+        let step = self
+            .steps
+            .iter()
+            .find(|step| {
+                start += step.jiffies as u64;
+                elapsed < start
+            })
+            .unwrap_or(&self.steps[0]);
+
+        let frame = &self.frames[step.frame];
+        (&frame.image, frame.hotspot)
+    }
+
+    /// What to composite for the output frame at `t`, e.g. with
+    /// [`crate::cursor::rendering::render_cursor_onto_frame`]
+    pub fn render_source_at(&self, t: Duration) -> CursorRenderSource {
+        let (image, hotspot) = self.frame_at(t);
+        CursorRenderSource::RawBitmap(image.clone(), hotspot)
+    }
+}
+
+/// The fields of the `anih` chunk playback needs
+struct AniHeader {
+    /// Default jiffies per step when there's no `rate` chunk
+    display_rate: u32,
+    flags: u32,
+}
+
+impl AniHeader {
+    fn parse(data: &[u8]) -> Option<Self> {
+        if data.len() < ANIH_SIZE {
+            return None;
+        }
+        let field = |index: usize| u32::from_le_bytes(data[index * 4..][..4].try_into().unwrap());
+
+        let (frames, steps) = (field(1), field(2));
+        if frames > MAX_ANI_STEPS || steps > MAX_ANI_STEPS {
+            return None;
+        }
+        Some(Self {
+            display_rate: field(7),
+            flags: field(8),
+        })
+    }
+}
+
+/// The `(id, data)` chunks of a RIFF body, stopping at the first one that
+/// runs past the end. Chunks are padded to an even length
+fn riff_chunks(mut body: &[u8]) -> impl Iterator<Item = (&[u8; 4], &[u8])> {
+    std::iter::from_fn(move || {
+        let (id, rest) = body.split_first_chunk::<4>()?;
+        let (size, rest) = rest.split_first_chunk::<4>()?;
+        let size = u32::from_le_bytes(*size) as usize;
+        let data = rest.get(..size)?;
+        body = rest.get(size + size % 2..).unwrap_or_default();
+        Some((id, data))
+    })
+}
+
+/// Little-endian `u32`s of a chunk, ignoring a trailing partial one
+fn le_u32s(data: &[u8]) -> Vec<u32> {
+    data.chunks_exact(4)
+        .take(MAX_ANI_STEPS as usize)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .collect()
+}
+
+/// Size of an ICO/CUR file header and of each of its directory entries
+const ICONDIR_SIZE: usize = 6;
+const ICONDIRENTRY_SIZE: usize = 16;
+
+/// Decode the largest image of an embedded CUR file, with its hotspot.
+///
+/// CUR files store the hotspot where ICO files have their color planes and
+/// bit depth, so the chosen entry is copied into a single-image ICO with
+/// those fields reset for the decoder.
+fn decode_cursor_frame(cur: &[u8]) -> Result<AniFrame, CursorError> {
+    let invalid = |message: String| CursorError::InvalidAni(message);
+
+    let count = match cur {
+        [0, 0, 1 | 2, 0, lo, hi, ..] => u16::from_le_bytes([*lo, *hi]) as usize,
+        _ => return Err(invalid("frame isn't a CUR file".into())),
+    };
+    let entries = cur
+        .get(ICONDIR_SIZE..ICONDIR_SIZE + count * ICONDIRENTRY_SIZE)
+        .ok_or_else(|| invalid("truncated frame directory".into()))?;
+
+    // A width or height of 0 means 256
+    let side = |byte: u8| if byte == 0 { 256 } else { byte as u32 };
+    let entry = entries
+        .chunks_exact(ICONDIRENTRY_SIZE)
+        .max_by_key(|entry| side(entry[0]) * side(entry[1]))
+        .ok_or_else(|| invalid("frame has no images".into()))?;
+
+    let field = |offset: usize| u16::from_le_bytes([entry[offset], entry[offset + 1]]);
+    let hotspot = (field(4), field(6));
+    let length = u32::from_le_bytes(entry[8..12].try_into().unwrap()) as usize;
+    let offset = u32::from_le_bytes(entry[12..16].try_into().unwrap()) as usize;
+    let data = cur
+        .get(offset..offset.saturating_add(length))
+        .ok_or_else(|| invalid("frame image runs past its chunk".into()))?;
+
+    let mut ico = vec![0, 0, 1, 0, 1, 0];
+    ico.extend_from_slice(&entry[..4]);
+    ico.extend_from_slice(&[1, 0, 32, 0]);
+    ico.extend_from_slice(&(length as u32).to_le_bytes());
+    ico.extend_from_slice(&((ICONDIR_SIZE + ICONDIRENTRY_SIZE) as u32).to_le_bytes());
+    ico.extend_from_slice(data);
+
+    let image = image::load_from_memory_with_format(&ico, image::ImageFormat::Ico)
+        .map_err(|e| invalid(e.to_string()))?
+        .to_rgba8();
+    let hotspot = (
+        hotspot.0 as f32 / image.width() as f32,
+        hotspot.1 as f32 / image.height() as f32,
+    );
+    Ok(AniFrame { image, hotspot })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A CUR file holding `image` with its hotspot at `hotspot` pixels
+    fn cur_file(image: &RgbaImage, hotspot: (u16, u16)) -> Vec<u8> {
+        let mut ico = Vec::new();
+        image
+            .write_to(&mut std::io::Cursor::new(&mut ico), image::ImageFormat::Ico)
+            .unwrap();
+        ico[2] = 2;
+        ico[10..12].copy_from_slice(&hotspot.0.to_le_bytes());
+        ico[12..14].copy_from_slice(&hotspot.1.to_le_bytes());
+        ico
+    }
+
+    fn chunk(id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut chunk = id.to_vec();
+        chunk.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(data);
+        if data.len() % 2 == 1 {
+            chunk.push(0);
+        }
+        chunk
+    }
+
+    fn u32s(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    /// An ANI of solid frames, red, green, blue, ..., each with its hotspot
+    /// one pixel further right
+    fn ani_file(
+        frames: usize,
+        display_rate: u32,
+        rate: Option<&[u32]>,
+        seq: Option<&[u32]>,
+    ) -> Vec<u8> {
+        let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]];
+        let steps = seq.map_or(frames, <[u32]>::len) as u32;
+        let flags = AF_ICON | if seq.is_some() { 0x2 } else { 0 };
+        let header = u32s(&[
+            ANIH_SIZE as u32,
+            frames as u32,
+            steps,
+            0,
+            0,
+            0,
+            0,
+            display_rate,
+            flags,
+        ]);
+
+        let mut fram = b"fram".to_vec();
+        for (i, color) in colors.iter().cycle().take(frames).enumerate() {
+            let image = RgbaImage::from_pixel(16, 16, image::Rgba(*color));
+            fram.extend(chunk(b"icon", &cur_file(&image, (i as u16, 4))));
+        }
+
+        let mut body = b"ACON".to_vec();
+        body.extend(chunk(b"anih", &header));
+        if let Some(rate) = rate {
+            body.extend(chunk(b"rate", &u32s(rate)));
+        }
+        if let Some(seq) = seq {
+            body.extend(chunk(b"seq ", &u32s(seq)));
+        }
+        body.extend(chunk(b"LIST", &fram));
+        chunk(b"RIFF", &body)
+    }
+
+    fn color_at(ani: &AniCursor, t: Duration) -> [u8; 4] {
+        ani.frame_at(t).0.get_pixel(0, 0).0
+    }
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const GREEN: [u8; 4] = [0, 255, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+    #[test]
+    fn test_frames_follow_rate_and_sequence() {
+        let ani =
+            AniCursor::parse(&ani_file(3, 10, Some(&[2, 4, 6, 3]), Some(&[0, 1, 2, 1]))).unwrap();
+        assert_eq!(ani.frame_count(), 3);
+        assert_eq!(ani.loop_duration(), JIFFY * 15);
+
+        assert_eq!(color_at(&ani, Duration::ZERO), RED);
+        assert_eq!(color_at(&ani, JIFFY * 2), GREEN);
+        assert_eq!(color_at(&ani, JIFFY * 5), GREEN);
+        assert_eq!(color_at(&ani, JIFFY * 6), BLUE);
+        assert_eq!(color_at(&ani, JIFFY * 12), GREEN);
+
+        // Loops, to the same frame a loop later
+        assert_eq!(color_at(&ani, JIFFY * 15), RED);
+        assert_eq!(color_at(&ani, JIFFY * 15 * 40 + JIFFY * 7), BLUE);
+
+        let (image, hotspot) = ani.frame_at(JIFFY * 6);
+        assert_eq!(image.dimensions(), (16, 16));
+        assert_eq!(hotspot, (2.0 / 16.0, 4.0 / 16.0));
+        assert_eq!(
+            ani.render_source_at(JIFFY * 6),
+            CursorRenderSource::RawBitmap(image.clone(), hotspot)
+        );
+    }
+
+    #[test]
+    fn test_header_rate_without_rate_chunk() {
+        let ani = AniCursor::parse(&ani_file(2, 5, None, None)).unwrap();
+        assert_eq!(ani.loop_duration(), JIFFY * 10);
+        assert_eq!(color_at(&ani, JIFFY * 4), RED);
+        assert_eq!(color_at(&ani, JIFFY * 5), GREEN);
+        assert_eq!(color_at(&ani, JIFFY * 10), RED);
+
+        // A zero rate still advances, a jiffy per frame
+        let ani = AniCursor::parse(&ani_file(2, 0, None, None)).unwrap();
+        assert_eq!(ani.loop_duration(), JIFFY * 2);
+        assert_eq!(color_at(&ani, JIFFY), GREEN);
+    }
+
+    #[test]
+    fn test_rejects_malformed_files() {
+        let invalid =
+            |bytes: &[u8]| matches!(AniCursor::parse(bytes), Err(CursorError::InvalidAni(_)));
+
+        assert!(invalid(b"RIFF\0\0\0\0WAVE"));
+        assert!(invalid(&chunk(b"RIFF", b"ACON")));
+        assert!(invalid(&ani_file(0, 5, None, None)));
+        // The sequence shows a third frame that isn't there
+        assert!(invalid(&ani_file(2, 5, None, Some(&[0, 1, 2]))));
+
+        let mut truncated = ani_file(2, 5, None, None);
+        truncated.truncate(truncated.len() - 20);
+        assert!(AniCursor::parse(&truncated).is_err());
+    }
+}
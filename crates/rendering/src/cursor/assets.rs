@@ -0,0 +1,269 @@
+use crate::cursor::detection::CommonCursorType;
+use crate::cursor::pack::CursorPack;
+use std::path::{Path, PathBuf};
+
+/// Somewhere cursor SVGs can be read from by file name (`arrow.svg`, ...).
+///
+/// Loading goes through a source instead of reading files directly, so sources
+/// can be layered with [`ChainedSource`], e.g. a user's cursor pack over a
+/// theme over the bundled SVGs.
+pub trait CursorAssetSource {
+    /// Contents of `filename`, `None` if this source doesn't have it
+    fn read(&self, filename: &str) -> Option<Vec<u8>>;
+
+    /// SVG of a cursor type, by its [`CommonCursorType::svg_filename`]
+    fn read_cursor(&self, cursor_type: &CommonCursorType) -> Option<Vec<u8>> {
+        self.read(cursor_type.svg_filename())
+    }
+
+    /// Whether [`Self::read`] would find `filename`. Reads it unless the
+    /// source can tell more cheaply
+    fn contains(&self, filename: &str) -> bool {
+        self.read(filename).is_some()
+    }
+
+    /// Whether [`Self::read_cursor`] would find an SVG for a cursor type
+    fn contains_cursor(&self, cursor_type: &CommonCursorType) -> bool {
+        self.contains(cursor_type.svg_filename())
+    }
+}
+
+/// The SVGs compiled into the binary, which has one for every cursor type
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EmbeddedSource;
+
+impl CursorAssetSource for EmbeddedSource {
+    fn read(&self, filename: &str) -> Option<Vec<u8>> {
+        CommonCursorType::ALL
+            .iter()
+            .find(|cursor_type| cursor_type.svg_filename() == filename)
+            .map(|cursor_type| cursor_type.svg_bytes().to_vec())
+    }
+
+    fn read_cursor(&self, cursor_type: &CommonCursorType) -> Option<Vec<u8>> {
+        Some(cursor_type.svg_bytes().to_vec())
+    }
+
+    fn contains(&self, filename: &str) -> bool {
+        CommonCursorType::ALL
+            .iter()
+            .any(|cursor_type| cursor_type.svg_filename() == filename)
+    }
+
+    fn contains_cursor(&self, _cursor_type: &CommonCursorType) -> bool {
+        true
+    }
+}
+
+/// SVGs in a directory on disk, e.g. an installed theme
+#[derive(Debug, Clone)]
+pub struct DirectorySource {
+    dir: PathBuf,
+}
+
+impl DirectorySource {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl CursorAssetSource for DirectorySource {
+    fn read(&self, filename: &str) -> Option<Vec<u8>> {
+        std::fs::read(file_in(&self.dir, filename)?).ok()
+    }
+
+    fn contains(&self, filename: &str) -> bool {
+        file_in(&self.dir, filename).is_some_and(|path| path.is_file())
+    }
+}
+
+/// SVGs bundled as app resources, located through the app's resource resolver.
+///
+/// The resolver maps a resource path such as `cursors/arrow.svg` to a file; in
+/// the desktop app it wraps Tauri's `PathResolver::resolve` with
+/// `BaseDirectory::Resource`. Taking it as a function keeps this crate free of
+/// a Tauri dependency.
+pub struct ResourceSource {
+    resolve: Box<ResourceResolver>,
+}
+
+/// Maps a resource path to the file it's bundled as
+type ResourceResolver = dyn Fn(&str) -> Option<PathBuf> + Send + Sync;
+
+impl ResourceSource {
+    /// Resource directory the cursor SVGs are bundled under
+    pub const RESOURCE_DIR: &'static str = "cursors";
+
+    pub fn new(resolve: impl Fn(&str) -> Option<PathBuf> + Send + Sync + 'static) -> Self {
+        Self {
+            resolve: Box::new(resolve),
+        }
+    }
+
+    fn resolve_file(&self, filename: &str) -> Option<PathBuf> {
+        (self.resolve)(&format!("{}/{}", Self::RESOURCE_DIR, filename))
+    }
+}
+
+impl CursorAssetSource for ResourceSource {
+    fn read(&self, filename: &str) -> Option<Vec<u8>> {
+        std::fs::read(self.resolve_file(filename)?).ok()
+    }
+
+    fn contains(&self, filename: &str) -> bool {
+        self.resolve_file(filename)
+            .is_some_and(|path| path.is_file())
+    }
+}
+
+/// A user's cursor pack, whose cursors are named after their file stems.
+/// SVGs that fail validation are treated as missing, so the next source in
+/// a chain gets a chance instead
+impl CursorAssetSource for CursorPack {
+    fn read(&self, filename: &str) -> Option<Vec<u8>> {
+        let name = filename.strip_suffix(".svg")?;
+        self.svg(name)?.ok().map(<[u8]>::to_vec)
+    }
+
+    fn contains(&self, filename: &str) -> bool {
+        filename
+            .strip_suffix(".svg")
+            .and_then(|name| self.svg(name))
+            .is_some_and(|svg| svg.is_ok())
+    }
+}
+
+/// Sources tried in order, the first one that has a file wins
+#[derive(Default)]
+pub struct ChainedSource {
+    sources: Vec<Box<dyn CursorAssetSource + Send + Sync>>,
+}
+
+impl ChainedSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a source, consulted after the ones added before it
+    pub fn then(mut self, source: impl CursorAssetSource + Send + Sync + 'static) -> Self {
+        self.sources.push(Box::new(source));
+        self
+    }
+}
+
+impl CursorAssetSource for ChainedSource {
+    fn read(&self, filename: &str) -> Option<Vec<u8>> {
+        self.sources.iter().find_map(|source| source.read(filename))
+    }
+
+    fn contains(&self, filename: &str) -> bool {
+        self.sources.iter().any(|source| source.contains(filename))
+    }
+
+    fn contains_cursor(&self, cursor_type: &CommonCursorType) -> bool {
+        self.sources
+            .iter()
+            .any(|source| source.contains_cursor(cursor_type))
+    }
+}
+
+/// File names are looked up directly in the directory, so names with path
+/// separators or `..` can't reach outside it
+fn file_in(dir: &Path, filename: &str) -> Option<PathBuf> {
+    let mut components = Path::new(filename).components();
+    match (components.next(), components.next()) {
+        (Some(std::path::Component::Normal(name)), None) => Some(dir.join(name)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source_dir(label: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("cursor-assets-{}-{}", label, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_embedded_source_has_every_type() {
+        for cursor_type in CommonCursorType::ALL {
+            assert_eq!(
+                EmbeddedSource.read(cursor_type.svg_filename()).as_deref(),
+                Some(cursor_type.svg_bytes())
+            );
+        }
+        assert!(EmbeddedSource.read("missing.svg").is_none());
+    }
+
+    #[test]
+    fn test_chained_sources_fall_through() {
+        let dir = source_dir("chain");
+        let themed = CommonCursorType::Crosshair.svg_bytes();
+        std::fs::write(dir.join("arrow.svg"), themed).unwrap();
+
+        let source = ChainedSource::new()
+            .then(DirectorySource::new(&dir))
+            .then(EmbeddedSource);
+        assert_eq!(
+            source.read_cursor(&CommonCursorType::Arrow).unwrap(),
+            themed
+        );
+        assert_eq!(
+            source.read_cursor(&CommonCursorType::IBeam).unwrap(),
+            CommonCursorType::IBeam.svg_bytes()
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_contains_follows_the_chain() {
+        let dir = source_dir("contains");
+        std::fs::write(dir.join("arrow.svg"), b"<svg/>").unwrap();
+        let directory = DirectorySource::new(&dir);
+        assert!(directory.contains_cursor(&CommonCursorType::Arrow));
+        assert!(!directory.contains_cursor(&CommonCursorType::IBeam));
+        assert!(!directory.contains("../contains/arrow.svg"));
+
+        let source = ChainedSource::new().then(directory.clone());
+        assert!(!source.contains_cursor(&CommonCursorType::IBeam));
+        let source = source.then(EmbeddedSource);
+        for cursor_type in CommonCursorType::ALL {
+            assert!(source.contains_cursor(cursor_type));
+        }
+        assert!(!source.contains("missing.svg"));
+        assert!(!ChainedSource::new().contains_cursor(&CommonCursorType::Arrow));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_resource_source_resolves_paths() {
+        let dir = source_dir("resources");
+        std::fs::create_dir_all(dir.join(ResourceSource::RESOURCE_DIR)).unwrap();
+        std::fs::write(dir.join("cursors/ibeam.svg"), b"<svg/>").unwrap();
+
+        let root = dir.clone();
+        let source = ResourceSource::new(move |path| Some(root.join(path)));
+        assert_eq!(source.read("ibeam.svg").unwrap(), b"<svg/>");
+        assert!(source.read("arrow.svg").is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_directory_source_stays_inside_its_directory() {
+        let dir = source_dir("escape");
+        std::fs::write(dir.join("arrow.svg"), b"<svg/>").unwrap();
+
+        let source = DirectorySource::new(dir.join("theme"));
+        assert!(source.read("../arrow.svg").is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
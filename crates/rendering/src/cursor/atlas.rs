@@ -0,0 +1,170 @@
+use crate::cursor::detection::CommonCursorType;
+use crate::cursor::error::CursorError;
+use crate::cursor::rendering::rasterize_cursor;
+use image::RgbaImage;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Cursors per sprite sheet row; more cursors add rows rather than columns
+pub const ATLAS_COLUMNS: u32 = 8;
+
+/// Where a cursor sits in a sprite sheet, in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AtlasEntry {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Hotspot relative to the entry's top-left corner
+    pub hotspot: (f32, f32),
+}
+
+/// Layout of a cursor sprite sheet, written next to it as JSON
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CursorAtlas {
+    /// Sheet size in pixels
+    pub width: u32,
+    pub height: u32,
+    /// Entries by the cursor's SVG name without extension (`arrow`, `ibeam`, ...)
+    pub cursors: BTreeMap<String, AtlasEntry>,
+}
+
+/// Rasterize each distinct cursor type at `size` pixels and pack them into a
+/// grid of [`ATLAS_COLUMNS`] columns and as many rows as needed
+pub fn build_cursor_atlas(
+    types: &[CommonCursorType],
+    size: u32,
+) -> Result<(RgbaImage, CursorAtlas), CursorError> {
+    let mut distinct = Vec::with_capacity(types.len());
+    for cursor_type in types {
+        if !distinct.contains(cursor_type) {
+            distinct.push(*cursor_type);
+        }
+    }
+
+    let count = distinct.len() as u32;
+    let columns = count.clamp(1, ATLAS_COLUMNS);
+    let rows = count.div_ceil(columns).max(1);
+    let mut sheet = RgbaImage::new(columns * size, rows * size);
+    let mut cursors = BTreeMap::new();
+
+    for (i, cursor_type) in distinct.into_iter().enumerate() {
+        let (x, y) = (i as u32 % columns * size, i as u32 / columns * size);
+        let image = rasterize_cursor(&cursor_type, size)?;
+        image::imageops::replace(&mut sheet, &image, x as i64, y as i64);
+
+        let (hotspot_x, hotspot_y) = cursor_type.hotspot();
+        cursors.insert(
+            cursor_type
+                .svg_filename()
+                .trim_end_matches(".svg")
+                .to_string(),
+            AtlasEntry {
+                x,
+                y,
+                width: size,
+                height: size,
+                hotspot: (hotspot_x * size as f32, hotspot_y * size as f32),
+            },
+        );
+    }
+
+    let atlas = CursorAtlas {
+        width: sheet.width(),
+        height: sheet.height(),
+        cursors,
+    };
+
+    Ok((sheet, atlas))
+}
+
+/// Write the sprite sheet of [`build_cursor_atlas`] to `out_png` and its
+/// layout to `out_json`, so a player can load all cursors as one texture
+pub fn export_cursor_atlas(
+    types: &[CommonCursorType],
+    size: u32,
+    out_png: &Path,
+    out_json: &Path,
+) -> Result<CursorAtlas, CursorError> {
+    let (sheet, atlas) = build_cursor_atlas(types, size)?;
+    let write_error = |path: &Path, message: String| CursorError::Write {
+        path: path.to_path_buf(),
+        message,
+    };
+
+    sheet
+        .save_with_format(out_png, image::ImageFormat::Png)
+        .map_err(|e| write_error(out_png, e.to_string()))?;
+
+    let json =
+        serde_json::to_string_pretty(&atlas).map_err(|e| write_error(out_json, e.to_string()))?;
+    std::fs::write(out_json, json).map_err(|e| write_error(out_json, e.to_string()))?;
+
+    Ok(atlas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::GenericImageView;
+
+    #[test]
+    fn test_atlas_grows_rows() {
+        let (sheet, atlas) = build_cursor_atlas(CommonCursorType::ALL, 16).unwrap();
+        let rows = (CommonCursorType::ALL.len() as u32).div_ceil(ATLAS_COLUMNS);
+
+        assert_eq!(sheet.dimensions(), (ATLAS_COLUMNS * 16, rows * 16));
+        assert_eq!(atlas.cursors.len(), CommonCursorType::ALL.len());
+        assert_eq!(
+            atlas.cursors["arrow"],
+            AtlasEntry {
+                x: 0,
+                y: 0,
+                width: 16,
+                height: 16,
+                hotspot: (1.6, 1.6),
+            }
+        );
+        // The ninth cursor starts the second row
+        let ninth = CommonCursorType::ALL[8]
+            .svg_filename()
+            .trim_end_matches(".svg");
+        assert_eq!((atlas.cursors[ninth].x, atlas.cursors[ninth].y), (0, 16));
+    }
+
+    #[test]
+    fn test_atlas_skips_duplicates() {
+        let types = [
+            CommonCursorType::IBeam,
+            CommonCursorType::Arrow,
+            CommonCursorType::IBeam,
+        ];
+        let (sheet, atlas) = build_cursor_atlas(&types, 32).unwrap();
+
+        assert_eq!(sheet.dimensions(), (64, 32));
+        assert_eq!(atlas.cursors["ibeam"].x, 0);
+        assert_eq!(atlas.cursors["arrow"].x, 32);
+        assert_eq!(
+            sheet.view(32, 0, 32, 32).to_image(),
+            rasterize_cursor(&CommonCursorType::Arrow, 32).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_export_cursor_atlas() {
+        let dir = std::env::temp_dir().join(format!("cursor-atlas-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (png, json) = (dir.join("cursors.png"), dir.join("cursors.json"));
+
+        let atlas = export_cursor_atlas(&[CommonCursorType::Arrow], 24, &png, &json).unwrap();
+
+        assert_eq!(image::open(&png).unwrap().to_rgba8().dimensions(), (24, 24));
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&json).unwrap()).unwrap();
+        assert_eq!(written["cursors"]["arrow"]["width"], 24);
+        assert_eq!(written["width"], atlas.width);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
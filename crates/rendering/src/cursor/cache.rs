@@ -0,0 +1,699 @@
+use crate::cursor::assets::CursorAssetSource;
+use crate::cursor::detection::{canonicalize, CommonCursorType};
+use crate::cursor::error::CursorError;
+use crate::cursor::fingerprint::perceptual_hash;
+#[cfg(feature = "svg")]
+use crate::cursor::loading::load_cursor_svg;
+#[cfg(feature = "svg")]
+use crate::cursor::rendering::{parse_svg, rasterize_tree};
+use crate::cursor::rendering::{rasterize_cursor, rasterize_cursor_from, ScaleLimits};
+use image::RgbaImage;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Cache key: cursor type, logical size and the bits of the scale factor
+type RasterKey = (CommonCursorType, u32, u32);
+
+/// Bounded cache of rasterized cursors, evicting the least recently used entry.
+///
+/// Compositors drawing the same cursor every frame only pay for rasterization
+/// once per (type, size, scale).
+pub struct CursorCache {
+    capacity: usize,
+    /// Rasterized image and the tick it was last used at
+    entries: HashMap<RasterKey, (RgbaImage, u64)>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl CursorCache {
+    /// Create a cache holding at most `capacity` images (at least one)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: HashMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Get the cursor rasterized at `size` * `scale` pixels, rendering it on a
+    /// miss. `scale` is clamped to the default [`ScaleLimits`] first, so scales
+    /// beyond the limit share the entry of the limit itself
+    pub fn get_or_render(
+        &mut self,
+        cursor_type: CommonCursorType,
+        size: u32,
+        scale: f32,
+    ) -> Result<&RgbaImage, CursorError> {
+        self.get_or_render_with(cursor_type, size, scale, |pixels| {
+            rasterize_cursor(&cursor_type, pixels)
+        })
+    }
+
+    /// Like [`Self::get_or_render`], rasterizing the SVG `source` has for the
+    /// type. Entries aren't told apart by source, so a cache should only ever
+    /// be used with one
+    pub fn get_or_render_from(
+        &mut self,
+        source: &dyn CursorAssetSource,
+        cursor_type: CommonCursorType,
+        size: u32,
+        scale: f32,
+    ) -> Result<&RgbaImage, CursorError> {
+        self.get_or_render_with(cursor_type, size, scale, |pixels| {
+            rasterize_cursor_from(source, &cursor_type, pixels)
+        })
+    }
+
+    /// Like [`Self::get_or_render`], rasterizing from the SVG `trees` has
+    /// parsed for the type, parsing it there first if need be
+    pub fn get_or_render_parsed(
+        &mut self,
+        trees: &mut TreeCache,
+        cursor_type: CommonCursorType,
+        size: u32,
+        scale: f32,
+    ) -> Result<&RgbaImage, CursorError> {
+        self.get_or_render_with(cursor_type, size, scale, |pixels| {
+            trees.rasterize(&cursor_type, pixels)
+        })
+    }
+
+    fn get_or_render_with(
+        &mut self,
+        cursor_type: CommonCursorType,
+        size: u32,
+        scale: f32,
+        render: impl FnOnce(u32) -> Result<RgbaImage, CursorError>,
+    ) -> Result<&RgbaImage, CursorError> {
+        let scale = ScaleLimits::default().clamp(scale)?;
+
+        let key = (cursor_type, size, scale.to_bits());
+        self.tick += 1;
+
+        if let Some(entry) = self.entries.get_mut(&key) {
+            entry.1 = self.tick;
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+            let pixels = (size as f32 * scale).round().max(1.0) as u32;
+            let image = render(pixels)?;
+
+            if self.entries.len() >= self.capacity {
+                self.evict_least_recently_used();
+            }
+            self.entries.insert(key, (image, self.tick));
+        }
+
+        Ok(&self.entries[&key].0)
+    }
+
+    fn evict_least_recently_used(&mut self) {
+        if let Some(oldest) = self
+            .entries
+            .iter()
+            .min_by_key(|(_, (_, last_used))| *last_used)
+            .map(|(key, _)| *key)
+        {
+            self.entries.remove(&oldest);
+        }
+    }
+
+    /// Number of lookups served from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of lookups that had to rasterize
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop all cached images, keeping the counters
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// The bundled SVGs parsed once per cursor type, so rasterizing a type at
+/// another size or scale doesn't read and parse its SVG again.
+///
+/// Without the `svg` feature cursors come from the bundled PNGs instead and
+/// nothing is ever cached.
+#[derive(Default)]
+pub struct TreeCache {
+    #[cfg(feature = "svg")]
+    trees: HashMap<CommonCursorType, resvg::usvg::Tree>,
+}
+
+impl TreeCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rasterize a cursor type like [`rasterize_cursor`], from its cached
+    /// tree, parsing the bundled SVG on a miss
+    pub fn rasterize(
+        &mut self,
+        cursor_type: &CommonCursorType,
+        size: u32,
+    ) -> Result<RgbaImage, CursorError> {
+        #[cfg(feature = "svg")]
+        return rasterize_tree(self.get_or_parse(cursor_type)?, size);
+
+        #[cfg(not(feature = "svg"))]
+        return rasterize_cursor(cursor_type, size);
+    }
+
+    #[cfg(feature = "svg")]
+    fn get_or_parse(
+        &mut self,
+        cursor_type: &CommonCursorType,
+    ) -> Result<&resvg::usvg::Tree, CursorError> {
+        if !self.trees.contains_key(cursor_type) {
+            let svg = load_cursor_svg(cursor_type).ok_or(CursorError::MissingSvg(*cursor_type))?;
+            self.trees.insert(*cursor_type, parse_svg(&svg)?);
+        }
+        Ok(&self.trees[cursor_type])
+    }
+
+    pub fn len(&self) -> usize {
+        #[cfg(feature = "svg")]
+        return self.trees.len();
+
+        #[cfg(not(feature = "svg"))]
+        return 0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop all parsed trees
+    pub fn clear(&mut self) {
+        #[cfg(feature = "svg")]
+        self.trees.clear();
+    }
+}
+
+/// Parse and rasterize every cursor type at every combination of `sizes` and
+/// `scales` ahead of time, e.g. behind a loading screen, so the first frames
+/// never wait on either. Returns how many entries were added to the two
+/// caches together; trees and images already cached, or images clamped to the
+/// same scale as an earlier one, don't count. Warming more combinations than
+/// `raster_cache`'s capacity evicts the first ones again
+pub fn warm_caches(
+    sizes: &[u32],
+    scales: &[f32],
+    raster_cache: &mut CursorCache,
+    tree_cache: &mut TreeCache,
+) -> Result<usize, CursorError> {
+    let (misses, trees) = (raster_cache.misses, tree_cache.len());
+    for &cursor_type in CommonCursorType::ALL {
+        for &size in sizes {
+            for &scale in scales {
+                raster_cache.get_or_render_parsed(tree_cache, cursor_type, size, scale)?;
+            }
+        }
+    }
+    Ok((raster_cache.misses - misses) as usize + tree_cache.len() - trees)
+}
+
+/// Bounded cache of detection results keyed by the cursor's [`canonicalize`]d
+/// mask, evicting the least recently used entry.
+///
+/// Canonicalizing is much cheaper than detecting, and the same cursor captured
+/// at another size or with other padding maps to the same mask, so it's only
+/// detected once. That first cursor is detected from its own pixels, like
+/// [`CommonCursorType::detect_from_image`], and its result reused for the
+/// others.
+pub struct DetectionCache {
+    capacity: usize,
+    /// Detected type and the tick it was last used at
+    entries: HashMap<[u8; 1024], (Option<CommonCursorType>, u64)>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl DetectionCache {
+    /// Create a cache holding at most `capacity` results (at least one)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: HashMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Detect the cursor in an RGBA buffer, reusing the result for an
+    /// identical-looking cursor
+    pub fn detect(
+        &mut self,
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Option<CommonCursorType> {
+        let mask = canonicalize(image_data, width, height);
+        self.tick += 1;
+
+        if let Some(entry) = self.entries.get_mut(&mask) {
+            entry.1 = self.tick;
+            self.hits += 1;
+            return entry.0;
+        }
+
+        self.misses += 1;
+        let cursor_type = CommonCursorType::detect_from_image(image_data, width, height);
+        if self.entries.len() >= self.capacity {
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(mask, _)| *mask)
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(mask, (cursor_type, self.tick));
+
+        cursor_type
+    }
+
+    /// Number of detections served from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of detections that had to run
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop all cached results, keeping the counters
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Version of the [`PersistentCursorCache`] file layout. Bumped whenever the
+/// order of the cursor type variants changes, since types are stored by index
+pub const DETECTION_CACHE_VERSION: u32 = 1;
+
+/// Bits two perceptual hashes may differ in by default and still be taken for
+/// the same cursor, see [`PersistentCursorCache::new`]
+pub const DEFAULT_HASH_TOLERANCE: u32 = 4;
+
+/// Leading bytes of every detection cache file
+const DETECTION_CACHE_MAGIC: &[u8; 4] = b"CCDC";
+
+/// Detection results keyed by the cursor's [`perceptual_hash`] and size, kept
+/// across sessions in a file.
+///
+/// Lookups accept any cached hash of the same size within a Hamming distance,
+/// so a cursor whose anti-aliased edges came out a little different still
+/// hits. Unlike [`DetectionCache`] nothing is evicted: an app only has so many
+/// distinct cursors, and a recording session reuses the results of every
+/// earlier one.
+#[derive(Debug, Clone)]
+pub struct PersistentCursorCache {
+    tolerance: u32,
+    entries: HashMap<(u64, u32, u32), Option<CommonCursorType>>,
+    hits: u64,
+    misses: u64,
+}
+
+impl PersistentCursorCache {
+    /// Create an empty cache matching hashes that differ in at most
+    /// `tolerance` bits
+    pub fn new(tolerance: u32) -> Self {
+        Self {
+            tolerance,
+            entries: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Load the results a previous session [`Self::save`]d to `path`.
+    ///
+    /// A missing file is a cold start and gives an empty cache. So does an
+    /// unreadable or corrupt one, or one of another
+    /// [`DETECTION_CACHE_VERSION`], after logging a warning: the cache only
+    /// saves time, detection still works without it.
+    pub fn load(path: &Path, tolerance: u32) -> Self {
+        let mut cache = Self::new(tolerance);
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return cache,
+            Err(e) => {
+                tracing::warn!("Ignoring cursor detection cache {}: {e}", path.display());
+                return cache;
+            }
+        };
+
+        match decode_cache(&bytes) {
+            Ok(entries) => {
+                cache.entries = entries
+                    .into_iter()
+                    .map(|(hash, width, height, cursor_type)| ((hash, width, height), cursor_type))
+                    .collect();
+            }
+            Err(message) => {
+                tracing::warn!(
+                    "Ignoring corrupt cursor detection cache {}: {message}",
+                    path.display()
+                );
+            }
+        }
+        cache
+    }
+
+    /// Write every cached result to `path`, for [`Self::load`] to pick up
+    pub fn save(&self, path: &Path) -> Result<(), CursorError> {
+        let write_error = |message: String| CursorError::Write {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        // Sorted so the same results always make the same file
+        let mut entries: Vec<_> = self
+            .entries
+            .iter()
+            .map(|(&(hash, width, height), &cursor_type)| (hash, width, height, cursor_type))
+            .collect();
+        entries.sort_unstable();
+
+        let mut bytes = DETECTION_CACHE_MAGIC.to_vec();
+        bytes.extend_from_slice(&DETECTION_CACHE_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, &entries).map_err(|e| write_error(e.to_string()))?;
+        std::fs::write(path, bytes).map_err(|e| write_error(e.to_string()))
+    }
+
+    /// Detect the cursor in an RGBA buffer, skipping detection when a cursor
+    /// of the same size and a close enough hash was seen before. Bitmaps
+    /// without any opaque pixels have no hash and are always detected
+    pub fn detect(
+        &mut self,
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Option<CommonCursorType> {
+        let Some(hash) = perceptual_hash(image_data, width, height) else {
+            return CommonCursorType::detect_from_image(image_data, width, height);
+        };
+
+        if let Some(cursor_type) = self.get(hash, width, height) {
+            self.hits += 1;
+            return cursor_type;
+        }
+
+        self.misses += 1;
+        let cursor_type = CommonCursorType::detect_from_image(image_data, width, height);
+        self.entries.insert((hash, width, height), cursor_type);
+        cursor_type
+    }
+
+    /// The cached result for a cursor of this size and hash, the closest
+    /// within the tolerance if there's no exact match. `Some(None)` is a
+    /// cursor that was detected as nothing
+    pub fn get(&self, hash: u64, width: u32, height: u32) -> Option<Option<CommonCursorType>> {
+        if let Some(&cursor_type) = self.entries.get(&(hash, width, height)) {
+            return Some(cursor_type);
+        }
+
+        self.entries
+            .iter()
+            .filter(|((_, w, h), _)| (*w, *h) == (width, height))
+            .map(|((cached, _, _), &cursor_type)| ((cached ^ hash).count_ones(), cursor_type))
+            .filter(|&(distance, _)| distance <= self.tolerance)
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, cursor_type)| cursor_type)
+    }
+
+    /// Number of detections served from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Number of detections that had to run
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop all cached results, keeping the counters
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl Default for PersistentCursorCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_HASH_TOLERANCE)
+    }
+}
+
+type CacheEntry = (u64, u32, u32, Option<CommonCursorType>);
+
+/// The entries of a file written by [`PersistentCursorCache::save`], or what's
+/// wrong with it
+fn decode_cache(bytes: &[u8]) -> Result<Vec<CacheEntry>, String> {
+    let payload = bytes
+        .strip_prefix(DETECTION_CACHE_MAGIC)
+        .ok_or("not a detection cache")?;
+    let (version, payload) = payload.split_first_chunk::<4>().ok_or("missing version")?;
+    let version = u32::from_le_bytes(*version);
+    if version != DETECTION_CACHE_VERSION {
+        return Err(format!(
+            "version {}, expected {}",
+            version, DETECTION_CACHE_VERSION
+        ));
+    }
+
+    bincode::deserialize(payload).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_lookups_hit() {
+        let mut cache = CursorCache::new(4);
+        for _ in 0..3 {
+            let image = cache
+                .get_or_render(CommonCursorType::Arrow, 32, 2.0)
+                .unwrap();
+            assert_eq!(image.dimensions(), (64, 64));
+        }
+
+        assert_eq!(cache.misses(), 1);
+        assert_eq!(cache.hits(), 2);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = CursorCache::new(2);
+        cache
+            .get_or_render(CommonCursorType::Arrow, 32, 1.0)
+            .unwrap();
+        cache
+            .get_or_render(CommonCursorType::IBeam, 32, 1.0)
+            .unwrap();
+        // Touch the arrow so the I-beam becomes the oldest entry
+        cache
+            .get_or_render(CommonCursorType::Arrow, 32, 1.0)
+            .unwrap();
+        cache
+            .get_or_render(CommonCursorType::Crosshair, 32, 1.0)
+            .unwrap();
+        assert_eq!(cache.len(), 2);
+
+        let misses = cache.misses();
+        cache
+            .get_or_render(CommonCursorType::Arrow, 32, 1.0)
+            .unwrap();
+        assert_eq!(cache.misses(), misses);
+        cache
+            .get_or_render(CommonCursorType::IBeam, 32, 1.0)
+            .unwrap();
+        assert_eq!(cache.misses(), misses + 1);
+    }
+
+    #[test]
+    fn test_scale_is_part_of_the_key() {
+        let mut cache = CursorCache::new(4);
+        cache
+            .get_or_render(CommonCursorType::Arrow, 32, 1.0)
+            .unwrap();
+        cache
+            .get_or_render(CommonCursorType::Arrow, 32, 1.5)
+            .unwrap();
+
+        assert_eq!(cache.misses(), 2);
+        assert!(cache
+            .get_or_render(CommonCursorType::Arrow, 32, -1.0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_warm_caches_renders_every_type_once() {
+        let (mut rasters, mut trees) = (CursorCache::new(256), TreeCache::new());
+        let types = CommonCursorType::ALL.len();
+        // One tree per type, with the svg feature
+        let parsed = if cfg!(feature = "svg") { types } else { 0 };
+        assert_eq!(
+            warm_caches(&[16], &[1.0, 2.0], &mut rasters, &mut trees).unwrap(),
+            types * 2 + parsed
+        );
+        assert_eq!(trees.len(), parsed);
+        // Already warm, and 8.0 is clamped to the 4.0 that's new
+        assert_eq!(
+            warm_caches(&[16], &[1.0, 4.0, 8.0], &mut rasters, &mut trees).unwrap(),
+            types
+        );
+        assert_eq!(rasters.len(), types * 3);
+
+        let misses = rasters.misses();
+        rasters
+            .get_or_render(CommonCursorType::Grab, 16, 2.0)
+            .unwrap();
+        assert_eq!(rasters.misses(), misses);
+        assert!(warm_caches(&[16], &[0.0], &mut rasters, &mut trees).is_err());
+
+        // Rasterizing from the parsed tree draws the same cursor
+        assert_eq!(
+            trees.rasterize(&CommonCursorType::Copy, 24).unwrap(),
+            rasterize_cursor(&CommonCursorType::Copy, 24).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_detection_shares_entries_across_sizes_and_padding() {
+        let mut cache = DetectionCache::new(8);
+        let ibeam = rasterize_cursor(&CommonCursorType::IBeam, 32).unwrap();
+        let doubled = image::imageops::resize(&ibeam, 64, 64, image::imageops::Nearest);
+        let mut padded = RgbaImage::new(96, 80);
+        image::imageops::overlay(&mut padded, &doubled, 20, 9);
+
+        assert_eq!(
+            cache.detect(ibeam.as_raw(), 32, 32),
+            Some(CommonCursorType::IBeam)
+        );
+        assert_eq!(
+            cache.detect(padded.as_raw(), 96, 80),
+            Some(CommonCursorType::IBeam)
+        );
+        assert_eq!(cache.misses(), 1);
+        assert_eq!(cache.hits(), 1);
+
+        let arrow = rasterize_cursor(&CommonCursorType::Arrow, 32).unwrap();
+        assert_eq!(
+            cache.detect(arrow.as_raw(), 32, 32),
+            Some(CommonCursorType::Arrow)
+        );
+        assert_eq!(cache.len(), 2);
+    }
+
+    fn cache_path(label: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "cursor-detection-cache-{}-{}.bin",
+            label,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn test_persistent_cache_survives_restart() {
+        let path = cache_path("restart");
+        let hand = rasterize_cursor(&CommonCursorType::PointingHand, 32).unwrap();
+        let mut cache = PersistentCursorCache::default();
+        assert_eq!(
+            cache.detect(hand.as_raw(), 32, 32),
+            Some(CommonCursorType::PointingHand)
+        );
+        cache.save(&path).unwrap();
+
+        // The next session hits on the same cursor with noisy edges
+        let mut noisy = hand.clone();
+        for (i, pixel) in noisy.pixels_mut().enumerate() {
+            if pixel[3] > 0 && pixel[3] < 255 {
+                let delta = if i % 2 == 0 { 12 } else { -12 };
+                pixel[3] = (pixel[3] as i32 + delta).clamp(1, 254) as u8;
+            }
+        }
+        let mut restarted = PersistentCursorCache::load(&path, DEFAULT_HASH_TOLERANCE);
+        assert_eq!(restarted.len(), 1);
+        assert_eq!(
+            restarted.detect(noisy.as_raw(), 32, 32),
+            Some(CommonCursorType::PointingHand)
+        );
+        assert_eq!((restarted.hits(), restarted.misses()), (1, 0));
+
+        // Size is part of the key, and a different shape is too far off
+        let hash = perceptual_hash(hand.as_raw(), 32, 32).unwrap();
+        assert_eq!(restarted.get(hash, 48, 48), None);
+        let arrow = rasterize_cursor(&CommonCursorType::Arrow, 32).unwrap();
+        assert_eq!(
+            restarted.detect(arrow.as_raw(), 32, 32),
+            Some(CommonCursorType::Arrow)
+        );
+        assert_eq!(restarted.misses(), 1);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_persistent_cache_starts_empty_on_bad_files() {
+        let path = cache_path("corrupt");
+        assert!(PersistentCursorCache::load(&path, 0).is_empty());
+
+        let ibeam = rasterize_cursor(&CommonCursorType::IBeam, 32).unwrap();
+        let mut cache = PersistentCursorCache::new(0);
+        cache.detect(ibeam.as_raw(), 32, 32);
+        cache.save(&path).unwrap();
+        let saved = std::fs::read(&path).unwrap();
+
+        let mut bytes = saved.clone();
+        bytes[DETECTION_CACHE_MAGIC.len()..][..4]
+            .copy_from_slice(&(DETECTION_CACHE_VERSION + 1).to_le_bytes());
+        for bad in [&bytes[..], &saved[..saved.len() - 1], b"not a cache"] {
+            std::fs::write(&path, bad).unwrap();
+            assert!(PersistentCursorCache::load(&path, 0).is_empty());
+        }
+
+        std::fs::write(&path, &saved).unwrap();
+        assert_eq!(PersistentCursorCache::load(&path, 0).len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
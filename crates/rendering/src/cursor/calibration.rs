@@ -0,0 +1,254 @@
+use crate::cursor::detection::CommonCursorType;
+
+/// Logistic mapping of one type's raw matcher score to the probability that
+/// a cursor scoring that much really is of the type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LogisticCoefficients {
+    pub slope: f32,
+    pub intercept: f32,
+}
+
+impl LogisticCoefficients {
+    /// Calibrated confidence in 0..=1 of a raw score
+    pub fn confidence(&self, raw: f32) -> f32 {
+        1.0 / (1.0 + (-(self.slope * raw + self.intercept)).exp())
+    }
+}
+
+/// Coefficients fitted with [`Calibration::fit`] on the detection ranking of
+/// every fixture in `tests/fixtures/cursors`. Types missing here, those the
+/// corpus has no examples of, keep their raw scores
+const FITTED: &[(CommonCursorType, f32, f32)] = &[
+    (CommonCursorType::Arrow, 7.684, -5.950),
+    (CommonCursorType::IBeam, 9.252, -5.170),
+    (CommonCursorType::Crosshair, 9.095, -4.695),
+    (CommonCursorType::PointingHand, 8.063, -6.791),
+    (CommonCursorType::ResizeNWSE, 8.932, -6.110),
+    (CommonCursorType::ResizeEW, 9.219, -5.535),
+    (CommonCursorType::ResizeAll, 7.658, -5.183),
+    (CommonCursorType::Copy, 6.645, -5.031),
+    (CommonCursorType::Alias, 6.645, -5.031),
+    (CommonCursorType::Help, 6.645, -5.031),
+    (CommonCursorType::Progress, 7.014, -4.992),
+    (CommonCursorType::ContextMenu, 6.645, -5.031),
+    (CommonCursorType::TouchPointer, 7.689, -5.154),
+    (CommonCursorType::ScrollNS, 6.645, -5.031),
+    (CommonCursorType::ScrollEW, 6.645, -5.031),
+    (CommonCursorType::NotAllowed, 7.014, -4.992),
+    (CommonCursorType::Grab, 7.630, -5.099),
+    (CommonCursorType::Grabbing, 8.871, -4.723),
+    (CommonCursorType::Reticle, 7.266, -4.962),
+    (CommonCursorType::Blank, 6.645, -5.031),
+    (CommonCursorType::Move, 7.535, -4.869),
+    (CommonCursorType::Eyedropper, 8.278, -4.718),
+    (CommonCursorType::DownloadLink, 7.014, -4.992),
+    (CommonCursorType::RowResize, 7.669, -4.856),
+    (CommonCursorType::Wait, 8.278, -4.718),
+];
+
+/// L2 penalty on the coefficients while fitting. Keeps the fit finite for
+/// types whose raw scores separate the corpus perfectly, as the structurally
+/// detected ones do
+const FIT_REGULARIZATION: f64 = 0.1;
+
+/// Newton steps a fit is given to converge
+const FIT_ITERATIONS: usize = 100;
+
+/// Per-type mapping from raw matcher scores to calibrated confidences.
+///
+/// Raw scores only say how clearly a type's heuristics are satisfied, so
+/// the same score means a different chance of being right for different
+/// types. Passed the ranking of a labeled set, [`Self::fit`] learns a
+/// logistic curve per type that turns them into probabilities, which makes a
+/// threshold on confidence mean the same for every type. The default is fitted
+/// on the bundled fixture corpus; captures from a specific OS are better
+/// served by a calibration fitted on that OS's cursors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    coefficients: [Option<LogisticCoefficients>; CommonCursorType::ALL.len()],
+}
+
+impl Calibration {
+    /// Calibration that leaves every raw score as it is
+    pub fn uncalibrated() -> Self {
+        Self {
+            coefficients: [None; CommonCursorType::ALL.len()],
+        }
+    }
+
+    /// The mapping used for a type, `None` if its scores are left raw
+    pub fn coefficients(&self, cursor_type: CommonCursorType) -> Option<LogisticCoefficients> {
+        self.coefficients[slot(cursor_type)]
+    }
+
+    /// Replace a type's mapping, `None` to leave its scores raw
+    pub fn set_coefficients(
+        &mut self,
+        cursor_type: CommonCursorType,
+        coefficients: Option<LogisticCoefficients>,
+    ) {
+        self.coefficients[slot(cursor_type)] = coefficients;
+    }
+
+    /// Calibrated confidence of a raw score for `cursor_type`
+    pub fn confidence(&self, cursor_type: CommonCursorType, raw: f32) -> f32 {
+        self.coefficients(cursor_type)
+            .map_or(raw, |coefficients| coefficients.confidence(raw))
+    }
+
+    /// Turn the raw scores of a ranking, e.g. from
+    /// [`CommonCursorType::detect_ranked`], into calibrated confidences and
+    /// sort it again. Equal confidences keep their order
+    pub fn calibrate(&self, ranked: &mut [(CommonCursorType, f32)]) {
+        for (cursor_type, score) in ranked.iter_mut() {
+            *score = self.confidence(*cursor_type, *score);
+        }
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    }
+
+    /// Fit a calibration to labeled samples, each the expected type of a
+    /// cursor and the [`CommonCursorType::detect_ranked`] scores of its image.
+    ///
+    /// Every type is fitted on its score across all samples, a type missing
+    /// from a sample's ranking counting as 0.0, with the samples of that type
+    /// as positives and all others as negatives. Types without both are left
+    /// raw, there's nothing to fit them to.
+    pub fn fit(samples: &[(CommonCursorType, Vec<(CommonCursorType, f32)>)]) -> Self {
+        let mut calibration = Self::uncalibrated();
+        for &cursor_type in CommonCursorType::ALL {
+            let points: Vec<(f64, bool)> = samples
+                .iter()
+                .map(|(expected, ranked)| {
+                    let raw = ranked
+                        .iter()
+                        .find(|(candidate, _)| *candidate == cursor_type)
+                        .map_or(0.0, |(_, score)| *score);
+                    (raw as f64, *expected == cursor_type)
+                })
+                .collect();
+
+            if points.iter().any(|p| p.1) && points.iter().any(|p| !p.1) {
+                calibration.set_coefficients(cursor_type, Some(fit_logistic(&points)));
+            }
+        }
+        calibration
+    }
+}
+
+/// Index of a type's coefficients, its position in [`CommonCursorType::ALL`]
+fn slot(cursor_type: CommonCursorType) -> usize {
+    CommonCursorType::ALL
+        .iter()
+        .position(|&candidate| candidate == cursor_type)
+        .expect("every type is in ALL")
+}
+
+/// The calibration fitted on the fixture corpus
+impl Default for Calibration {
+    fn default() -> Self {
+        let mut calibration = Self::uncalibrated();
+        for &(cursor_type, slope, intercept) in FITTED {
+            calibration
+                .set_coefficients(cursor_type, Some(LogisticCoefficients { slope, intercept }));
+        }
+        calibration
+    }
+}
+
+/// Regularized logistic regression of the labels on the scores, by Newton's
+/// method. The penalty makes the loss strictly convex, so it converges from
+/// zero even when the classes are separable
+fn fit_logistic(points: &[(f64, bool)]) -> LogisticCoefficients {
+    let (mut slope, mut intercept) = (0.0f64, 0.0f64);
+
+    for _ in 0..FIT_ITERATIONS {
+        let mut grad_slope = FIT_REGULARIZATION * slope;
+        let mut grad_intercept = FIT_REGULARIZATION * intercept;
+        let (mut h_ss, mut h_si, mut h_ii) = (FIT_REGULARIZATION, 0.0, FIT_REGULARIZATION);
+
+        for &(x, label) in points {
+            let p = 1.0 / (1.0 + (-(slope * x + intercept)).exp());
+            let error = p - if label { 1.0 } else { 0.0 };
+            let weight = p * (1.0 - p);
+            grad_slope += error * x;
+            grad_intercept += error;
+            h_ss += weight * x * x;
+            h_si += weight * x;
+            h_ii += weight;
+        }
+
+        let det = h_ss * h_ii - h_si * h_si;
+        if det.abs() < f64::EPSILON {
+            break;
+        }
+        let step_slope = (h_ii * grad_slope - h_si * grad_intercept) / det;
+        let step_intercept = (h_ss * grad_intercept - h_si * grad_slope) / det;
+        slope -= step_slope;
+        intercept -= step_intercept;
+
+        if step_slope.abs().max(step_intercept.abs()) < 1e-9 {
+            break;
+        }
+    }
+
+    LogisticCoefficients {
+        slope: slope as f32,
+        intercept: intercept as f32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_orders_confidence_by_score() {
+        // Arrows score 0.7 and up as an arrow, other cursors less
+        let samples: Vec<_> = (0..20)
+            .map(|i| {
+                let score = i as f32 / 20.0;
+                let expected = if score >= 0.6 {
+                    CommonCursorType::Arrow
+                } else {
+                    CommonCursorType::IBeam
+                };
+                (expected, vec![(CommonCursorType::Arrow, score + 0.1)])
+            })
+            .collect();
+
+        let calibration = Calibration::fit(&samples);
+        let arrow = |raw| calibration.confidence(CommonCursorType::Arrow, raw);
+        assert!(arrow(0.5) < 0.5 && arrow(0.9) > 0.5);
+        assert!(arrow(0.6) < arrow(0.7) && arrow(0.7) < arrow(0.8));
+
+        // No I-beam ever got a score, and no crosshair was labeled
+        let ibeam = calibration.coefficients(CommonCursorType::IBeam).unwrap();
+        assert!(ibeam.confidence(0.0) > 0.5);
+        assert_eq!(calibration.coefficients(CommonCursorType::Crosshair), None);
+        assert_eq!(
+            calibration.confidence(CommonCursorType::Crosshair, 0.7),
+            0.7
+        );
+    }
+
+    #[test]
+    fn test_calibrate_reorders_ranking() {
+        let mut calibration = Calibration::uncalibrated();
+        calibration.set_coefficients(
+            CommonCursorType::PointingHand,
+            Some(LogisticCoefficients {
+                slope: 10.0,
+                intercept: -8.0,
+            }),
+        );
+
+        let mut ranked = vec![
+            (CommonCursorType::PointingHand, 0.7),
+            (CommonCursorType::Arrow, 0.6),
+        ];
+        calibration.calibrate(&mut ranked);
+        assert_eq!(ranked[0], (CommonCursorType::Arrow, 0.6));
+        assert_eq!(ranked[1].0, CommonCursorType::PointingHand);
+        assert!((ranked[1].1 - 0.2689).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,1284 @@
+use crate::cursor::detection::OPAQUE_ALPHA_THRESHOLD;
+use std::borrow::Cow;
+use std::cell::RefCell;
+
+/// Inclusive pixel bounds of a region within a cursor bitmap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComponentBounds {
+    pub min_x: u32,
+    pub min_y: u32,
+    pub max_x: u32,
+    pub max_y: u32,
+}
+
+impl ComponentBounds {
+    pub fn width(&self) -> u32 {
+        self.max_x - self.min_x + 1
+    }
+
+    pub fn height(&self) -> u32 {
+        self.max_y - self.min_y + 1
+    }
+
+    pub fn area(&self) -> u32 {
+        self.width() * self.height()
+    }
+
+    fn include(&mut self, x: u32, y: u32) {
+        self.min_x = self.min_x.min(x);
+        self.min_y = self.min_y.min(y);
+        self.max_x = self.max_x.max(x);
+        self.max_y = self.max_y.max(y);
+    }
+}
+
+/// A single 8-connected blob of opaque pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct CursorComponent {
+    pub bbox: ComponentBounds,
+    /// Number of opaque pixels in the component
+    pub area: u32,
+    /// Mean pixel position of the component
+    pub centroid: (f32, f32),
+}
+
+impl CursorComponent {
+    /// Fraction of the bounding box covered by the component
+    pub fn fill_ratio(&self) -> f32 {
+        self.area as f32 / self.bbox.area() as f32
+    }
+}
+
+/// Connected-component labeling of a cursor's alpha mask.
+///
+/// Components are sorted by area (largest first), so index 0 is usually the
+/// main cursor glyph and any further entries are badges or detached details.
+#[derive(Debug, Default)]
+pub struct ComponentMap {
+    width: u32,
+    height: u32,
+    /// Per-pixel component index + 1, 0 for transparent pixels
+    labels: Vec<u32>,
+    components: Vec<CursorComponent>,
+    /// Buffers of the labeling passes, kept so [`Self::relabel`] doesn't allocate
+    workspace: LabelWorkspace,
+    /// Flood fill buffers of [`Self::enclosed_hole_area`]
+    flood: RefCell<FloodFill>,
+}
+
+/// Union-find state and per-component statistics of a labeling pass
+#[derive(Debug, Default)]
+struct LabelWorkspace {
+    parent: Vec<u32>,
+    roots: Vec<Option<usize>>,
+    stats: Vec<(ComponentBounds, u32, u64, u64)>,
+    order: Vec<usize>,
+    remap: Vec<u32>,
+}
+
+#[derive(Debug, Default)]
+struct FloodFill {
+    reached: Vec<bool>,
+    stack: Vec<(u32, u32)>,
+}
+
+impl ComponentMap {
+    /// Label the opaque pixels of an RGBA buffer using a two-pass union-find scan
+    pub fn from_rgba(image_data: &[u8], width: u32, height: u32) -> Option<Self> {
+        let mut map = Self::default();
+        map.relabel(image_data, width, height).then_some(map)
+    }
+
+    /// Like [`Self::from_rgba`], replacing this map's labels and reusing its
+    /// buffers. Returns `false`, leaving the map empty, for a malformed buffer
+    pub fn relabel(&mut self, image_data: &[u8], width: u32, height: u32) -> bool {
+        if width == 0 || height == 0 || image_data.len() < width as usize * height as usize * 4 {
+            self.clear();
+            return false;
+        }
+        self.components.clear();
+
+        let is_opaque = |x: u32, y: u32| {
+            image_data[((y * width + x) * 4 + 3) as usize] > OPAQUE_ALPHA_THRESHOLD
+        };
+
+        let LabelWorkspace {
+            parent,
+            roots,
+            stats,
+            order,
+            remap,
+        } = &mut self.workspace;
+        let labels = &mut self.labels;
+        labels.clear();
+        labels.resize(width as usize * height as usize, 0);
+        // parent[0] is unused so provisional labels can start at 1
+        parent.clear();
+        parent.push(0);
+
+        for y in 0..height {
+            for x in 0..width {
+                if !is_opaque(x, y) {
+                    continue;
+                }
+
+                // Already visited neighbours in an 8-connected raster scan
+                let mut neighbours = [0u32; 4];
+                let mut count = 0;
+                let candidates = [
+                    (x.checked_sub(1), Some(y)),
+                    (x.checked_sub(1), y.checked_sub(1)),
+                    (Some(x), y.checked_sub(1)),
+                    (Some(x + 1).filter(|&nx| nx < width), y.checked_sub(1)),
+                ];
+                for (nx, ny) in candidates {
+                    if let (Some(nx), Some(ny)) = (nx, ny) {
+                        let label = labels[(ny * width + nx) as usize];
+                        if label != 0 {
+                            neighbours[count] = label;
+                            count += 1;
+                        }
+                    }
+                }
+
+                let idx = (y * width + x) as usize;
+                if count == 0 {
+                    let label = parent.len() as u32;
+                    parent.push(label);
+                    labels[idx] = label;
+                } else {
+                    let root = neighbours[..count]
+                        .iter()
+                        .map(|&l| find_root(parent, l))
+                        .min()
+                        .unwrap_or(neighbours[0]);
+                    for &label in &neighbours[..count] {
+                        let other = find_root(parent, label);
+                        parent[other as usize] = root;
+                    }
+                    labels[idx] = root;
+                }
+            }
+        }
+
+        // Second pass: resolve provisional labels and accumulate statistics
+        roots.clear();
+        roots.resize(parent.len(), None);
+        stats.clear();
+
+        for y in 0..height {
+            for x in 0..width {
+                let idx = (y * width + x) as usize;
+                if labels[idx] == 0 {
+                    continue;
+                }
+
+                let root = find_root(parent, labels[idx]) as usize;
+                let slot = *roots[root].get_or_insert_with(|| {
+                    stats.push((
+                        ComponentBounds {
+                            min_x: x,
+                            min_y: y,
+                            max_x: x,
+                            max_y: y,
+                        },
+                        0,
+                        0,
+                        0,
+                    ));
+                    stats.len() - 1
+                });
+
+                let entry = &mut stats[slot];
+                entry.0.include(x, y);
+                entry.1 += 1;
+                entry.2 += x as u64;
+                entry.3 += y as u64;
+                labels[idx] = slot as u32 + 1;
+            }
+        }
+
+        order.clear();
+        order.extend(0..stats.len());
+        // Unstable sorts don't allocate; ties keep scan order as a stable sort would
+        order.sort_unstable_by(|&a, &b| stats[b].1.cmp(&stats[a].1).then(a.cmp(&b)));
+
+        remap.clear();
+        remap.resize(stats.len(), 0);
+        for (new_index, &old_index) in order.iter().enumerate() {
+            remap[old_index] = new_index as u32 + 1;
+        }
+        for label in labels.iter_mut().filter(|l| **l != 0) {
+            *label = remap[*label as usize - 1];
+        }
+
+        self.components.extend(order.iter().map(|&i| {
+            let (bbox, area, sum_x, sum_y) = stats[i];
+            CursorComponent {
+                bbox,
+                area,
+                centroid: (sum_x as f32 / area as f32, sum_y as f32 / area as f32),
+            }
+        }));
+        self.width = width;
+        self.height = height;
+
+        true
+    }
+
+    /// Remove all components, keeping the buffers
+    pub fn clear(&mut self) {
+        self.width = 0;
+        self.height = 0;
+        self.labels.clear();
+        self.components.clear();
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn components(&self) -> &[CursorComponent] {
+        &self.components
+    }
+
+    /// Index into `components()` of the blob covering a pixel, if any
+    pub fn component_at(&self, x: u32, y: u32) -> Option<usize> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        match self.labels[(y * self.width + x) as usize] {
+            0 => None,
+            label => Some(label as usize - 1),
+        }
+    }
+
+    /// Number of transparent pixels inside a component's bounding box that are
+    /// fully enclosed by it (i.e. not reachable from the box edge)
+    pub fn enclosed_hole_area(&self, index: usize) -> u32 {
+        let Some(component) = self.components.get(index) else {
+            return 0;
+        };
+        let bbox = component.bbox;
+        let (bw, bh) = (bbox.width(), bbox.height());
+
+        let inside =
+            |x: u32, y: u32| self.component_at(bbox.min_x + x, bbox.min_y + y) == Some(index);
+
+        // Flood fill the background from the box edge, whatever is left is a hole
+        let mut flood = self.flood.borrow_mut();
+        let FloodFill { reached, stack } = &mut *flood;
+        reached.clear();
+        reached.resize((bw * bh) as usize, false);
+        stack.clear();
+        for x in 0..bw {
+            stack.push((x, 0));
+            stack.push((x, bh - 1));
+        }
+        for y in 0..bh {
+            stack.push((0, y));
+            stack.push((bw - 1, y));
+        }
+
+        while let Some((x, y)) = stack.pop() {
+            let idx = (y * bw + x) as usize;
+            if reached[idx] || inside(x, y) {
+                continue;
+            }
+            reached[idx] = true;
+
+            if x > 0 {
+                stack.push((x - 1, y));
+            }
+            if x + 1 < bw {
+                stack.push((x + 1, y));
+            }
+            if y > 0 {
+                stack.push((x, y - 1));
+            }
+            if y + 1 < bh {
+                stack.push((x, y + 1));
+            }
+        }
+
+        (0..bh)
+            .flat_map(|y| (0..bw).map(move |x| (x, y)))
+            .filter(|&(x, y)| !reached[(y * bw + x) as usize] && !inside(x, y))
+            .count() as u32
+    }
+
+    /// Fraction of each corner cell (a third of the box per side) covered by a component,
+    /// in the order top-left, top-right, bottom-left, bottom-right
+    pub fn corner_occupancy(&self, index: usize) -> [f32; 4] {
+        let Some(component) = self.components.get(index) else {
+            return [0.0; 4];
+        };
+        let bbox = component.bbox;
+        let cell_w = (bbox.width() / 3).max(1);
+        let cell_h = (bbox.height() / 3).max(1);
+
+        let origins = [
+            (bbox.min_x, bbox.min_y),
+            (bbox.max_x + 1 - cell_w, bbox.min_y),
+            (bbox.min_x, bbox.max_y + 1 - cell_h),
+            (bbox.max_x + 1 - cell_w, bbox.max_y + 1 - cell_h),
+        ];
+
+        origins.map(|(ox, oy)| {
+            let covered = (oy..oy + cell_h)
+                .flat_map(|y| (ox..ox + cell_w).map(move |x| (x, y)))
+                .filter(|&(x, y)| self.component_at(x, y) == Some(index))
+                .count();
+            covered as f32 / (cell_w * cell_h) as f32
+        })
+    }
+
+    /// Whether a component looks the same mirrored left to right: the share
+    /// of its pixels whose mirror image within its bounding box is part of it
+    pub fn horizontal_symmetry(&self, index: usize) -> f32 {
+        let Some(component) = self.components.get(index) else {
+            return 0.0;
+        };
+        let bbox = component.bbox;
+        let kept = (bbox.min_y..=bbox.max_y)
+            .flat_map(|y| (bbox.min_x..=bbox.max_x).map(move |x| (x, y)))
+            .filter(|&(x, y)| {
+                self.component_at(x, y) == Some(index)
+                    && self.component_at(bbox.min_x + bbox.max_x - x, y) == Some(index)
+            })
+            .count();
+        kept as f32 / component.area.max(1) as f32
+    }
+
+    /// Pixels of a component in row `y` of the image
+    pub fn row_width(&self, index: usize, y: u32) -> u32 {
+        let Some(component) = self.components.get(index) else {
+            return 0;
+        };
+        (component.bbox.min_x..=component.bbox.max_x)
+            .filter(|&x| self.component_at(x, y) == Some(index))
+            .count() as u32
+    }
+}
+
+fn find_root(parent: &mut [u32], mut label: u32) -> u32 {
+    while parent[label as usize] != label {
+        // Path halving keeps the trees shallow without recursion
+        let grandparent = parent[parent[label as usize] as usize];
+        parent[label as usize] = grandparent;
+        label = grandparent;
+    }
+    label
+}
+
+/// Shape of the secondary glyph drawn next to an arrow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BadgeShape {
+    /// A plus sign
+    Plus,
+    /// A curved arrow
+    CurvedArrow,
+    /// A question mark (hook plus a detached dot)
+    QuestionMark,
+    /// A ring / spinner
+    Ring,
+    /// A solid menu panel
+    Menu,
+    /// An arrow pointing down, as in a download icon
+    DownArrow,
+}
+
+/// Split a labeled cursor into an arrow body and a badge, and classify the badge.
+///
+/// The body is the largest component and must start in the top-left quadrant with
+/// its tip on the left; every other sizeable component below and to the right of
+/// the body's centroid is treated as part of the badge.
+pub fn classify_badge(map: &ComponentMap) -> Option<BadgeShape> {
+    let body = map.components().first()?;
+    if body.bbox.min_x >= map.width() / 2
+        || body.bbox.min_y >= map.height() / 2
+        || !tip_on_left(map, 0)
+    {
+        return None;
+    }
+
+    let significant = badge_components(map)?;
+    let mut badge = significant.clone();
+    match (badge.next(), badge.next(), badge.next()) {
+        (Some((single, _)), None, _) => classify_single_badge(map, single),
+        (Some((_, hook)), Some((_, dot)), None) => {
+            // A question mark's dot sits directly underneath its hook
+            let overlaps_x = dot.bbox.min_x <= hook.bbox.max_x && dot.bbox.max_x >= hook.bbox.min_x;
+            (overlaps_x && dot.bbox.min_y > hook.bbox.max_y && dot.area * 2 < hook.area)
+                .then_some(BadgeShape::QuestionMark)
+        }
+        // Windows 11 draws its busy indicator as a ring of separate dots
+        _ => dotted_ring(significant.map(|(_, c)| c)).then_some(BadgeShape::Ring),
+    }
+}
+
+/// Whether a spinner is drawn bottom-right of the largest component, whatever
+/// that is. Windows shows its "app starting" state this way on top of the
+/// current cursor, so the body needn't be an arrow as for [`classify_badge`]
+pub fn has_spinner_badge(map: &ComponentMap) -> bool {
+    let Some(significant) = badge_components(map) else {
+        return false;
+    };
+    let mut badge = significant.clone();
+    match (badge.next(), badge.next()) {
+        (Some((single, _)), None) => is_ring(map, single),
+        _ => dotted_ring(significant.map(|(_, c)| c)),
+    }
+}
+
+/// The sizeable components besides the largest, the body, if there are any
+/// and they could be a badge: all below and to the right of the body's
+/// centroid, and smaller together than three quarters of it
+fn badge_components(
+    map: &ComponentMap,
+) -> Option<impl Iterator<Item = (usize, &CursorComponent)> + Clone> {
+    let components = map.components();
+    let body = components.first()?;
+    if body.area < 20 {
+        return None;
+    }
+
+    // Anything smaller than this is treated as anti-aliasing noise
+    let min_area = (body.area / 50).max(2);
+    let significant = components
+        .iter()
+        .enumerate()
+        .skip(1)
+        .filter(move |(_, c)| c.area >= min_area);
+
+    // Badges only sit bottom-right of the body; blobs in other directions
+    // mean a symmetric shape, e.g. a four-way resize cursor split into pieces
+    if significant
+        .clone()
+        .any(|(_, c)| c.centroid.0 <= body.centroid.0 || c.centroid.1 <= body.centroid.1)
+    {
+        return None;
+    }
+
+    let badge_area: u32 = significant.clone().map(|(_, c)| c.area).sum();
+    (badge_area > 0 && badge_area * 4 <= body.area * 3).then_some(significant)
+}
+
+/// Fewest dots a dotted ring badge is drawn with
+const MIN_RING_DOTS: usize = 5;
+
+/// Whether the components are similar small dots spaced around a circle
+fn dotted_ring<'a>(dots: impl Iterator<Item = &'a CursorComponent> + Clone) -> bool {
+    let count = dots.clone().count();
+    if count < MIN_RING_DOTS {
+        return false;
+    }
+
+    let (min_area, max_area) = dots.clone().fold((u32::MAX, 0), |(min, max), dot| {
+        (min.min(dot.area), max.max(dot.area))
+    });
+    if max_area > min_area * 3 {
+        return false;
+    }
+
+    let count = count as f32;
+    let center = dots.clone().fold((0.0, 0.0), |(x, y), dot| {
+        (x + dot.centroid.0 / count, y + dot.centroid.1 / count)
+    });
+    let polar = dots.map(move |dot| {
+        let (dx, dy) = (dot.centroid.0 - center.0, dot.centroid.1 - center.1);
+        (
+            dx.hypot(dy),
+            dy.atan2(dx),
+            dot.bbox.width().max(dot.bbox.height()),
+        )
+    });
+
+    // All on one circle, which is wider than the dots themselves
+    let radius = polar.clone().map(|(r, _, _)| r).sum::<f32>() / count;
+    let dot_size = polar.clone().map(|(_, _, size)| size).max().unwrap_or(0) as f32;
+    if radius < dot_size
+        || polar
+            .clone()
+            .any(|(r, _, _)| (r - radius).abs() > radius * 0.25)
+    {
+        return false;
+    }
+
+    // Spread around the whole circle, not bunched into an arc: the gap from
+    // each dot to the next one counterclockwise stays well under a third of it
+    let largest_gap = polar
+        .clone()
+        .map(|(_, angle, _)| {
+            polar
+                .clone()
+                .map(|(_, other, _)| (other - angle).rem_euclid(std::f32::consts::TAU))
+                .filter(|&gap| gap > 0.0)
+                .fold(std::f32::consts::TAU, f32::min)
+        })
+        .fold(0.0, f32::max);
+
+    largest_gap < std::f32::consts::TAU / 3.0
+}
+
+/// Whether a component encloses a hole of at least an eighth of its bounds
+fn is_ring(map: &ComponentMap, index: usize) -> bool {
+    map.enclosed_hole_area(index) * 8 >= map.components()[index].bbox.area()
+}
+
+fn classify_single_badge(map: &ComponentMap, index: usize) -> Option<BadgeShape> {
+    let component = &map.components()[index];
+    let fill = component.fill_ratio();
+
+    if is_ring(map, index) {
+        return Some(BadgeShape::Ring);
+    }
+
+    if fill >= 0.85 {
+        return Some(BadgeShape::Menu);
+    }
+
+    let corners = map.corner_occupancy(index);
+    if is_down_arrow(map, index, corners) {
+        return Some(BadgeShape::DownArrow);
+    }
+    if corners.iter().all(|&c| c < 0.2) && fill > 0.35 {
+        return Some(BadgeShape::Plus);
+    }
+
+    (0.15..0.65)
+        .contains(&fill)
+        .then_some(BadgeShape::CurvedArrow)
+}
+
+/// Whether a badge is a shaft standing on a downward arrowhead. Like a plus
+/// it leaves the top corners of its box empty and looks the same mirrored
+/// left to right, but below its widest row the head tapers to a point while
+/// mostly staying wider than the shaft above, where a plus is as narrow below
+/// its bar as above it. Medians, so the anti-aliased rows next to a plus's
+/// bar don't count
+fn is_down_arrow(map: &ComponentMap, index: usize, corners: [f32; 4]) -> bool {
+    let [top_left, top_right, _, _] = corners;
+    if top_left >= 0.2 || top_right >= 0.2 || map.horizontal_symmetry(index) < 0.8 {
+        return false;
+    }
+
+    let bbox = map.components()[index].bbox;
+    let rows = || (bbox.min_y..bbox.max_y + 1).map(|y| map.row_width(index, y));
+    let widest = rows().max().unwrap_or(0);
+    let (Some(first), Some(last)) = (
+        rows().position(|w| w == widest),
+        rows().rposition(|w| w == widest),
+    ) else {
+        return false;
+    };
+    let shaft = || rows().take(first);
+    let head = || rows().skip(last + 1);
+
+    first > 0 && head().count() >= 2 && median(head) > median(shaft) * 1.2
+}
+
+/// Median of some row widths, the upper one for an even count
+fn median<I: Iterator<Item = u32>>(rows: impl Fn() -> I) -> f32 {
+    let half = rows().count() / 2;
+    rows()
+        .filter(|&w| rows().filter(|&other| other <= w).count() > half)
+        .min()
+        .unwrap_or(0) as f32
+}
+
+/// Direction a scroll cursor's arrowheads point in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollAxis {
+    /// Up and down
+    Vertical,
+    /// Left and right
+    Horizontal,
+}
+
+/// Recognize a single-axis scroll cursor: a pivot dot with an outward-pointing
+/// arrowhead on either side of it, all three detached from each other.
+///
+/// Resize cursors join their arrowheads with a shaft and so are a single
+/// component, which is what tells the two apart.
+pub fn classify_scroll(map: &ComponentMap) -> Option<ScrollAxis> {
+    let components = map.components();
+    let largest = components.first()?;
+
+    // Anything smaller than this is treated as anti-aliasing noise
+    let min_area = (largest.area / 50).max(2);
+    let mut significant = components.iter().filter(|c| c.area >= min_area);
+    // Sorted by area, and the dot is the smallest of the three
+    let (Some(first), Some(second), Some(dot), None) = (
+        significant.next(),
+        significant.next(),
+        significant.next(),
+        significant.next(),
+    ) else {
+        return None;
+    };
+
+    // Both arrowheads are the same shape, the dot is clearly smaller and round
+    let dot_aspect = dot.bbox.width() as f32 / dot.bbox.height() as f32;
+    if second.area * 3 < first.area * 2
+        || dot.area * 10 > second.area * 9
+        || !(0.6..=1.66).contains(&dot_aspect)
+        || dot.fill_ratio() < 0.6
+    {
+        return None;
+    }
+
+    let (dx, dy) = (
+        second.centroid.0 - first.centroid.0,
+        second.centroid.1 - first.centroid.1,
+    );
+    let axis = if dy.abs() > dx.abs() * 3.0 {
+        ScrollAxis::Vertical
+    } else if dx.abs() > dy.abs() * 3.0 {
+        ScrollAxis::Horizontal
+    } else {
+        return None;
+    };
+
+    // The dot pivots halfway between the heads
+    let midpoint = (
+        (first.centroid.0 + second.centroid.0) / 2.0,
+        (first.centroid.1 + second.centroid.1) / 2.0,
+    );
+    let spacing = dx.hypot(dy);
+    if (dot.centroid.0 - midpoint.0).hypot(dot.centroid.1 - midpoint.1) > spacing * 0.15 {
+        return None;
+    }
+
+    // A triangle's centroid sits a third of the way up from its base, so an
+    // outward-pointing head has its centroid on the side facing the dot
+    let facing_dot = |head: &CursorComponent| match axis {
+        ScrollAxis::Vertical => {
+            let offset =
+                (head.centroid.1 - head.bbox.min_y as f32 + 0.5) / head.bbox.height() as f32;
+            if head.centroid.1 < dot.centroid.1 {
+                offset > 0.55
+            } else {
+                offset < 0.45
+            }
+        }
+        ScrollAxis::Horizontal => {
+            let offset =
+                (head.centroid.0 - head.bbox.min_x as f32 + 0.5) / head.bbox.width() as f32;
+            if head.centroid.0 < dot.centroid.0 {
+                offset > 0.55
+            } else {
+                offset < 0.45
+            }
+        }
+    };
+
+    (facing_dot(first) && facing_dot(second)).then_some(axis)
+}
+
+/// Points sampled around the ring of a slashed circle
+const RING_SAMPLES: usize = 32;
+
+/// Recognize a not-allowed cursor: a ring crossed by a top-left to
+/// bottom-right slash, both drawn in the same color.
+///
+/// Only the ring and the slash are sampled, so whatever shows through the
+/// circle doesn't matter, even a dragged item's thumbnail that touches them
+/// and merges into one component. Inside the circle the only requirement is
+/// that the halves either side of the slash aren't drawn in the ring's color
+/// too, which is what tells it apart from a filled disc.
+pub fn is_slashed_circle(map: &ComponentMap, image_data: &[u8]) -> bool {
+    let components = map.components();
+    let Some(body) = components.first() else {
+        return false;
+    };
+    let bbox = body.bbox;
+    let (width, height) = (bbox.width(), bbox.height());
+    if width.min(height) < 12 || width.abs_diff(height) * 8 > width.max(height) {
+        return false;
+    }
+
+    let center = (
+        (bbox.min_x + bbox.max_x + 1) as f32 / 2.0,
+        (bbox.min_y + bbox.max_y + 1) as f32 / 2.0,
+    );
+    let radius = (width + height) as f32 / 4.0;
+
+    // Detached blobs are fine inside the circle, elsewhere they make it
+    // some other cursor. Anything smaller than this is anti-aliasing noise
+    let min_area = (body.area / 50).max(2);
+    if components.iter().skip(1).any(|c| {
+        c.area >= min_area
+            && (c.centroid.0 + 0.5 - center.0).hypot(c.centroid.1 + 0.5 - center.1) > radius
+    }) {
+        return false;
+    }
+
+    // Color of the opaque pixel at an offset from the center
+    let color_at = |dx: f32, dy: f32| {
+        let (x, y) = ((center.0 + dx).floor(), (center.1 + dy).floor());
+        if x < 0.0 || y < 0.0 || x >= map.width() as f32 || y >= map.height() as f32 {
+            return None;
+        }
+        let i = ((y as u32 * map.width() + x as u32) * 4) as usize;
+        let pixel = &image_data[i..i + 4];
+        (pixel[3] >= OPAQUE_ALPHA_THRESHOLD).then(|| [pixel[0], pixel[1], pixel[2]])
+    };
+    let ring_point = |k: usize| {
+        let angle = k as f32 * std::f32::consts::TAU / RING_SAMPLES as f32;
+        let distance = radius * 0.8;
+        (angle.cos() * distance, angle.sin() * distance)
+    };
+
+    let mut drawn = 0u32;
+    let mut sum = [0u32; 3];
+    for (dx, dy) in (0..RING_SAMPLES).map(ring_point) {
+        if let Some(color) = color_at(dx, dy) {
+            drawn += 1;
+            for (total, channel) in sum.iter_mut().zip(color) {
+                *total += channel as u32;
+            }
+        }
+    }
+    if (drawn as usize) * 10 < RING_SAMPLES * 9 {
+        return false;
+    }
+    let ring_color = sum.map(|total| (total / drawn) as u8);
+    let is_ring_colored = |color: Option<[u8; 3]>| {
+        color.is_some_and(|color| {
+            color
+                .iter()
+                .zip(ring_color)
+                .all(|(&channel, ring)| channel.abs_diff(ring) <= 64)
+        })
+    };
+
+    let ring_matches = (0..RING_SAMPLES)
+        .map(ring_point)
+        .filter(|&(dx, dy)| is_ring_colored(color_at(dx, dy)))
+        .count();
+    if ring_matches * 10 < RING_SAMPLES * 9 {
+        return false;
+    }
+
+    // Along the slash, which runs through the center on the main diagonal
+    let diagonal = std::f32::consts::FRAC_1_SQRT_2;
+    let slash_matches = (-4..=4)
+        .map(|step| step as f32 * radius * 0.125 * diagonal)
+        .filter(|&offset| is_ring_colored(color_at(offset, offset)))
+        .count();
+    if slash_matches < 8 {
+        return false;
+    }
+
+    // Either side of the slash, clear of the ring
+    let beside_slash = [0.35, 0.45, 0.55]
+        .into_iter()
+        .map(|distance| distance * radius * diagonal)
+        .flat_map(|offset| [(offset, -offset), (-offset, offset)])
+        .filter(|&(dx, dy)| is_ring_colored(color_at(dx, dy)))
+        .count();
+    beside_slash <= 1
+}
+
+/// Angular sectors a reticle's ring has to reach into to count as closed
+const RETICLE_SECTORS: usize = 24;
+
+/// Recognize an aiming reticle: a thin, closed ring around a small center
+/// dot, as game overlays draw, with nothing else around it.
+///
+/// Every pixel of the ring has to lie in an outer band of its bounding
+/// circle. Cross lines through the ring join it into one blob reaching the
+/// center, which is what tells it apart from a crosshair with a circle, and
+/// the dot tells it apart from a bare spinner ring.
+pub fn is_reticle(map: &ComponentMap) -> bool {
+    let components = map.components();
+    let Some(body) = components.first() else {
+        return false;
+    };
+    let bbox = body.bbox;
+    let (width, height) = (bbox.width(), bbox.height());
+    if width.min(height) < 10 || width.abs_diff(height) * 8 > width.max(height) {
+        return false;
+    }
+
+    let center = (
+        (bbox.min_x + bbox.max_x + 1) as f32 / 2.0,
+        (bbox.min_y + bbox.max_y + 1) as f32 / 2.0,
+    );
+    let radius = (width + height) as f32 / 4.0;
+
+    let mut sectors = [false; RETICLE_SECTORS];
+    for y in bbox.min_y..=bbox.max_y {
+        for x in bbox.min_x..=bbox.max_x {
+            if map.component_at(x, y) != Some(0) {
+                continue;
+            }
+            let (dx, dy) = (x as f32 + 0.5 - center.0, y as f32 + 0.5 - center.1);
+            if dx.hypot(dy) < radius * 0.6 {
+                return false;
+            }
+            let turn = (dy.atan2(dx) / std::f32::consts::TAU).rem_euclid(1.0);
+            sectors[((turn * RETICLE_SECTORS as f32) as usize).min(RETICLE_SECTORS - 1)] = true;
+        }
+    }
+    if !sectors.iter().all(|&covered| covered) {
+        return false;
+    }
+
+    // Anything smaller than this is anti-aliasing noise
+    let min_area = (body.area / 50).max(2);
+    let mut details = components.iter().skip(1).filter(|c| c.area >= min_area);
+    let (Some(dot), None) = (details.next(), details.next()) else {
+        return false;
+    };
+
+    let offset = (dot.centroid.0 + 0.5 - center.0).hypot(dot.centroid.1 + 0.5 - center.1);
+    let dot_side = dot.bbox.width().max(dot.bbox.height()) as f32;
+    offset <= radius * 0.15 && dot_side <= radius * 0.8 && dot.fill_ratio() >= 0.5
+}
+
+/// Most blobs a glyph attached to a cursor is drawn with, e.g. the digits of
+/// a coordinate readout
+const MAX_GLYPH_BLOBS: usize = 8;
+
+/// Whether the cursor is one large shape plus a small detached glyph, like the
+/// coordinate readouts and tool hints CAD apps draw next to their crosshairs,
+/// or the link hint next to an I-beam over selectable link text.
+///
+/// The glyph's blobs together cover at most half as many pixels as the shape and
+/// each is under half the shape's longest side on both axes, since a link
+/// hint is easily half as wide as the I-beam it's attached to.
+pub fn has_attached_glyph(map: &ComponentMap) -> bool {
+    let components = map.components();
+    let Some(body) = components.first() else {
+        return false;
+    };
+
+    // Anything smaller than this is treated as anti-aliasing noise
+    let min_area = (body.area / 50).max(2);
+    let glyph = || components.iter().skip(1).filter(|c| c.area >= min_area);
+
+    let blobs = glyph().count();
+    let glyph_area: u32 = glyph().map(|c| c.area).sum();
+    let body_side = body.bbox.width().max(body.bbox.height());
+    (1..=MAX_GLYPH_BLOBS).contains(&blobs)
+        && glyph_area * 2 <= body.area
+        && glyph().all(|c| c.bbox.width() * 2 < body_side && c.bbox.height() * 2 < body_side)
+}
+
+/// Fewest blobs a text readout is drawn with. Badges are a single compact
+/// blob, or the hook and dot of a question mark
+const MIN_READOUT_BLOBS: usize = 3;
+
+/// Whether the blobs bottom-right of the largest component read as a line
+/// or two of text, like the coordinates some design tools print next to
+/// their arrow in precision mode: several glyphs of about the same height,
+/// each sharing its rows with another, and not the dots of a spinner ring.
+pub fn has_text_readout(map: &ComponentMap) -> bool {
+    let Some(glyphs) = badge_components(map) else {
+        return false;
+    };
+
+    let blobs = glyphs.clone().count();
+    let tallest = glyphs
+        .clone()
+        .map(|(_, c)| c.bbox.height())
+        .max()
+        .unwrap_or(0);
+    let shares_rows = |(index, glyph): (usize, &CursorComponent)| {
+        glyphs.clone().any(|(other_index, other)| {
+            other_index != index
+                && other.bbox.min_y <= glyph.bbox.max_y
+                && other.bbox.max_y >= glyph.bbox.min_y
+        })
+    };
+
+    (MIN_READOUT_BLOBS..=MAX_GLYPH_BLOBS).contains(&blobs)
+        && glyphs.clone().all(|(_, c)| c.bbox.height() * 2 >= tallest)
+        && glyphs.clone().all(shares_rows)
+        && !dotted_ring(glyphs.map(|(_, c)| c))
+}
+
+/// Fewest opaque pixels each of two stacked shapes needs to be classified alone
+const MIN_STACKED_AREA: u32 = 16;
+
+/// Whether the mask is two comparably sized shapes and nothing else of note,
+/// which is what a capture taken while the OS swaps cursors looks like when
+/// both the old and the new one made it into the bitmap. The shapes are
+/// components 0 and 1, the smaller covering at least half as many pixels as
+/// the larger, so a badge or detached detail never passes for a second cursor
+pub fn is_stacked(map: &ComponentMap) -> bool {
+    let components = map.components();
+    let Some(first) = components.first() else {
+        return false;
+    };
+
+    let min_area = (first.area / 50).max(2);
+    let mut rest = components.iter().skip(1).filter(|c| c.area >= min_area);
+    let (Some(second), None) = (rest.next(), rest.next()) else {
+        return false;
+    };
+    second.area >= MIN_STACKED_AREA && second.area * 2 >= first.area
+}
+
+/// Copy of the image with only a component's pixels left opaque, e.g. to
+/// measure a shape without the glyph attached to it
+pub fn isolate_component(
+    image_data: &[u8],
+    map: &ComponentMap,
+    index: usize,
+    isolated: &mut Vec<u8>,
+) {
+    let len = (map.width() * map.height() * 4) as usize;
+    isolated.clear();
+    isolated.extend_from_slice(&image_data[..len]);
+
+    for (i, pixel) in isolated.chunks_exact_mut(4).enumerate() {
+        let (x, y) = (i as u32 % map.width(), i as u32 / map.width());
+        if map.component_at(x, y) != Some(index) {
+            pixel[3] = 0;
+        }
+    }
+}
+
+/// Like [`isolate_component`], cropped to the component's bounds with a
+/// transparent pixel of margin all around, so the shape measures the same
+/// wherever it sits in the image. Returns the crop's width and height
+pub fn crop_component(
+    image_data: &[u8],
+    map: &ComponentMap,
+    index: usize,
+    cropped: &mut Vec<u8>,
+) -> (u32, u32) {
+    let bbox = map.components()[index].bbox;
+    let (width, height) = (bbox.width() + 2, bbox.height() + 2);
+    cropped.clear();
+    cropped.resize(width as usize * height as usize * 4, 0);
+
+    for y in bbox.min_y..=bbox.max_y {
+        for x in bbox.min_x..=bbox.max_x {
+            if map.component_at(x, y) != Some(index) {
+                continue;
+            }
+            let src = ((y * map.width() + x) * 4) as usize;
+            let dst = (((y - bbox.min_y + 1) * width + x - bbox.min_x + 1) * 4) as usize;
+            cropped[dst..dst + 4].copy_from_slice(&image_data[src..src + 4]);
+        }
+    }
+    (width, height)
+}
+
+/// Share of its bounding box a tooltip covers at least: a filled box, text
+/// and all where the text is drawn on it
+const MIN_TOOLTIP_FILL: f32 = 0.8;
+
+/// Fewest transparent pixels between a tooltip and the cursor it belongs to
+const MIN_TOOLTIP_GAP: u32 = 2;
+
+/// Most times wider than tall a tooltip is, past which a filled box is a
+/// stroke, such as one arm of a crosshair with a gap at its center
+const MAX_TOOLTIP_ASPECT: u32 = 4;
+
+/// A cursor bitmap with its detached tooltips cleared, see [`strip_tooltips`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrippedCursor<'a> {
+    /// The image with the tooltips' pixels made fully transparent, borrowed
+    /// when there was no tooltip
+    pub image_data: Cow<'a, [u8]>,
+    /// Bounds of the opaque pixels left, `None` for a blank image
+    pub cursor_bounds: Option<ComponentBounds>,
+    /// Bounds of all that was cleared, `None` when nothing was
+    pub stripped_bounds: Option<ComponentBounds>,
+}
+
+/// Clear the tooltips some apps draw into the cursor bitmap itself, so a
+/// label box isn't composited or scored as part of the cursor.
+///
+/// The component nearest `anchor`, the hotspot's pixel when it's known, is
+/// the cursor. Any other component that is a filled box wider than it is
+/// tall but no stroke, at least a quarter the cursor's area and
+/// [`MIN_TOOLTIP_GAP`] pixels away from it is a tooltip, and goes together
+/// with its anti-aliased edge. Badges, glyphs and readouts are too small, too
+/// close or too sparse to be taken for one, a crosshair's arms too thin.
+pub fn strip_tooltips(
+    image_data: &[u8],
+    width: u32,
+    height: u32,
+    anchor: (u32, u32),
+) -> StrippedCursor<'_> {
+    let unchanged = |cursor_bounds| StrippedCursor {
+        image_data: Cow::Borrowed(image_data),
+        cursor_bounds,
+        stripped_bounds: None,
+    };
+    let Some(map) = ComponentMap::from_rgba(image_data, width, height) else {
+        return unchanged(None);
+    };
+    let Some(cursor) = nearest_component(&map, anchor) else {
+        return unchanged(None);
+    };
+    let components = map.components();
+    let is_tooltip = |index: &usize| is_tooltip(&map, cursor, *index);
+
+    let union = |bounds: Option<ComponentBounds>, bbox: &ComponentBounds| {
+        let mut bounds = bounds.unwrap_or(*bbox);
+        bounds.include(bbox.min_x, bbox.min_y);
+        bounds.include(bbox.max_x, bbox.max_y);
+        Some(bounds)
+    };
+    let cursor_bounds = (0..components.len())
+        .filter(|index| !is_tooltip(index))
+        .fold(None, |bounds, index| union(bounds, &components[index].bbox));
+    if !(0..components.len()).any(|index| is_tooltip(&index)) {
+        return unchanged(cursor_bounds);
+    }
+
+    let mut stripped = image_data[..width as usize * height as usize * 4].to_vec();
+    let mut stripped_bounds = None;
+    for index in (0..components.len()).filter(is_tooltip) {
+        // One pixel out, for the edge pixels too faint to be labeled
+        let bbox = components[index].bbox;
+        let edge = ComponentBounds {
+            min_x: bbox.min_x.saturating_sub(1),
+            min_y: bbox.min_y.saturating_sub(1),
+            max_x: (bbox.max_x + 1).min(width - 1),
+            max_y: (bbox.max_y + 1).min(height - 1),
+        };
+        for y in edge.min_y..=edge.max_y {
+            for x in edge.min_x..=edge.max_x {
+                if map.component_at(x, y).is_some_and(|other| other != index) {
+                    continue;
+                }
+                let i = (y as usize * width as usize + x as usize) * 4;
+                stripped[i..i + 4].fill(0);
+            }
+        }
+        stripped_bounds = union(stripped_bounds, &edge);
+    }
+
+    StrippedCursor {
+        image_data: Cow::Owned(stripped),
+        cursor_bounds,
+        stripped_bounds,
+    }
+}
+
+/// The cursor component of an image with a tooltip drawn next to it, see
+/// [`strip_tooltips`], `None` when there is no tooltip. Without a hotspot
+/// the cursor is the component nearest the top-left corner, where captured
+/// cursors start and their tooltips don't
+pub fn tooltip_cursor(map: &ComponentMap) -> Option<usize> {
+    let cursor = nearest_component(map, (0, 0))?;
+    (0..map.components().len())
+        .any(|index| is_tooltip(map, cursor, index))
+        .then_some(cursor)
+}
+
+/// Index of the component nearest a pixel, the largest of equally near ones
+fn nearest_component(map: &ComponentMap, anchor: (u32, u32)) -> Option<usize> {
+    let components = map.components();
+    (0..components.len()).min_by_key(|&index| distance_to(&components[index].bbox, anchor))
+}
+
+/// Whether a component is a tooltip drawn next to the `cursor` component
+fn is_tooltip(map: &ComponentMap, cursor: usize, index: usize) -> bool {
+    let (component, cursor_component) = (&map.components()[index], &map.components()[cursor]);
+    index != cursor
+        && component.fill_ratio() >= MIN_TOOLTIP_FILL
+        && component.bbox.width() * 2 >= component.bbox.height() * 3
+        && component.bbox.width() <= component.bbox.height() * MAX_TOOLTIP_ASPECT
+        && component.area * 4 >= cursor_component.area
+        && gap_between(&component.bbox, &cursor_component.bbox) >= MIN_TOOLTIP_GAP
+}
+
+/// Squared distance from a pixel to the nearest pixel of `bbox`
+fn distance_to(bbox: &ComponentBounds, (x, y): (u32, u32)) -> u32 {
+    let dx = bbox
+        .min_x
+        .saturating_sub(x)
+        .max(x.saturating_sub(bbox.max_x));
+    let dy = bbox
+        .min_y
+        .saturating_sub(y)
+        .max(y.saturating_sub(bbox.max_y));
+    dx * dx + dy * dy
+}
+
+/// Transparent pixels between two boxes, along the axis they're furthest
+/// apart on. 0 for boxes that touch or overlap
+fn gap_between(a: &ComponentBounds, b: &ComponentBounds) -> u32 {
+    let apart = |a_min: u32, a_max: u32, b_min: u32, b_max: u32| {
+        a_min
+            .saturating_sub(b_max)
+            .max(b_min.saturating_sub(a_max))
+            .saturating_sub(1)
+    };
+    apart(a.min_x, a.max_x, b.min_x, b.max_x).max(apart(a.min_y, a.max_y, b.min_y, b.max_y))
+}
+
+/// Upper bound on refinement steps after the initial algebraic circle fit
+const RING_FIT_ITERATIONS: usize = 20;
+
+/// Circle fitted through the pixels of a ring-shaped component
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RingFit {
+    pub center: (f32, f32),
+    pub radius: f32,
+    /// Share of the circumference covered by the component, in 0..=1
+    pub angular_coverage: f32,
+}
+
+/// Fit a circle through a component and measure how much of it is drawn.
+///
+/// Uses a least-squares circle fit, which also works for partial arcs, then
+/// checks the radial distribution: a ring's pixels all sit within a thin band
+/// around the fitted radius. Returns `None` for anything that isn't ring-like.
+pub fn fit_ring(map: &ComponentMap, index: usize) -> Option<RingFit> {
+    let component = map.components().get(index)?;
+    let bbox = component.bbox;
+    let pixels: Vec<(f64, f64)> = (bbox.min_y..=bbox.max_y)
+        .flat_map(|y| (bbox.min_x..=bbox.max_x).map(move |x| (x, y)))
+        .filter(|&(x, y)| map.component_at(x, y) == Some(index))
+        .map(|(x, y)| (x as f64 + 0.5, y as f64 + 0.5))
+        .collect();
+    if pixels.len() < 8 {
+        return None;
+    }
+
+    // Work relative to the centroid to keep the normal equations well conditioned
+    let (mean_x, mean_y) = (
+        component.centroid.0 as f64 + 0.5,
+        component.centroid.1 as f64 + 0.5,
+    );
+    let pixels: Vec<(f64, f64)> = pixels
+        .into_iter()
+        .map(|(x, y)| (x - mean_x, y - mean_y))
+        .collect();
+    let n = pixels.len() as f64;
+
+    // Algebraic fit of x^2 + y^2 + D x + E y + F = 0 as the starting point
+    let mut a = [[0.0; 3]; 3];
+    let mut b = [0.0; 3];
+    for &(x, y) in &pixels {
+        let row = [x, y, 1.0];
+        for i in 0..3 {
+            for j in 0..3 {
+                a[i][j] += row[i] * row[j];
+            }
+            b[i] -= row[i] * (x * x + y * y);
+        }
+    }
+    let [d, e, f] = solve3(a, b)?;
+    let (mut cx, mut cy) = (-d / 2.0, -e / 2.0);
+    let mut radius = (cx * cx + cy * cy - f).sqrt();
+    if radius.is_nan() {
+        return None;
+    }
+
+    // The algebraic fit is biased towards small circles on short, thick arcs,
+    // so refine the geometric distance error with Gauss-Newton steps
+    for _ in 0..RING_FIT_ITERATIONS {
+        let mut jtj = [[0.0; 3]; 3];
+        let mut jtr = [0.0; 3];
+        for &(x, y) in &pixels {
+            let (dx, dy) = (x - cx, y - cy);
+            let distance = (dx * dx + dy * dy).sqrt().max(1e-9);
+            let jacobian = [-dx / distance, -dy / distance, -1.0];
+            let residual = distance - radius;
+            for i in 0..3 {
+                for j in 0..3 {
+                    jtj[i][j] += jacobian[i] * jacobian[j];
+                }
+                jtr[i] -= jacobian[i] * residual;
+            }
+        }
+
+        let Some([step_x, step_y, step_r]) = solve3(jtj, jtr) else {
+            break;
+        };
+        cx += step_x;
+        cy += step_y;
+        radius += step_r;
+        if step_x.abs() + step_y.abs() + step_r.abs() < 1e-6 {
+            break;
+        }
+    }
+    if !radius.is_finite() || radius < 3.0 {
+        return None;
+    }
+
+    // Radial distribution: every pixel close to the circle, none near the center
+    let in_band = pixels
+        .iter()
+        .filter(|&&(px, py)| {
+            let distance = ((px - cx).powi(2) + (py - cy).powi(2)).sqrt();
+            (distance - radius).abs() <= radius * 0.35
+        })
+        .count();
+    if (in_band as f64) < n * 0.95 {
+        return None;
+    }
+
+    // Enough angular bins that neighbouring pixels on the circle share one
+    let bins = ((std::f64::consts::TAU * radius / 2.0) as usize).clamp(8, 36);
+    let mut covered = vec![false; bins];
+    for &(px, py) in &pixels {
+        let angle = (py - cy).atan2(px - cx);
+        let bin = ((angle + std::f64::consts::PI) / std::f64::consts::TAU * bins as f64) as usize;
+        covered[bin.min(bins - 1)] = true;
+    }
+
+    Some(RingFit {
+        center: ((mean_x + cx) as f32, (mean_y + cy) as f32),
+        radius: radius as f32,
+        angular_coverage: covered.iter().filter(|&&c| c).count() as f32 / bins as f32,
+    })
+}
+
+/// Estimate how far a progress ring has filled, e.g. 0.4 for a ring drawn
+/// 40% of the way around. Looks for the largest ring-shaped component, so a
+/// spinner badge next to an arrow works as well as a standalone spinner.
+pub fn detect_spinner_progress(image_data: &[u8], width: u32, height: u32) -> Option<f32> {
+    let map = ComponentMap::from_rgba(image_data, width, height)?;
+
+    (0..map.components().len())
+        .find_map(|index| fit_ring(&map, index))
+        .map(|ring| ring.angular_coverage)
+}
+
+/// Solve a 3x3 linear system with Cramer's rule, `None` if it's singular
+fn solve3(a: [[f64; 3]; 3], b: [f64; 3]) -> Option<[f64; 3]> {
+    let det3 = |m: [[f64; 3]; 3]| {
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    };
+
+    let det = det3(a);
+    if det.abs() < 1e-9 {
+        return None;
+    }
+
+    Some([0, 1, 2].map(|column| {
+        let mut m = a;
+        for row in 0..3 {
+            m[row][column] = b[row];
+        }
+        det3(m) / det
+    }))
+}
+
+/// Arrow bodies start with their tip: the topmost row begins at the left edge
+fn tip_on_left(map: &ComponentMap, index: usize) -> bool {
+    let bbox = map.components()[index].bbox;
+    let first_x =
+        (bbox.min_x..=bbox.max_x).find(|&x| map.component_at(x, bbox.min_y) == Some(index));
+
+    first_x.is_some_and(|x| (x - bbox.min_x) * 10 <= bbox.width() * 3)
+}
@@ -0,0 +1,182 @@
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::super::cache::DetectionCache;
+    use super::super::calibration::Calibration;
+    use super::super::detection::*;
+    use super::super::loading::analyze_cursor_image;
+    use image::RgbaImage;
+    use std::path::{Path, PathBuf};
+
+    /// Detection accuracy across the whole corpus below which the test fails
+    const MIN_CORPUS_ACCURACY: f32 = 1.0;
+
+    fn corpus_root() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/cursors")
+    }
+
+    /// Path of the `<kind>/<name>-<size>.png` fixture, `kind` being the
+    /// folder of its type
+    pub(crate) fn fixture_path(kind: &str, name: &str, size: u32) -> PathBuf {
+        corpus_root().join(kind).join(format!("{name}-{size}.png"))
+    }
+
+    /// The `<kind>/<name>-<size>.png` fixture, see [`fixture_path`]
+    pub(crate) fn fixture(kind: &str, name: &str, size: u32) -> RgbaImage {
+        let path = fixture_path(kind, name, size);
+        image::open(&path)
+            .unwrap_or_else(|e| panic!("Failed to load {}: {}", path.display(), e))
+            .to_rgba8()
+    }
+
+    /// Fixture folders are named after the bundled SVG of the expected type
+    fn type_for_label(label: &str) -> Option<CommonCursorType> {
+        CommonCursorType::ALL
+            .iter()
+            .copied()
+            .find(|t| t.svg_filename().trim_end_matches(".svg") == label)
+    }
+
+    fn sorted_entries(dir: &Path) -> Vec<PathBuf> {
+        let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)
+            .unwrap_or_else(|e| panic!("Failed to read {}: {}", dir.display(), e))
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    #[test]
+    fn test_fixture_corpus_accuracy() {
+        let mut total = 0;
+        let mut mismatches = Vec::new();
+
+        for dir in sorted_entries(&corpus_root())
+            .into_iter()
+            .filter(|p| p.is_dir())
+        {
+            let label = dir.file_name().unwrap().to_string_lossy().into_owned();
+            let expected = type_for_label(&label)
+                .unwrap_or_else(|| panic!("Fixture folder doesn't name a cursor type: {}", label));
+
+            for file in sorted_entries(&dir)
+                .into_iter()
+                .filter(|p| p.extension().is_some_and(|ext| ext == "png"))
+            {
+                total += 1;
+                let detected = analyze_cursor_image(&file);
+                if detected != Some(expected) {
+                    mismatches.push(format!(
+                        "{}/{}: expected {:?}, detected {:?}",
+                        label,
+                        file.file_name().unwrap().to_string_lossy(),
+                        expected,
+                        detected
+                    ));
+                }
+            }
+        }
+
+        assert!(
+            total > 0,
+            "No fixtures found in {}",
+            corpus_root().display()
+        );
+
+        let accuracy = (total - mismatches.len()) as f32 / total as f32;
+        println!(
+            "Cursor detection accuracy: {}/{} ({:.1}%)",
+            total - mismatches.len(),
+            total,
+            accuracy * 100.0
+        );
+        for mismatch in &mismatches {
+            println!("  {}", mismatch);
+        }
+
+        assert!(
+            accuracy >= MIN_CORPUS_ACCURACY,
+            "Detection accuracy {:.1}% is below the {:.1}% bar:\n{}",
+            accuracy * 100.0,
+            MIN_CORPUS_ACCURACY * 100.0,
+            mismatches.join("\n")
+        );
+    }
+
+    #[test]
+    fn test_detection_cache_matches_direct_detection() {
+        let images: Vec<(PathBuf, RgbaImage)> = sorted_entries(&corpus_root())
+            .into_iter()
+            .filter(|p| p.is_dir())
+            .flat_map(|dir| sorted_entries(&dir))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "png"))
+            .map(|file| {
+                let image = image::open(&file).unwrap().to_rgba8();
+                (file, image)
+            })
+            .collect();
+
+        // Room for every fixture, so the second pass is served from the cache
+        let mut cache = DetectionCache::new(images.len());
+        for pass in 1..=2 {
+            for (file, image) in &images {
+                let (width, height) = image.dimensions();
+                assert_eq!(
+                    cache.detect(image.as_raw(), width, height),
+                    CommonCursorType::detect_from_image(image.as_raw(), width, height),
+                    "{} on pass {}",
+                    file.display(),
+                    pass
+                );
+            }
+        }
+        assert_eq!(cache.misses() as usize, cache.len());
+    }
+
+    #[test]
+    fn test_default_calibration_is_fitted_on_corpus() {
+        let mut samples = Vec::new();
+        for dir in sorted_entries(&corpus_root())
+            .into_iter()
+            .filter(|p| p.is_dir())
+        {
+            let label = dir.file_name().unwrap().to_string_lossy().into_owned();
+            let expected = type_for_label(&label).unwrap();
+            for file in sorted_entries(&dir)
+                .into_iter()
+                .filter(|p| p.extension().is_some_and(|ext| ext == "png"))
+            {
+                let image = image::open(&file).unwrap().to_rgba8();
+                let ranked =
+                    CommonCursorType::detect_ranked(image.as_raw(), image.width(), image.height());
+                samples.push((expected, ranked));
+            }
+        }
+
+        // Refit after changing the matchers or the corpus, and update the
+        // baked coefficients to match
+        let fitted = Calibration::fit(&samples);
+        let baked = Calibration::default();
+        for &cursor_type in CommonCursorType::ALL {
+            let (Some(fitted), Some(baked)) = (
+                fitted.coefficients(cursor_type),
+                baked.coefficients(cursor_type),
+            ) else {
+                assert_eq!(
+                    fitted.coefficients(cursor_type),
+                    baked.coefficients(cursor_type),
+                    "{:?}",
+                    cursor_type
+                );
+                continue;
+            };
+            assert!(
+                (fitted.slope - baked.slope).abs() < 0.01
+                    && (fitted.intercept - baked.intercept).abs() < 0.01,
+                "{:?} fits as {:?}, baked as {:?}",
+                cursor_type,
+                fitted,
+                baked
+            );
+        }
+    }
+}
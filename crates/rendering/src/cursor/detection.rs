@@ -0,0 +1,2028 @@
+use crate::cursor::calibration::{Calibration, LogisticCoefficients};
+use crate::cursor::components::{
+    classify_badge, classify_scroll, crop_component, has_attached_glyph, has_spinner_badge,
+    has_text_readout, is_reticle, is_slashed_circle, is_stacked, isolate_component, tooltip_cursor,
+    BadgeShape, ComponentMap, ScrollAxis,
+};
+use crate::cursor::error::CursorError;
+use crate::cursor::features::{
+    canonical_rgba, demagnify_in, fill_hollow_in, normalize_alpha_in, HollowScratch,
+};
+use crate::cursor::geometry::{Point, Rect, Size};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+
+pub use crate::cursor::components::{
+    detect_spinner_progress, strip_tooltips, ComponentBounds, StrippedCursor,
+};
+pub use crate::cursor::features::{
+    canonicalize, canonicalize_with, defringe, dominant_cursor_color, dominant_cursor_color_with,
+    fill_hollow, is_magnified, luma_silhouette, normalize_alpha, normalize_translation,
+    skeletonize, CanonicalPooling, ChannelOrder, CursorFeatures, FeatureScratch, CANONICAL_SIZE,
+    MAGNIFIED_MIN_EXTENT,
+};
+pub use crate::cursor::fingerprint::{cursor_fingerprint, perceptual_hash};
+pub use crate::cursor::stream::{
+    AnimatedCursorClassifier, CursorClassifier, CursorDetectExt, CursorRegionAssembler,
+    DetectAnimatedCursors, DetectCursors, Handedness, DEFAULT_ANIMATION_WINDOW, HANDEDNESS_STREAK,
+};
+
+/// Alpha above which a cursor pixel counts as part of the cursor shape
+pub const OPAQUE_ALPHA_THRESHOLD: u8 = 128;
+
+/// Smallest bitmap side length detection will try to classify
+const MIN_DETECTION_SIZE: u32 = 8;
+
+/// Fewer opaque pixels than this is treated as noise rather than a cursor
+const MIN_OPAQUE_PIXELS: u32 = 16;
+
+/// Score of a type recognized by its components rather than by a matcher,
+/// see [`CommonCursorType::detect_ranked`]. Badges, rings and arrowheads
+/// either are there or aren't, so these are reported as certain
+const STRUCTURAL_SCORE: f32 = 1.0;
+
+/// Share of its bounding box a filled-in outline may cover and still be
+/// matched as the shape it outlines, rather than left a ring or frame
+const MAX_HOLLOW_FILL_RATIO: f32 = 0.7;
+
+/// Whether a well-formed bitmap of any size has no visible pixel at all,
+/// which is detected as [`CommonCursorType::Blank`]. Faint strays still make
+/// it a cursor too small or dim to tell, rather than a blank one
+fn is_blank(image_data: &[u8], width: u32, height: u32) -> bool {
+    let len = width as usize * height as usize * 4;
+    len > 0 && image_data.len() >= len && image_data[..len].chunks_exact(4).all(|p| p[3] == 0)
+}
+
+/// Score added to the previous frame's type in [`CommonCursorType::detect_with_prior`].
+/// Enough to carry a borderline frame, far less than the margin a clearly
+/// different cursor wins by
+pub const PRIOR_BONUS: f32 = 0.1;
+
+/// Scores how well a cursor's features fit one type, see [`MATCH_THRESHOLD`],
+/// with the thresholds for the cursor's size
+type Matcher = fn(&CursorFeatures, &SizeThresholds) -> f32;
+
+/// Declares `CommonCursorType` together with its stable ids and bundled
+/// assets, named after the variant's stem, so every variant is guaranteed an
+/// SVG and its PNG fallbacks: a variant missing any of them on disk fails the
+/// `include_bytes!` below and with it the whole build.
+macro_rules! cursor_types {
+    ($($(#[$meta:meta])* $variant:ident = $id:literal => $stem:literal,)+) => {
+        // Common cursor types that we support with SVG versions. Ordered by
+        // id, which derived `Ord` takes from the discriminants
+        #[derive(
+            Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+        )]
+        #[repr(u16)]
+        pub enum CommonCursorType {
+            $($(#[$meta])* $variant = $id,)+
+        }
+
+        impl CommonCursorType {
+            /// Every cursor type, in declaration order
+            pub const ALL: &'static [CommonCursorType] = &[$(CommonCursorType::$variant,)+];
+
+            /// Stable id of this cursor type, which also orders the types.
+            /// Ids are never reused or changed, so new types get the next
+            /// unused one wherever they're declared
+            pub fn id(&self) -> u16 {
+                *self as u16
+            }
+
+            /// Get the SVG filename for this cursor type
+            pub fn svg_filename(&self) -> &'static str {
+                match self {
+                    $(CommonCursorType::$variant => concat!($stem, ".svg"),)+
+                }
+            }
+
+            /// Contents of the bundled SVG for this cursor type
+            pub fn svg_bytes(&self) -> &'static [u8] {
+                match self {
+                    $(CommonCursorType::$variant => {
+                        include_bytes!(concat!("../../../../apps/desktop/src/cursors/", $stem, ".svg"))
+                    })+
+                }
+            }
+
+            /// Bundled 1x and 2x PNG renderings of the SVG, in that order, see
+            /// [`crate::cursor::rendering::rasterize_cursor_fallback`]
+            pub fn fallback_pngs(&self) -> [&'static [u8]; 2] {
+                match self {
+                    $(CommonCursorType::$variant => [
+                        include_bytes!(concat!("../../../../apps/desktop/src/cursors/fallback/", $stem, ".png")),
+                        include_bytes!(concat!("../../../../apps/desktop/src/cursors/fallback/", $stem, "@2x.png")),
+                    ],)+
+                }
+            }
+        }
+    };
+}
+
+cursor_types! {
+    Arrow = 0 => "arrow",
+    IBeam = 1 => "ibeam",
+    Crosshair = 2 => "crosshair",
+    PointingHand = 3 => "pointing-hand",
+    /// Diagonal resize (northwest-southeast)
+    ResizeNWSE = 4 => "resize-nwse",
+    /// Horizontal resize (east-west)
+    ResizeEW = 5 => "resize-ew",
+    /// Resize towards any corner, with arrowheads on both diagonals
+    ResizeAll = 6 => "resize-all",
+    /// Arrow with a plus badge
+    Copy = 7 => "copy",
+    /// Arrow with a shortcut arrow badge
+    Alias = 8 => "alias",
+    /// Arrow with a question mark badge
+    Help = 9 => "help",
+    /// Arrow with a spinner badge (busy in background)
+    Progress = 10 => "progress",
+    /// Arrow with a menu badge
+    ContextMenu = 11 => "context-menu",
+    /// Large contact disc drawn by touch-first systems
+    TouchPointer = 12 => "touch-pointer",
+    /// Up-down scroll, arrowheads around a pivot dot
+    ScrollNS = 13 => "scroll-ns",
+    /// Left-right scroll, arrowheads around a pivot dot
+    ScrollEW = 14 => "scroll-ew",
+    /// Circle with a diagonal slash, for actions and drops that aren't allowed
+    NotAllowed = 15 => "not-allowed",
+    /// Open hand with splayed fingers, over something that can be dragged
+    Grab = 16 => "grab",
+    /// Closed fist, while dragging
+    Grabbing = 17 => "grabbing",
+    /// Thin ring around a center dot, the aiming reticle of games
+    Reticle = 18 => "reticle",
+    /// Fully transparent cursor an app sets on purpose, e.g. to draw its own
+    Blank = 19 => "blank",
+    /// Four arrows around a diamond, the fleur Linux desktops show while a
+    /// window is dragged by its title bar
+    Move = 20 => "move",
+    /// Diagonal pipette with a bulb at its top-right end, for picking colors
+    Eyedropper = 21 => "eyedropper",
+    /// Arrow with a downward arrow badge, over a link that downloads a file
+    DownloadLink = 22 => "download-link",
+    /// Vertical double arrow across a horizontal bar, shown over the divider
+    /// between two table or spreadsheet rows
+    RowResize = 23 => "row-resize",
+    /// Disc of wedges in every hue, the spinning beachball macOS shows while
+    /// an app is unresponsive
+    Wait = 24 => "wait",
+}
+
+impl CommonCursorType {
+    /// Hotspot of the bundled SVG, relative to the square it is rasterized into
+    pub fn hotspot(&self) -> (f32, f32) {
+        match self {
+            CommonCursorType::Arrow => (0.1, 0.1),        // Top-left point
+            CommonCursorType::IBeam => (0.5, 0.5),        // Center
+            CommonCursorType::Crosshair => (0.5, 0.5),    // Center
+            CommonCursorType::PointingHand => (0.3, 0.1), // Finger tip
+            CommonCursorType::ResizeNWSE => (0.5, 0.5),   // Center
+            CommonCursorType::ResizeEW => (0.5, 0.5),     // Center
+            CommonCursorType::ResizeAll => (0.5, 0.5),    // Center
+            // Badge cursors share the arrow's tip, their badge sits bottom-right
+            CommonCursorType::Copy
+            | CommonCursorType::Alias
+            | CommonCursorType::Help
+            | CommonCursorType::Progress
+            | CommonCursorType::ContextMenu
+            | CommonCursorType::DownloadLink => (0.1, 0.1),
+            CommonCursorType::TouchPointer => (0.5, 0.5), // Center of the disc
+            CommonCursorType::ScrollNS | CommonCursorType::ScrollEW => (0.5, 0.5), // Pivot dot
+            CommonCursorType::NotAllowed => (0.5, 0.5),   // Center of the circle
+            CommonCursorType::Grab | CommonCursorType::Grabbing => (0.5, 0.5), // Palm
+            CommonCursorType::Reticle => (0.5, 0.5),      // Center dot
+            CommonCursorType::Blank => (0.0, 0.0),        // Nothing to point with
+            CommonCursorType::Move => (0.5, 0.5),         // Center of the diamond
+            CommonCursorType::Eyedropper => (0.1, 0.9),   // Bottom-left tip
+            CommonCursorType::RowResize => (0.5, 0.5),    // Middle of the bar
+            CommonCursorType::Wait => (0.5, 0.5),         // Center of the disc
+        }
+    }
+
+    /// The type a CSS `cursor` keyword names, for capture backends that report
+    /// the cursor by name, which is exact and much cheaper than detecting it
+    /// from pixels. Keywords are matched ASCII case-insensitively, including
+    /// the legacy and vendor-prefixed spellings (`hand`, `-webkit-grab`, ...).
+    /// `None` for `auto` and for cursors no type stands for, e.g. `cell`, the
+    /// vertical `ns-resize` or the `nesw-resize` diagonal, which
+    /// [`Self::ResizeNWSE`] would draw mirrored; detect those from pixels
+    /// instead
+    pub fn from_css_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+        let name = ["-webkit-", "-moz-"]
+            .iter()
+            .find_map(|prefix| name.strip_prefix(prefix))
+            .unwrap_or(&name);
+
+        Some(match name {
+            "default" => CommonCursorType::Arrow,
+            "none" => CommonCursorType::Blank,
+            "context-menu" => CommonCursorType::ContextMenu,
+            "help" => CommonCursorType::Help,
+            "pointer" | "hand" => CommonCursorType::PointingHand,
+            "progress" => CommonCursorType::Progress,
+            "wait" => CommonCursorType::Wait,
+            "crosshair" => CommonCursorType::Crosshair,
+            "text" => CommonCursorType::IBeam,
+            "alias" => CommonCursorType::Alias,
+            "copy" => CommonCursorType::Copy,
+            "move" | "all-scroll" => CommonCursorType::Move,
+            "no-drop" | "not-allowed" => CommonCursorType::NotAllowed,
+            "grab" => CommonCursorType::Grab,
+            "grabbing" => CommonCursorType::Grabbing,
+            "ew-resize" | "e-resize" | "w-resize" | "col-resize" => CommonCursorType::ResizeEW,
+            "row-resize" => CommonCursorType::RowResize,
+            "nwse-resize" | "nw-resize" | "se-resize" => CommonCursorType::ResizeNWSE,
+            _ => return None,
+        })
+    }
+
+    /// The type of a Win32 stock cursor, by the numeric id of its `IDC_*`
+    /// constant (`IDC_ARROW` is 32512). `IDC_WAIT`, a busy ring without the
+    /// arrow, is [`Self::Progress`] like `IDC_APPSTARTING`, as its pixels
+    /// detect: [`Self::Wait`] is the macOS beachball, which Windows doesn't
+    /// draw. Like [`Self::from_css_name`], `None` for the stock cursors no
+    /// type stands for, e.g. `IDC_UPARROW`, `IDC_SIZENS` or `IDC_SIZENESW`
+    pub fn from_win32_idc(id: u32) -> Option<Self> {
+        Some(match id {
+            32512 => CommonCursorType::Arrow,        // IDC_ARROW
+            32513 => CommonCursorType::IBeam,        // IDC_IBEAM
+            32514 => CommonCursorType::Progress,     // IDC_WAIT
+            32515 => CommonCursorType::Crosshair,    // IDC_CROSS
+            32642 => CommonCursorType::ResizeNWSE,   // IDC_SIZENWSE
+            32644 => CommonCursorType::ResizeEW,     // IDC_SIZEWE
+            32646 => CommonCursorType::Move,         // IDC_SIZEALL
+            32648 => CommonCursorType::NotAllowed,   // IDC_NO
+            32649 => CommonCursorType::PointingHand, // IDC_HAND
+            32650 => CommonCursorType::Progress,     // IDC_APPSTARTING
+            32651 => CommonCursorType::Help,         // IDC_HELP
+            _ => return None,
+        })
+    }
+
+    /// Detect cursor type from image data (simplified heuristic approach)
+    /// In a real implementation, this could use more sophisticated image analysis.
+    /// A fully transparent bitmap is [`CommonCursorType::Blank`], an
+    /// app's deliberate choice, while `None` means no cursor could be made out
+    pub fn detect_from_image(image_data: &[u8], width: u32, height: u32) -> Option<Self> {
+        Self::detect_with_config(image_data, width, height, &DetectionConfig::default())
+    }
+
+    /// Like [`Self::detect_from_image`], reusing `scratch`'s buffers instead
+    /// of allocating, for detecting every frame of a realtime capture.
+    ///
+    /// Once `scratch` has seen a cursor of the same size and shape family,
+    /// detection doesn't allocate at all.
+    pub fn detect_from_image_in(
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+        scratch: &mut DetectionScratch,
+    ) -> Option<Self> {
+        let mut best = BestMatch::default();
+        Self::detect_staged(
+            image_data,
+            width,
+            height,
+            &SizeBuckets::default(),
+            scratch,
+            &mut best,
+        );
+        best.matched().map(|(cursor_type, _)| cursor_type)
+    }
+
+    /// Detect a cursor without taking much longer than `budget`, for realtime
+    /// loops that can't afford a slow frame. Returns the best match with its
+    /// score, only above [`MATCH_THRESHOLD`]. The score is the one
+    /// [`Self::detect_ranked`] gives the type, so cursors recognized by their
+    /// components score 1.0 here too.
+    ///
+    /// The elapsed time is checked before each stage of detection: feature
+    /// extraction, segmenting the image for hinted I-beams and the badge,
+    /// not-allowed and scroll cursors, then each single-blob matcher in
+    /// priority order. Once it's past `budget` the best match so far is
+    /// returned. With time to spare that's the type
+    /// [`Self::detect_from_image`] finds, but under a tight budget results
+    /// are worse: no type, or one of the higher priority matchers' types when
+    /// a lower one would have scored better. A stage that has started always
+    /// runs to the end, so on large inputs the budget is overrun by however
+    /// long feature extraction takes.
+    pub fn detect_within(
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+        budget: Duration,
+    ) -> Option<(Self, f32)> {
+        let mut budgeted = Budgeted {
+            best: BestMatch::default(),
+            start: Instant::now(),
+            budget,
+        };
+        Self::detect_staged(
+            image_data,
+            width,
+            height,
+            &SizeBuckets::default(),
+            &mut DetectionScratch::new(),
+            &mut budgeted,
+        );
+        budgeted.best.matched()
+    }
+
+    /// Detect a cursor in a bitmap of any supported [`CursorPixelFormat`]
+    pub fn detect_from_pixels(
+        data: &[u8],
+        width: u32,
+        height: u32,
+        format: CursorPixelFormat,
+    ) -> Option<Self> {
+        match format {
+            CursorPixelFormat::Rgba8 => Self::detect_from_image(data, width, height),
+            CursorPixelFormat::Gray8 => {
+                let silhouette = luma_silhouette(data, width, height)?;
+                Self::detect_from_image(&silhouette, width, height)
+            }
+        }
+    }
+
+    /// Detect a classic Win32 cursor given as a color bitmap plus a 1-bit AND mask.
+    ///
+    /// `color` is RGBA, top-down like everywhere else, and its alpha is ignored.
+    /// `and_mask` has one bit per pixel, most significant bit first, with each
+    /// row padded to a multiple of 32 bits as Win32 stores it. A clear bit
+    /// draws the color pixel; a set bit leaves the screen as is, unless the
+    /// color pixel isn't black, in which case the screen is inverted there,
+    /// which is still part of the cursor's shape.
+    pub fn detect_from_masked(
+        color: &[u8],
+        and_mask: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Option<Self> {
+        let stride = width.div_ceil(32) as usize * 4;
+        let len = width as usize * height as usize * 4;
+        if color.len() < len || and_mask.len() < stride * height as usize {
+            return None;
+        }
+
+        let mut image_data = color[..len].to_vec();
+        for (i, pixel) in image_data.chunks_exact_mut(4).enumerate() {
+            let (x, y) = (i % width as usize, i / width as usize);
+            let transparent = and_mask[y * stride + x / 8] & (0x80 >> (x % 8)) != 0;
+            let inverts = pixel[..3].iter().any(|&c| c != 0);
+            pixel[3] = if !transparent || inverts { 255 } else { 0 };
+        }
+
+        Self::detect_from_image(&image_data, width, height)
+    }
+
+    /// Detect a cursor from its [`canonicalize`]d mask. The mask has no color,
+    /// so a [`Self::Wait`] beachball comes back as the disc it's shaped like,
+    /// a [`Self::TouchPointer`]
+    pub fn detect_canonical(mask: &[u8; 1024]) -> Option<Self> {
+        Self::detect_from_image(&canonical_rgba(mask), CANONICAL_SIZE, CANONICAL_SIZE)
+    }
+
+    /// Detect cursor type with explicit thresholds, see [`DetectionConfig`].
+    /// The type is passed through the config's [`DetectionConfig::type_mapper`]
+    pub fn detect_with_config(
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+        config: &DetectionConfig,
+    ) -> Option<Self> {
+        Self::detect_mapped(image_data, width, height, config).map(|detection| detection.mapped)
+    }
+
+    /// Like [`Self::detect_with_config`], also returning the type as detected
+    /// before the config's [`DetectionConfig::type_mapper`] was applied
+    pub fn detect_mapped(
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+        config: &DetectionConfig,
+    ) -> Option<MappedDetection> {
+        let ranked = Self::ranked_with_config(image_data, width, height, config)?;
+        let (best, best_score) = *ranked.first()?;
+        if best_score < config.min_score {
+            return None;
+        }
+
+        if let Some(margin) = config.strict_margin {
+            if best_score - Self::runner_up_score(&ranked) < margin {
+                return None;
+            }
+        }
+
+        Some(MappedDetection {
+            raw: best,
+            mapped: config.map_type(best),
+        })
+    }
+
+    /// [`Self::detect_ranked`] as `config` sees it: of the image defringed
+    /// and moved to the origin if the config asks for either, or of its
+    /// skeleton if it asks for that and the image matches nothing, calibrated
+    /// if it has a calibration. `None` if the image can't be normalized
+    fn ranked_with_config(
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+        config: &DetectionConfig,
+    ) -> Option<Vec<(Self, f32)>> {
+        let defringed = if config.defringe {
+            defringe(image_data, width, height)
+        } else {
+            Cow::Borrowed(image_data)
+        };
+        let image_data = &*defringed;
+        let normalized;
+        let image_data = if config.normalize_translation && !is_blank(image_data, width, height) {
+            normalized = normalize_translation(image_data, width, height)?;
+            &normalized
+        } else {
+            image_data
+        };
+        let mut ranked =
+            Self::detect_ranked_by_size(image_data, width, height, &config.size_buckets);
+
+        // Thinning turns filled shapes into stick figures, so the skeleton is
+        // only scored when the image itself matches nothing
+        let best_score = |ranked: &[(Self, f32)]| ranked.first().map_or(0.0, |&(_, score)| score);
+        if config.skeletonize && best_score(&ranked) < MATCH_THRESHOLD {
+            if let Some(thinned) = skeletonize(image_data, width, height)
+                .map(|skeleton| {
+                    Self::detect_ranked_by_size(&skeleton, width, height, &config.size_buckets)
+                })
+                .filter(|thinned| best_score(thinned) > best_score(&ranked))
+            {
+                ranked = thinned;
+            }
+        }
+        if let Some(calibration) = &config.calibration {
+            calibration.calibrate(&mut ranked);
+        }
+        Some(ranked)
+    }
+
+    /// Score of the best candidate competing with the first of `ranked`. A
+    /// badge cursor's body also scores as an arrow, which doesn't make the
+    /// structural badge match any less certain, so that arrow doesn't compete
+    fn runner_up_score(ranked: &[(Self, f32)]) -> f32 {
+        let Some(&(best, _)) = ranked.first() else {
+            return 0.0;
+        };
+        ranked
+            .iter()
+            .skip(1)
+            .find(|(cursor_type, _)| !Self::badge_body(best, *cursor_type))
+            .map_or(0.0, |(_, score)| *score)
+    }
+
+    /// Whether `candidate` is just the arrow body of `best`, a badge cursor
+    fn badge_body(best: Self, candidate: Self) -> bool {
+        best.has_badge() && candidate == CommonCursorType::Arrow
+    }
+
+    /// Collapse every resize variant to [`CommonCursorType::ResizeEW`], for
+    /// consumers that only care that the cursor resizes something. Meant as a
+    /// [`DetectionConfig::type_mapper`]
+    pub fn canonical_resize(self) -> Self {
+        match self {
+            CommonCursorType::ResizeNWSE
+            | CommonCursorType::ResizeEW
+            | CommonCursorType::ResizeAll
+            | CommonCursorType::RowResize => CommonCursorType::ResizeEW,
+            other => other,
+        }
+    }
+
+    /// Detect a cursor favoring `prior`, typically the type detected in the
+    /// previous frame, by [`PRIOR_BONUS`].
+    ///
+    /// The bonus applies both when comparing against other types and against
+    /// [`MATCH_THRESHOLD`], so a frame where the prior only just misses is
+    /// still reported as the prior. The returned score is the type's own,
+    /// without the bonus, and can therefore be slightly below the threshold.
+    pub fn detect_with_prior(
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+        prior: Option<Self>,
+    ) -> Option<(Self, f32)> {
+        let bonus = |cursor_type: Self| {
+            if Some(cursor_type) == prior {
+                PRIOR_BONUS
+            } else {
+                0.0
+            }
+        };
+
+        Self::detect_ranked(image_data, width, height)
+            .into_iter()
+            // Ranked is in priority order for equal scores, keep the first
+            .reduce(|best, candidate| {
+                if candidate.1 + bonus(candidate.0) > best.1 + bonus(best.0) {
+                    candidate
+                } else {
+                    best
+                }
+            })
+            .filter(|(cursor_type, score)| score + bonus(*cursor_type) >= MATCH_THRESHOLD)
+    }
+
+    /// Whether this is an arrow with a secondary glyph next to it
+    pub fn has_badge(&self) -> bool {
+        matches!(
+            self,
+            CommonCursorType::Copy
+                | CommonCursorType::Alias
+                | CommonCursorType::Help
+                | CommonCursorType::Progress
+                | CommonCursorType::ContextMenu
+                | CommonCursorType::DownloadLink
+        )
+    }
+
+    /// Score functions of the single-blob cursor types. Listed in priority
+    /// order, which breaks ties: the line-based shapes come before the arrow
+    /// and hand, whose heuristics are looser
+    pub(crate) const MATCHERS: [(CommonCursorType, Matcher); 14] = [
+        (CommonCursorType::IBeam, Self::ibeam_score),
+        (CommonCursorType::Crosshair, Self::crosshair_score),
+        (CommonCursorType::Move, Self::fleur_score),
+        (CommonCursorType::ResizeEW, Self::horizontal_resize_score),
+        (CommonCursorType::RowResize, Self::row_resize_score),
+        (CommonCursorType::ResizeNWSE, Self::diagonal_resize_score),
+        (CommonCursorType::ResizeAll, Self::all_corners_resize_score),
+        (CommonCursorType::Eyedropper, Self::eyedropper_score),
+        (CommonCursorType::Wait, Self::wait_score),
+        (CommonCursorType::TouchPointer, Self::touch_pointer_score),
+        (CommonCursorType::Arrow, Self::arrow_score),
+        (CommonCursorType::PointingHand, Self::hand_score),
+        (CommonCursorType::Grab, Self::open_hand_score),
+        (CommonCursorType::Grabbing, Self::fist_score),
+    ];
+
+    /// Score every candidate type against the image, best match first.
+    ///
+    /// Scores are in 0..=1 with [`MATCH_THRESHOLD`] meaning a type's
+    /// heuristics are only just satisfied. Badge, not-allowed, reticle and
+    /// scroll cursors, I-beams with a link hint and arrows with a text
+    /// readout, are recognized structurally rather than scored, so they only
+    /// ever appear as a single 1.0 entry at the front. A blank bitmap ranks as
+    /// nothing but [`CommonCursorType::Blank`], whatever its size.
+    pub fn detect_ranked(image_data: &[u8], width: u32, height: u32) -> Vec<(Self, f32)> {
+        Self::detect_ranked_by_size(image_data, width, height, &SizeBuckets::default())
+    }
+
+    /// [`Self::detect_ranked`] with the matcher thresholds in `size_buckets`
+    fn detect_ranked_by_size(
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+        size_buckets: &SizeBuckets,
+    ) -> Vec<(Self, f32)> {
+        let mut ranking = Ranking::default();
+        Self::detect_staged(
+            image_data,
+            width,
+            height,
+            size_buckets,
+            &mut DetectionScratch::new(),
+            &mut ranking,
+        );
+        let mut ranked = ranking.0;
+        // Stable sort, so equal scores keep the priority order
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        #[cfg(feature = "trace")]
+        if let Some((cursor_type, score)) = ranked.first() {
+            tracing::trace!(
+                cursor_type = ?cursor_type,
+                score,
+                matched = *score >= MATCH_THRESHOLD,
+                "cursor detection result"
+            );
+        }
+
+        ranked
+    }
+
+    /// Like [`Self::detect_from_image`], also measuring how long each stage takes
+    #[cfg(feature = "profiling")]
+    pub fn detect_profiled(
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+    ) -> (Option<Self>, MatcherTimings) {
+        let mut profiled = Profiled {
+            best: BestMatch::default(),
+            timings: MatcherTimings::default(),
+            stage_start: Instant::now(),
+        };
+        Self::detect_staged(
+            image_data,
+            width,
+            height,
+            &SizeBuckets::default(),
+            &mut DetectionScratch::new(),
+            &mut profiled,
+        );
+        let detected = profiled.best.matched().map(|(cursor_type, _)| cursor_type);
+        (detected, profiled.timings)
+    }
+
+    /// The detection pipeline behind every entry point, one stage after the
+    /// other: normalizing the cursor's alpha and magnification and extracting
+    /// its features, segmenting it for the cursors made of several blobs,
+    /// then scoring the [`Self::MATCHERS`]. Candidate types go to `stages` as
+    /// they're found, which also decides whether each stage runs at all.
+    ///
+    /// A blank bitmap is [`CommonCursorType::Blank`] whatever its size, and
+    /// bitmaps too small or sparse to tell shapes apart have no candidates.
+    /// A type recognized by its components comes first, scoring
+    /// [`STRUCTURAL_SCORE`]; the matchers only run after it for stages that
+    /// rank every type
+    fn detect_staged(
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+        size_buckets: &SizeBuckets,
+        scratch: &mut DetectionScratch,
+        stages: &mut impl DetectionStages,
+    ) {
+        #[cfg(feature = "trace")]
+        let _span = tracing::trace_span!("detect_cursor", width, height).entered();
+
+        if is_blank(image_data, width, height) {
+            return stages.candidate(CommonCursorType::Blank, 1.0);
+        }
+        // Anything smaller than this is too coarse to tell shapes apart
+        if width < MIN_DETECTION_SIZE || height < MIN_DETECTION_SIZE {
+            return;
+        }
+
+        let DetectionScratch {
+            alpha,
+            magnified,
+            hollow,
+            isolated,
+            features: feature_scratch,
+            components: map,
+        } = scratch;
+
+        if !stages.enter(Stage::Features) {
+            return;
+        }
+        // Dimmed cursors are matched by shape as if they were fully opaque,
+        // and magnified ones at about the size they'd be drawn without
+        let image_data = normalize_alpha_in(image_data, width, height, alpha);
+        let (image_data, width, height) = demagnify_in(image_data, width, height, magnified);
+        let thresholds = size_buckets.for_size(width, height);
+        let features = CursorFeatures::extract_in(image_data, width, height, feature_scratch);
+        stages.leave(Stage::Features);
+        let Some(features) = features else {
+            return;
+        };
+
+        #[cfg(feature = "trace")]
+        tracing::trace!(
+            opaque_pixels = features.opaque_pixels,
+            aspect_ratio = features.aspect_ratio(),
+            fill_ratio = features.fill_ratio,
+            center_column_ratio = features.center_column_ratio,
+            center_row_ratio = features.center_row_ratio,
+            cross_ratio = features.cross_ratio,
+            column_coverage = features.column_coverage,
+            row_coverage = features.row_coverage,
+            diagonal_ratio = features.diagonal_ratio,
+            quadrant_ratios = ?features.quadrant_ratios,
+            bottom_half_ratio = features.bottom_half_ratio,
+            left_aligned_rows = features.left_aligned_rows,
+            "cursor features"
+        );
+
+        if features.opaque_pixels < MIN_OPAQUE_PIXELS || !stages.enter(Stage::Components) {
+            return;
+        }
+        // Arrow + badge and scroll cursors are split into their blobs first,
+        // otherwise the extra blobs just skew the single-blob heuristics below
+        let structural = Self::detect_by_components(
+            image_data,
+            width,
+            height,
+            &thresholds,
+            map,
+            isolated,
+            feature_scratch,
+        );
+        // Stacked cursors only score as the better of the two alone, the
+        // merged pixels would match at random
+        let stacked = match structural {
+            Some(_) => None,
+            None => Self::detect_stacked(image_data, map, &thresholds, isolated, feature_scratch),
+        };
+        stages.leave(Stage::Components);
+
+        if let Some((cursor_type, score)) = stacked {
+            return stages.candidate(cursor_type, score);
+        }
+        if let Some(cursor_type) = structural {
+            stages.candidate(cursor_type, STRUCTURAL_SCORE);
+            if !stages.rank_all() {
+                return;
+            }
+        }
+
+        let features =
+            Self::matched_features(features, image_data, width, height, hollow, feature_scratch);
+        // An I-beam or arrow found by its components isn't scored a second time
+        let matchers = Self::MATCHERS
+            .into_iter()
+            .filter(|(cursor_type, _)| Some(*cursor_type) != structural);
+        for entry in matchers {
+            let stage = Stage::Matcher(entry.0);
+            if !stages.enter(stage) {
+                return;
+            }
+            let score = Self::matcher_score(
+                entry,
+                &features,
+                &thresholds,
+                image_data,
+                map,
+                isolated,
+                feature_scratch,
+            );
+            stages.leave(stage);
+            stages.candidate(entry.0, score);
+        }
+    }
+
+    /// The known type the image looks most like, with its score.
+    ///
+    /// Unlike [`Self::detect_from_image`] this always picks a type, for asking
+    /// the user to confirm what an unrecognized cursor is. The score is often
+    /// well below [`MATCH_THRESHOLD`] and is 0.0 when there is nothing to score,
+    /// e.g. for blank or tiny bitmaps, in which case the suggestion is
+    /// [`CommonCursorType::Arrow`] as the most common cursor.
+    pub fn suggest_type(image_data: &[u8], width: u32, height: u32) -> (Self, f32) {
+        Self::detect_ranked(image_data, width, height)
+            .first()
+            .copied()
+            .unwrap_or((CommonCursorType::Arrow, 0.0))
+    }
+
+    /// Detect the cursors made of several blobs by segmenting the image into
+    /// connected components: an I-beam with a link hint next to it and an
+    /// arrow with a text readout by measuring the I-beam or arrow alone, the
+    /// arrow-plus-badge family (Copy/Alias/Help/Progress/ContextMenu/
+    /// DownloadLink) by classifying the badge, any other cursor with a
+    /// spinner badge by measuring its body alone, the not-allowed circle by
+    /// its ring and slash whatever is drawn inside it, the reticle by its ring
+    /// and center dot, and the scroll cursors by their detached arrowheads
+    /// and pivot dot.
+    ///
+    /// Labels into `map`, which is left empty for images too large to segment.
+    /// `isolated` and `feature_scratch` are reused to measure single shapes
+    fn detect_by_components(
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+        thresholds: &SizeThresholds,
+        map: &mut ComponentMap,
+        isolated: &mut Vec<u8>,
+        feature_scratch: &mut FeatureScratch,
+    ) -> Option<Self> {
+        if width > 64 || height > 64 {
+            map.clear();
+            return None;
+        }
+        if !map.relabel(image_data, width, height) {
+            return None;
+        }
+        let map = &*map;
+
+        // A tooltip box can outweigh the cursor it's drawn next to, and would
+        // be scored as the cursor's body, so the cursor is scored alone
+        if let Some(cursor) = tooltip_cursor(map) {
+            if let Some((cursor_type, _)) =
+                Self::shape_features(image_data, map, cursor, isolated, feature_scratch)
+                    .and_then(|shape| Self::best_match(&shape, thresholds))
+            {
+                return Some(cursor_type);
+            }
+        }
+
+        // The hint's pixels sit low and to the side, which on the whole image
+        // reads as a hand's palm, and its corner can pass for an arrow's badge
+        if has_attached_glyph(map)
+            && Self::shape_features(image_data, map, 0, isolated, feature_scratch)
+                .is_some_and(|shape| Self::ibeam_score(&shape, thresholds) >= MATCH_THRESHOLD)
+        {
+            return Some(CommonCursorType::IBeam);
+        }
+
+        // A readout's glyphs are where a badge would be, but there are too
+        // many of them to be one
+        if has_text_readout(map)
+            && Self::shape_features(image_data, map, 0, isolated, feature_scratch)
+                .is_some_and(|shape| Self::arrow_score(&shape, thresholds) >= MATCH_THRESHOLD)
+        {
+            return Some(CommonCursorType::Arrow);
+        }
+
+        // Busy arrows are progress cursors, as the arrow-plus-badge family
+        // below. Other busy cursors are the type of their base shape
+        if has_spinner_badge(map) {
+            if let Some((base, _)) =
+                Self::shape_features(image_data, map, 0, isolated, feature_scratch)
+                    .and_then(|body| Self::best_match(&body, thresholds))
+                    .filter(|(base, _)| *base != CommonCursorType::Arrow)
+            {
+                return Some(base);
+            }
+        }
+
+        if let Some(badge) = classify_badge(map) {
+            return Some(match badge {
+                BadgeShape::Plus => CommonCursorType::Copy,
+                BadgeShape::CurvedArrow => CommonCursorType::Alias,
+                BadgeShape::QuestionMark => CommonCursorType::Help,
+                BadgeShape::Ring => CommonCursorType::Progress,
+                BadgeShape::Menu => CommonCursorType::ContextMenu,
+                BadgeShape::DownArrow => CommonCursorType::DownloadLink,
+            });
+        }
+
+        if is_slashed_circle(map, image_data) {
+            return Some(CommonCursorType::NotAllowed);
+        }
+
+        if is_reticle(map) {
+            return Some(CommonCursorType::Reticle);
+        }
+
+        Some(match classify_scroll(map)? {
+            ScrollAxis::Vertical => CommonCursorType::ScrollNS,
+            ScrollAxis::Horizontal => CommonCursorType::ScrollEW,
+        })
+    }
+
+    /// The better match of two cursors captured at once, as when the OS swaps
+    /// cursors mid-capture. The merged pixels only match the single-blob
+    /// heuristics by accident, so each of two comparably sized shapes is
+    /// cropped and scored alone. `None` unless both clear [`MATCH_THRESHOLD`]
+    /// as some type: shapes that aren't cursors on their own are parts of
+    /// one, like the corners a minimal diagonal resize cursor is drawn with.
+    ///
+    /// Called once [`Self::detect_by_components`] has labeled `map`
+    fn detect_stacked(
+        image_data: &[u8],
+        map: &ComponentMap,
+        thresholds: &SizeThresholds,
+        cropped: &mut Vec<u8>,
+        feature_scratch: &mut FeatureScratch,
+    ) -> Option<(Self, f32)> {
+        if !is_stacked(map) {
+            return None;
+        }
+
+        let mut best: Option<(Self, f32)> = None;
+        for index in 0..2 {
+            let (width, height) = crop_component(image_data, map, index, cropped);
+            let shape = CursorFeatures::extract_in(cropped, width, height, feature_scratch)?;
+            let shape_best = Self::best_match(&shape, thresholds)?;
+            if best.is_none_or(|(_, best_score)| shape_best.1 > best_score) {
+                best = Some(shape_best);
+            }
+        }
+        best
+    }
+
+    /// The best scoring of the [`Self::MATCHERS`] on a single shape, if it
+    /// clears [`MATCH_THRESHOLD`]
+    fn best_match(shape: &CursorFeatures, thresholds: &SizeThresholds) -> Option<(Self, f32)> {
+        // Strictly greater keeps the first of equal scores, like the stable sort
+        let mut best: Option<(Self, f32)> = None;
+        for (cursor_type, matcher) in Self::MATCHERS {
+            let score = matcher(shape, thresholds);
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((cursor_type, score));
+            }
+        }
+        best.filter(|(_, score)| *score >= MATCH_THRESHOLD)
+    }
+
+    /// Score of one of the [`Self::MATCHERS`]. A crosshair that misses is
+    /// scored again on the cross alone when it has a small glyph attached,
+    /// which otherwise throws off the whole image's features. `map` is the
+    /// image's labeling, empty if it wasn't segmented
+    fn matcher_score(
+        (cursor_type, matcher): (Self, Matcher),
+        features: &CursorFeatures,
+        thresholds: &SizeThresholds,
+        image_data: &[u8],
+        map: &ComponentMap,
+        isolated: &mut Vec<u8>,
+        feature_scratch: &mut FeatureScratch,
+    ) -> f32 {
+        let score = matcher(features, thresholds);
+        if cursor_type != CommonCursorType::Crosshair
+            || score >= MATCH_THRESHOLD
+            || !has_attached_glyph(map)
+        {
+            return score;
+        }
+
+        Self::shape_features(image_data, map, 0, isolated, feature_scratch).map_or(score, |cross| {
+            score.max(Self::crosshair_score(&cross, thresholds))
+        })
+    }
+
+    /// The features the matchers score: those of a cursor outline with its
+    /// interior filled in, see [`fill_hollow`], as the matchers expect filled
+    /// shapes; `features` for anything else. Outlines of plain blobs, rings
+    /// and frames, are left hollow, filled in they'd pass for a touch pointer
+    /// or a fist
+    fn matched_features(
+        features: CursorFeatures,
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+        hollow: &mut HollowScratch,
+        feature_scratch: &mut FeatureScratch,
+    ) -> CursorFeatures {
+        fill_hollow_in(image_data, width, height, hollow)
+            .and_then(|filled| CursorFeatures::extract_in(filled, width, height, feature_scratch))
+            .filter(|filled| filled.fill_ratio < MAX_HOLLOW_FILL_RATIO)
+            .unwrap_or(features)
+    }
+
+    /// Features of one component alone, e.g. the largest without whatever
+    /// glyph is drawn next to it
+    fn shape_features(
+        image_data: &[u8],
+        map: &ComponentMap,
+        index: usize,
+        isolated: &mut Vec<u8>,
+        feature_scratch: &mut FeatureScratch,
+    ) -> Option<CursorFeatures> {
+        isolate_component(image_data, map, index, isolated);
+        CursorFeatures::extract_in(isolated, map.width(), map.height(), feature_scratch)
+    }
+
+    /// Arrow cursors have their tip in the top-left of the image, a straight
+    /// left edge below the tip and most of their mass away from the bottom-right
+    fn arrow_score(features: &CursorFeatures, _: &SizeThresholds) -> f32 {
+        let (width, height) = (features.width as f32, features.height as f32);
+
+        all_of(&[
+            at_most(features.bounds.min_x as f32, width / 3.0, width / 6.0),
+            at_most(features.bounds.min_y as f32, height / 3.0, height / 6.0),
+            at_least(features.left_aligned_rows, 0.6, 0.3),
+            at_most(features.image_centroid.0, 0.45, 0.2),
+            at_most(features.quadrant_ratios[3], 0.35, 0.2),
+        ])
+    }
+
+    /// I-beam cursors are tall and narrow with an unbroken vertical stem
+    fn ibeam_score(features: &CursorFeatures, thresholds: &SizeThresholds) -> f32 {
+        all_of(&[
+            at_most(features.aspect_ratio(), 0.7, 0.4),
+            at_least(
+                features.bounds.height() as f32,
+                thresholds.min_ibeam_height,
+                thresholds.min_ibeam_height * 2.0 / 3.0,
+            ),
+            at_least(features.column_coverage, 0.9, 0.1),
+            at_least(features.center_column_ratio, 0.4, 0.3),
+            at_most((features.centroid.0 - 0.5).abs(), 0.1, 0.1),
+        ])
+    }
+
+    /// Crosshair cursors are two thin lines crossing at the center of a square
+    /// box, without the arrowheads a move cursor has at their ends
+    fn crosshair_score(features: &CursorFeatures, _: &SizeThresholds) -> f32 {
+        all_of(&[
+            within(features.aspect_ratio(), 0.75, 1.33, 0.2),
+            at_least(features.column_coverage, 0.7, 0.3),
+            at_least(features.row_coverage, 0.7, 0.3),
+            at_least(features.cross_ratio, 0.75, 0.2),
+            at_most(features.fill_ratio, 0.45, 0.3),
+            at_most(features.head_flare, 1.6, 0.6),
+        ])
+    }
+
+    /// Move cursors are a plus of shafts ending in arrowheads: unbroken center
+    /// bands like a crosshair's, but flaring out at the top and with nothing
+    /// in the corners that the diagonal arrows of a resize cursor fill. Their
+    /// horizontal arms taper off into arrowheads rather than run across the
+    /// box as the bar of a row resize cursor does
+    fn fleur_score(features: &CursorFeatures, _: &SizeThresholds) -> f32 {
+        all_of(&[
+            within(features.aspect_ratio(), 0.8, 1.25, 0.2),
+            at_least(features.column_coverage, 0.9, 0.1),
+            at_least(features.row_coverage, 0.9, 0.1),
+            at_least(features.cross_ratio, 0.6, 0.2),
+            at_least(features.head_flare, 2.0, 0.8),
+            at_most(features.corner_ratio, 0.1, 0.1),
+            at_most(features.horizontal_bars as f32, 0.5, 0.5),
+        ])
+    }
+
+    /// The macOS spinning beachball is a filled disc too, but its wedges run
+    /// through every hue, which no touch pointer's flat fill does
+    fn wait_score(features: &CursorFeatures, _: &SizeThresholds) -> f32 {
+        all_of(&[
+            within(features.aspect_ratio(), 0.85, 1.18, 0.15),
+            at_most(features.outside_ellipse_ratio, 0.05, 0.05),
+            at_least(features.core_fill, 0.9, 0.2),
+            at_least(features.saturated_ratio, 0.6, 0.2),
+            at_least(features.hue_spread, 0.7, 0.2),
+        ])
+    }
+
+    /// Touch pointers are a large filled disc: round, nothing in the corners of
+    /// the box and, unlike a spinner ring, solid all the way to the center
+    fn touch_pointer_score(features: &CursorFeatures, thresholds: &SizeThresholds) -> f32 {
+        all_of(&[
+            within(features.aspect_ratio(), 0.85, 1.18, 0.15),
+            at_most(features.hue_spread, 0.5, 0.2),
+            at_least(
+                features.bounds.height() as f32,
+                thresholds.min_disc_diameter,
+                thresholds.min_disc_diameter * 0.6,
+            ),
+            at_least(features.fill_ratio, thresholds.min_disc_fill, 0.15),
+            at_most(features.outside_ellipse_ratio, 0.03, 0.05),
+            at_least(features.core_fill, 0.9, 0.2),
+        ])
+    }
+
+    /// Pointing hand cursors have a narrow finger on top of a wider palm. Any
+    /// more fingers up there make it an open hand
+    fn hand_score(features: &CursorFeatures, thresholds: &SizeThresholds) -> f32 {
+        all_of(&[
+            at_least(features.bottom_half_ratio, 0.55, 0.2),
+            at_most(features.top_runs as f32, thresholds.hand_finger_runs, 2.0),
+            at_least(
+                features.opaque_pixels as f32,
+                thresholds.min_hand_pixels,
+                thresholds.min_hand_pixels * 2.0,
+            ),
+        ])
+    }
+
+    /// Open hands spread several fingers across the top of a roughly square
+    /// box. The gaps between the fingers keep it loosely filled, which is
+    /// what sets it apart from a fist
+    fn open_hand_score(features: &CursorFeatures, thresholds: &SizeThresholds) -> f32 {
+        all_of(&[
+            at_least(features.top_runs as f32, thresholds.hand_finger_runs, 1.0),
+            within(features.aspect_ratio(), 0.7, 1.4, 0.2),
+            within(features.fill_ratio, 0.5, 0.72, 0.15),
+            at_least(
+                features.opaque_pixels as f32,
+                thresholds.min_hand_pixels,
+                thresholds.min_hand_pixels * 2.0,
+            ),
+        ])
+    }
+
+    /// Fists are a compact, nearly square blob: densely filled unlike the open
+    /// hand, but with fuller corners than the disc of a touch pointer
+    fn fist_score(features: &CursorFeatures, thresholds: &SizeThresholds) -> f32 {
+        all_of(&[
+            at_least(features.fill_ratio, 0.75, 0.15),
+            within(features.aspect_ratio(), 0.8, 1.35, 0.2),
+            at_least(features.outside_ellipse_ratio, 0.06, 0.06),
+            at_least(features.core_fill, 0.9, 0.2),
+            at_least(
+                features.opaque_pixels as f32,
+                thresholds.min_hand_pixels,
+                thresholds.min_hand_pixels * 2.0,
+            ),
+        ])
+    }
+
+    /// Horizontal resize cursors are a wide, left-right symmetric double arrow.
+    /// Its tips are judged at the ends alone, as some frameworks put a square
+    /// handle in the middle that spreads the rest off the center line
+    fn horizontal_resize_score(features: &CursorFeatures, _: &SizeThresholds) -> f32 {
+        all_of(&[
+            at_least(features.aspect_ratio(), 1.5, 1.0),
+            at_least(features.row_coverage, 0.9, 0.1),
+            at_least(features.horizontal_tip_ratio, 0.6, 0.3),
+            at_most((features.centroid.0 - 0.5).abs(), 0.1, 0.1),
+        ])
+    }
+
+    /// Row resize cursors are a horizontal bar, the row divider, with a short
+    /// arrow pointing away from it on either side. The bar is what sets them
+    /// apart from a plain vertical double arrow, and there's only one, unlike
+    /// the pair a split cursor has. Crosshairs have a bar too, but their arms
+    /// are thin lines without arrowheads
+    fn row_resize_score(features: &CursorFeatures, _: &SizeThresholds) -> f32 {
+        all_of(&[
+            at_most(features.horizontal_bars.abs_diff(1) as f32, 0.5, 0.5),
+            within(features.bar_arm_ratio, 0.25, 0.6, 0.15),
+            within(features.aspect_ratio(), 0.75, 1.33, 0.25),
+            at_least(features.column_coverage, 0.9, 0.1),
+            at_least(features.head_flare, 2.0, 0.8),
+            at_most((features.centroid.1 - 0.5).abs(), 0.1, 0.1),
+        ])
+    }
+
+    /// Eyedroppers are a thin shaft along the top-right/bottom-left diagonal,
+    /// with the far end swelling into a rounded bulb wider than the shaft. A
+    /// shaft ending in a sharp point, like a pencil's, is only as heavy at
+    /// its top-right end as along the middle, and a double-headed arrow is as
+    /// heavy at both ends
+    fn eyedropper_score(features: &CursorFeatures, _: &SizeThresholds) -> f32 {
+        let [tip, shaft, _, bulb] = features.anti_diagonal_quarters;
+        let [top_left, _, _, bottom_right] = features.corner_ratios;
+
+        all_of(&[
+            within(features.aspect_ratio(), 0.75, 1.33, 0.25),
+            at_least(features.anti_diagonal_ratio, 0.75, 0.15),
+            at_most(top_left + bottom_right, 0.05, 0.1),
+            at_most(features.fill_ratio, 0.45, 0.2),
+            at_least(bulb / shaft.max(f32::EPSILON), 1.2, 0.4),
+            at_least(bulb / tip.max(f32::EPSILON), 1.4, 0.4),
+        ])
+    }
+
+    /// Four-way diagonal resize cursors have arrowheads in all four corners of
+    /// their box and more mass there than along the axes, which is what sets
+    /// them apart from a four-way move cross
+    fn all_corners_resize_score(features: &CursorFeatures, _: &SizeThresholds) -> f32 {
+        let min_quadrant = features
+            .quadrant_ratios
+            .iter()
+            .copied()
+            .fold(f32::INFINITY, f32::min);
+
+        all_of(&[
+            within(features.aspect_ratio(), 0.75, 1.33, 0.25),
+            at_least(features.diagonal_ratio, 0.35, 0.2),
+            at_least(features.anti_diagonal_ratio, 0.35, 0.2),
+            at_least(min_quadrant, 0.18, 0.1),
+            at_least(features.corner_ratio, 0.4, 0.2),
+            at_least(features.corner_ratio - features.cross_ratio, 0.1, 0.2),
+        ])
+    }
+
+    /// Diagonal resize cursors sit on the top-left/bottom-right diagonal with
+    /// both of its ends occupied and the opposite corners empty. Only the
+    /// corners need to be, so a handle square at the center is allowed
+    fn diagonal_resize_score(features: &CursorFeatures, _: &SizeThresholds) -> f32 {
+        let [top_left, _, _, bottom_right] = features.quadrant_ratios;
+        let [_, top_right, bottom_left, _] = features.corner_ratios;
+
+        all_of(&[
+            within(features.aspect_ratio(), 0.6, 1.66, 0.3),
+            at_least(features.diagonal_ratio, 0.6, 0.3),
+            at_least(top_left, 0.2, 0.2),
+            at_least(bottom_right, 0.2, 0.2),
+            at_most(top_right + bottom_left, 0.05, 0.1),
+        ])
+    }
+}
+
+/// Pixel layout of a bitmap passed to [`CommonCursorType::detect_from_pixels`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CursorPixelFormat {
+    /// 4 bytes per pixel with straight alpha, as cursors are captured
+    #[default]
+    Rgba8,
+    /// 1 byte of luminance per pixel, e.g. a crop of an NV12 frame's Y plane,
+    /// which spares converting the frame to RGBA. Without alpha the cursor's
+    /// shape is a luminance-threshold silhouette (see [`luma_silhouette`]), so
+    /// the alpha-based steps like [`normalize_alpha`] don't apply
+    Gray8,
+}
+
+/// Score at which a heuristic's thresholds are exactly met
+pub const MATCH_THRESHOLD: f32 = 0.5;
+
+/// Time spent in each stage of [`CommonCursorType::detect_profiled`], in nanoseconds
+#[cfg(feature = "profiling")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MatcherTimings {
+    /// Alpha normalization and feature extraction
+    pub feature_extraction_ns: u64,
+    /// Segmenting the image to look for an arrow badge, a slashed circle or
+    /// scroll arrowheads
+    pub badge_ns: u64,
+    /// Each single-blob matcher, in priority order. Empty when detection
+    /// stopped early, e.g. because a badge, not-allowed or scroll cursor was found
+    pub matchers: Vec<(CommonCursorType, u64)>,
+}
+
+/// Buffers reused across [`CommonCursorType::detect_from_image_in`] calls.
+///
+/// Holds copies of the cursor for alpha normalization and for measuring a
+/// crosshair without its attached glyph, the feature
+/// accumulators and the component labeling state, each grown to the largest
+/// cursor seen so far. Allocate one per capture thread and keep it across frames.
+#[derive(Debug, Default)]
+pub struct DetectionScratch {
+    alpha: Vec<u8>,
+    magnified: Vec<u8>,
+    hollow: HollowScratch,
+    isolated: Vec<u8>,
+    features: FeatureScratch,
+    components: ComponentMap,
+}
+
+impl DetectionScratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// A stage of [`CommonCursorType::detect_staged`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    /// Normalizing the cursor and extracting its features
+    Features,
+    /// Segmenting the image for the cursors made of several blobs
+    Components,
+    /// Scoring the matcher of one type
+    Matcher(CommonCursorType),
+}
+
+/// How an entry point hooks into [`CommonCursorType::detect_staged`]: what
+/// it keeps of the candidates, and whether each stage gets to run
+trait DetectionStages {
+    /// A type the image matched with its score, in priority order for equal
+    /// scores
+    fn candidate(&mut self, cursor_type: CommonCursorType, score: f32);
+
+    /// Whether to run `stage`. Once one is skipped detection stops there,
+    /// with the candidates found so far
+    fn enter(&mut self, _stage: Stage) -> bool {
+        true
+    }
+
+    /// Called once `stage` has run
+    fn leave(&mut self, _stage: Stage) {}
+
+    /// Whether the matchers still run once the components gave the type, to
+    /// rank the other types behind it
+    fn rank_all(&self) -> bool {
+        false
+    }
+}
+
+/// Keeps the best candidate only, without allocating
+#[derive(Debug, Default)]
+struct BestMatch(Option<(CommonCursorType, f32)>);
+
+impl BestMatch {
+    /// The best candidate, if it clears [`MATCH_THRESHOLD`]
+    fn matched(&self) -> Option<(CommonCursorType, f32)> {
+        self.0.filter(|(_, score)| *score >= MATCH_THRESHOLD)
+    }
+}
+
+impl DetectionStages for BestMatch {
+    fn candidate(&mut self, cursor_type: CommonCursorType, score: f32) {
+        // Strictly greater keeps the first of equal scores, like the stable sort
+        if self.0.is_none_or(|(_, best_score)| score > best_score) {
+            self.0 = Some((cursor_type, score));
+        }
+    }
+}
+
+/// Keeps every candidate, for [`CommonCursorType::detect_ranked`]
+#[derive(Debug, Default)]
+struct Ranking(Vec<(CommonCursorType, f32)>);
+
+impl DetectionStages for Ranking {
+    fn candidate(&mut self, cursor_type: CommonCursorType, score: f32) {
+        self.0.push((cursor_type, score));
+    }
+
+    fn rank_all(&self) -> bool {
+        true
+    }
+}
+
+/// The best candidate found before `budget` ran out, for
+/// [`CommonCursorType::detect_within`]
+struct Budgeted {
+    best: BestMatch,
+    start: Instant,
+    budget: Duration,
+}
+
+impl DetectionStages for Budgeted {
+    fn candidate(&mut self, cursor_type: CommonCursorType, score: f32) {
+        self.best.candidate(cursor_type, score);
+    }
+
+    fn enter(&mut self, _stage: Stage) -> bool {
+        self.start.elapsed() < self.budget
+    }
+}
+
+/// The best candidate and how long each stage took, for
+/// [`CommonCursorType::detect_profiled`]
+#[cfg(feature = "profiling")]
+struct Profiled {
+    best: BestMatch,
+    timings: MatcherTimings,
+    stage_start: Instant,
+}
+
+#[cfg(feature = "profiling")]
+impl DetectionStages for Profiled {
+    fn candidate(&mut self, cursor_type: CommonCursorType, score: f32) {
+        self.best.candidate(cursor_type, score);
+    }
+
+    fn enter(&mut self, _stage: Stage) -> bool {
+        self.stage_start = Instant::now();
+        true
+    }
+
+    fn leave(&mut self, stage: Stage) {
+        let elapsed_ns = self.stage_start.elapsed().as_nanos() as u64;
+        match stage {
+            Stage::Features => self.timings.feature_extraction_ns = elapsed_ns,
+            Stage::Components => self.timings.badge_ns = elapsed_ns,
+            Stage::Matcher(cursor_type) => self.timings.matchers.push((cursor_type, elapsed_ns)),
+        }
+    }
+}
+
+/// Remaps a detected type, see [`DetectionConfig::type_mapper`]
+pub type TypeMapper = fn(CommonCursorType) -> CommonCursorType;
+
+/// Result of [`CommonCursorType::detect_mapped`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MappedDetection {
+    /// Type the detector picked
+    pub raw: CommonCursorType,
+    /// `raw` after the config's type mapper, the same type without one
+    pub mapped: CommonCursorType,
+}
+
+/// Margin within which [`classify`] reports the top candidates as
+/// [`DetectionOutcome::Ambiguous`] rather than picking one
+pub const AMBIGUOUS_MARGIN: f32 = 0.05;
+
+/// Everything [`classify`] made of a cursor bitmap, one variant per case a
+/// caller may want to handle differently
+#[derive(Debug, Clone, PartialEq)]
+pub enum DetectionOutcome {
+    /// One type clearly won, scoring `confidence`
+    Detected {
+        ty: CommonCursorType,
+        confidence: f32,
+    },
+    /// Several types scored within the margin of the best, see
+    /// [`AMBIGUOUS_MARGIN`]. Best first, with their scores
+    Ambiguous {
+        candidates: Vec<(CommonCursorType, f32)>,
+    },
+    /// There is no cursor bitmap at all, as when the OS isn't drawing one
+    Hidden,
+    /// A fully transparent bitmap, [`CommonCursorType::Blank`]: the app hides
+    /// the cursor on purpose, e.g. to draw its own
+    Blank,
+    /// The best candidate scored below the config's minimum. `best` is `None`
+    /// when nothing could be scored at all, e.g. a cursor a few pixels big
+    LowConfidence {
+        best: Option<CommonCursorType>,
+        confidence: f32,
+    },
+    /// The bitmap is empty or too short for an RGBA buffer of the given size
+    Error(CursorError),
+}
+
+impl DetectionOutcome {
+    /// This outcome as a consumer requiring at least `min_confidence` sees
+    /// it: a detection or the best of ambiguous candidates scoring below it
+    /// is [`DetectionOutcome::LowConfidence`], anything else is unchanged.
+    ///
+    /// The outcome [`classify_with_min_confidence`] gives for the same bar,
+    /// for gating one classification per consumer after the fact. A bar can
+    /// only be raised this way, a `LowConfidence` outcome stays one
+    pub fn with_min_confidence(self, min_confidence: f32) -> Self {
+        let (best, confidence) = match &self {
+            DetectionOutcome::Detected { ty, confidence } => (*ty, *confidence),
+            DetectionOutcome::Ambiguous { candidates } => match candidates.first() {
+                Some(&best) => best,
+                None => return self,
+            },
+            _ => return self,
+        };
+        if confidence < min_confidence {
+            return DetectionOutcome::LowConfidence {
+                best: Some(best),
+                confidence,
+            };
+        }
+        self
+    }
+}
+
+/// Classify a cursor bitmap into a [`DetectionOutcome`], with the default
+/// [`DetectionConfig`] and top candidates within [`AMBIGUOUS_MARGIN`]
+/// reported as ambiguous
+pub fn classify(image_data: &[u8], width: u32, height: u32) -> DetectionOutcome {
+    let config = DetectionConfig::strict(AMBIGUOUS_MARGIN);
+    classify_with_config(image_data, width, height, &config)
+}
+
+/// Like [`classify`] under `config`: below its `min_score` the outcome is
+/// [`DetectionOutcome::LowConfidence`], within its `strict_margin`, if any,
+/// [`DetectionOutcome::Ambiguous`]. Types are passed through its type mapper,
+/// scores are confidences if it has a calibration. Consumers needing more
+/// confidence than `min_score` can classify with
+/// [`classify_with_min_confidence`], or gate the outcome further with
+/// [`DetectionOutcome::with_min_confidence`]
+pub fn classify_with_config(
+    image_data: &[u8],
+    width: u32,
+    height: u32,
+    config: &DetectionConfig,
+) -> DetectionOutcome {
+    classify_with_min_confidence(image_data, width, height, config, config.min_score)
+}
+
+/// Like [`classify_with_config`], with the bar for a detection raised to
+/// `min_confidence` for this call only, so one config serves consumers with
+/// different bars, e.g. a live preview taking any guess and an export that
+/// doesn't. A `min_confidence` below the config's `min_score` doesn't lower
+/// it.
+///
+/// The bar is checked before the config's `strict_margin`: a best candidate
+/// below it is [`DetectionOutcome::LowConfidence`] however close the runner
+/// up, and one above it is [`DetectionOutcome::Ambiguous`] only within the
+/// margin, same as without a `min_confidence`. Raising the bar never turns a
+/// detection ambiguous
+pub fn classify_with_min_confidence(
+    image_data: &[u8],
+    width: u32,
+    height: u32,
+    config: &DetectionConfig,
+    min_confidence: f32,
+) -> DetectionOutcome {
+    if width == 0 && height == 0 && image_data.is_empty() {
+        return DetectionOutcome::Hidden;
+    }
+    if width == 0 || height == 0 || image_data.len() < width as usize * height as usize * 4 {
+        return DetectionOutcome::Error(CursorError::InvalidSize { width, height });
+    }
+    if is_blank(image_data, width, height) {
+        return DetectionOutcome::Blank;
+    }
+
+    let ranked =
+        CommonCursorType::ranked_with_config(image_data, width, height, config).unwrap_or_default();
+    let Some(&(best, confidence)) = ranked.first() else {
+        return DetectionOutcome::LowConfidence {
+            best: None,
+            confidence: 0.0,
+        };
+    };
+    if confidence < config.min_score.max(min_confidence) {
+        return DetectionOutcome::LowConfidence {
+            best: Some(config.map_type(best)),
+            confidence,
+        };
+    }
+
+    if let Some(margin) = config.strict_margin {
+        if confidence - CommonCursorType::runner_up_score(&ranked) < margin {
+            let candidates = ranked
+                .iter()
+                .filter(|(cursor_type, score)| {
+                    confidence - score < margin && !CommonCursorType::badge_body(best, *cursor_type)
+                })
+                .map(|&(cursor_type, score)| (config.map_type(cursor_type), score))
+                .collect();
+            return DetectionOutcome::Ambiguous { candidates };
+        }
+    }
+
+    DetectionOutcome::Detected {
+        ty: config.map_type(best),
+        confidence,
+    }
+}
+
+/// Largest image side, after demagnifying, detected with the
+/// [`SizeBuckets::small`] thresholds, halfway between 16px and 32px cursors
+pub const SMALL_CURSOR_MAX_SIZE: u32 = 24;
+
+/// Largest image side detected with the [`SizeBuckets::medium`] thresholds,
+/// halfway between 32px and 64px cursors. Anything bigger is large
+pub const MEDIUM_CURSOR_MAX_SIZE: u32 = 48;
+
+/// The thresholds of the matchers that measure a cursor in pixels, where a
+/// ratio doesn't work, for one range of cursor sizes.
+///
+/// Each is where its heuristic's score crosses [`MATCH_THRESHOLD`]; the
+/// ramp up to a full score above it scales along.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SizeThresholds {
+    /// Fewest opaque pixels a hand, pointing, open or closed, is drawn with
+    pub min_hand_pixels: f32,
+    /// Shortest an I-beam's stem is, in pixels
+    pub min_ibeam_height: f32,
+    /// Smallest diameter of a touch pointer's disc, in pixels
+    pub min_disc_diameter: f32,
+    /// Least share of its box a touch pointer's disc fills. The anti-aliased
+    /// rim takes up more of a small disc's box
+    pub min_disc_fill: f32,
+    /// Separate runs across a hand's top row from which it's an open hand
+    /// rather than a pointing one. Fingers merge at small sizes
+    pub hand_finger_runs: f32,
+}
+
+impl SizeThresholds {
+    /// Thresholds for 16px cursors
+    pub const SMALL: Self = Self {
+        min_hand_pixels: 40.0,
+        min_ibeam_height: 6.0,
+        min_disc_diameter: 5.0,
+        min_disc_fill: 0.6,
+        hand_finger_runs: 2.5,
+    };
+
+    /// Thresholds for 32px cursors, what the matchers were tuned on
+    pub const MEDIUM: Self = Self {
+        min_hand_pixels: 50.0,
+        min_ibeam_height: 12.0,
+        min_disc_diameter: 10.0,
+        min_disc_fill: 0.7,
+        hand_finger_runs: 3.0,
+    };
+
+    /// Thresholds for 64px cursors
+    pub const LARGE: Self = Self {
+        min_hand_pixels: 200.0,
+        min_ibeam_height: 24.0,
+        min_disc_diameter: 20.0,
+        min_disc_fill: 0.7,
+        hand_finger_runs: 3.0,
+    };
+
+    /// One-line listing for [`DetectionConfig::report`]
+    fn describe(&self) -> String {
+        format!(
+            "hand pixels {:.1}, ibeam height {:.1}, disc diameter {:.1}, disc fill {:.2}, hand finger runs {:.1}",
+            self.min_hand_pixels,
+            self.min_ibeam_height,
+            self.min_disc_diameter,
+            self.min_disc_fill,
+            self.hand_finger_runs
+        )
+    }
+}
+
+/// [`SizeThresholds`] for small, medium and large cursors, picked by the
+/// size of the image being detected once a magnified cursor is shrunk back,
+/// see [`SMALL_CURSOR_MAX_SIZE`] and [`MEDIUM_CURSOR_MAX_SIZE`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SizeBuckets {
+    pub small: SizeThresholds,
+    pub medium: SizeThresholds,
+    pub large: SizeThresholds,
+}
+
+impl SizeBuckets {
+    /// The thresholds for a `width` by `height` image, by its larger side
+    pub fn for_size(&self, width: u32, height: u32) -> SizeThresholds {
+        match width.max(height) {
+            size if size <= SMALL_CURSOR_MAX_SIZE => self.small,
+            size if size <= MEDIUM_CURSOR_MAX_SIZE => self.medium,
+            _ => self.large,
+        }
+    }
+}
+
+impl Default for SizeBuckets {
+    fn default() -> Self {
+        Self {
+            small: SizeThresholds::SMALL,
+            medium: SizeThresholds::MEDIUM,
+            large: SizeThresholds::LARGE,
+        }
+    }
+}
+
+/// Thresholds applied to the ranked scores when picking a single cursor type.
+/// Not `PartialEq`, function pointers don't compare reliably
+#[derive(Debug, Clone, Copy)]
+pub struct DetectionConfig {
+    /// Lowest score the best candidate needs to be reported
+    pub min_score: f32,
+    /// Strict mode: when set, detection returns `None` unless the best candidate
+    /// beats the runner-up by at least this much
+    pub strict_margin: Option<f32>,
+    /// Move the opaque bounding box to the image origin before scoring, so
+    /// captures jittering by a pixel or two get identical results. See
+    /// [`normalize_translation`]
+    pub normalize_translation: bool,
+    /// Applied to the picked type, to collapse or rename types for consumers
+    /// with coarser categories, e.g. [`CommonCursorType::canonical_resize`].
+    /// Thresholds are checked against the detected type, before mapping
+    pub type_mapper: Option<TypeMapper>,
+    /// Turns the raw scores into calibrated confidences before the best
+    /// candidate is picked, so `min_score` and `strict_margin` are
+    /// probabilities that mean the same for every type. Raw scores if unset
+    pub calibration: Option<Calibration>,
+    /// Desaturate subpixel anti-aliasing fringes before scoring, see
+    /// [`defringe`], for cursors captured with LCD font smoothing
+    pub defringe: bool,
+    /// Score the [`skeletonize`]d image when the image itself matches no
+    /// type, so line-art cursors drawn with a heavier stroke than the
+    /// matchers expect are still recognized. Thinning takes a pass over the
+    /// image per pixel of stroke width, hence off by default
+    pub skeletonize: bool,
+    /// Matcher thresholds by cursor size, so small cursors aren't held to
+    /// pixel counts only larger ones reach
+    pub size_buckets: SizeBuckets,
+}
+
+impl DetectionConfig {
+    /// Config that rejects ambiguous results where the top two candidates
+    /// score within `margin` of each other
+    pub fn strict(margin: f32) -> Self {
+        Self {
+            strict_margin: Some(margin),
+            ..Self::default()
+        }
+    }
+
+    /// Pick and threshold candidates by confidence under `calibration`, e.g.
+    /// one fitted on a specific OS's cursors. [`Calibration::default`] is the
+    /// one fitted on the bundled fixtures
+    pub fn set_calibration(&mut self, calibration: Calibration) {
+        self.calibration = Some(calibration);
+    }
+
+    /// `cursor_type` passed through [`Self::type_mapper`], if any
+    pub fn map_type(&self, cursor_type: CommonCursorType) -> CommonCursorType {
+        self.type_mapper
+            .map_or(cursor_type, |mapper| mapper(cursor_type))
+    }
+
+    /// Human-readable listing of every setting and its value, each marked
+    /// when it differs from [`Self::default`], followed by the lowest raw
+    /// matcher score each type needs to be reported. Meant for checking
+    /// which config a running app ended up with after its layers were
+    /// merged, not for parsing
+    pub fn report(&self) -> String {
+        let defaults = Self::default();
+        let mut report = String::new();
+        let mut line = |name: &str, value: String, default: String| {
+            report.push_str(&format!("{name}: {value}"));
+            if value != default {
+                report.push_str(&format!(" (overridden, default {default})"));
+            }
+            report.push('\n');
+        };
+        let optional = |value: Option<f32>| value.map_or("none".to_string(), |v| format!("{v:.4}"));
+        let calibration = |calibration: Option<Calibration>| match calibration {
+            None => "none".to_string(),
+            Some(calibration) if calibration == Calibration::default() => "fitted".to_string(),
+            Some(_) => "custom".to_string(),
+        };
+        let mapped = |config: &Self| {
+            config.type_mapper.map_or("none".to_string(), |_| {
+                let changed: Vec<_> = CommonCursorType::ALL
+                    .iter()
+                    .filter(|&&cursor_type| config.map_type(cursor_type) != cursor_type)
+                    .map(|&cursor_type| {
+                        format!("{cursor_type:?} -> {:?}", config.map_type(cursor_type))
+                    })
+                    .collect();
+                if changed.is_empty() {
+                    "identity".to_string()
+                } else {
+                    changed.join(", ")
+                }
+            })
+        };
+
+        line(
+            "min_score",
+            format!("{:.4}", self.min_score),
+            format!("{:.4}", defaults.min_score),
+        );
+        line(
+            "strict_margin",
+            optional(self.strict_margin),
+            optional(defaults.strict_margin),
+        );
+        line(
+            "normalize_translation",
+            self.normalize_translation.to_string(),
+            defaults.normalize_translation.to_string(),
+        );
+        line(
+            "defringe",
+            self.defringe.to_string(),
+            defaults.defringe.to_string(),
+        );
+        line(
+            "skeletonize",
+            self.skeletonize.to_string(),
+            defaults.skeletonize.to_string(),
+        );
+        line("type_mapper", mapped(self), mapped(&defaults));
+        line(
+            "calibration",
+            calibration(self.calibration),
+            calibration(defaults.calibration),
+        );
+        for (bucket, thresholds, default) in [
+            (
+                "small",
+                self.size_buckets.small,
+                defaults.size_buckets.small,
+            ),
+            (
+                "medium",
+                self.size_buckets.medium,
+                defaults.size_buckets.medium,
+            ),
+            (
+                "large",
+                self.size_buckets.large,
+                defaults.size_buckets.large,
+            ),
+        ] {
+            line(
+                &format!("size_buckets.{bucket}"),
+                thresholds.describe(),
+                default.describe(),
+            );
+        }
+
+        report.push_str("min raw score per type:\n");
+        for &cursor_type in CommonCursorType::ALL {
+            let coefficients = self
+                .calibration
+                .and_then(|calibration| calibration.coefficients(cursor_type));
+            let needed = match coefficients {
+                None => format!("{:.4}", self.min_score),
+                Some(coefficients) => match raw_score_for(coefficients, self.min_score) {
+                    Some(raw) => format!(
+                        "{raw:.4} (slope {:.3}, intercept {:.3})",
+                        coefficients.slope, coefficients.intercept
+                    ),
+                    None => "n/a".to_string(),
+                },
+            };
+            report.push_str(&format!("  {cursor_type:?}: {needed}\n"));
+        }
+        report
+    }
+
+    /// Override the fields `other` sets, keeping the rest. Merging layer after
+    /// layer, e.g. OS defaults then user settings, gives the effective config
+    pub fn merge(&mut self, other: PartialDetectionConfig) {
+        let PartialDetectionConfig {
+            min_score,
+            strict_margin,
+            normalize_translation,
+            type_mapper,
+            calibration,
+            defringe,
+            skeletonize,
+            size_buckets,
+        } = other;
+        if let Some(min_score) = min_score {
+            self.min_score = min_score;
+        }
+        if let Some(strict_margin) = strict_margin {
+            self.strict_margin = strict_margin;
+        }
+        if let Some(normalize_translation) = normalize_translation {
+            self.normalize_translation = normalize_translation;
+        }
+        if let Some(type_mapper) = type_mapper {
+            self.type_mapper = type_mapper;
+        }
+        if let Some(calibration) = calibration {
+            self.calibration = calibration;
+        }
+        if let Some(defringe) = defringe {
+            self.defringe = defringe;
+        }
+        if let Some(skeletonize) = skeletonize {
+            self.skeletonize = skeletonize;
+        }
+        if let Some(size_buckets) = size_buckets {
+            self.size_buckets = size_buckets;
+        }
+    }
+}
+
+/// Raw score from which `coefficients` give a confidence of at least
+/// `min_confidence`, `None` when no score reaches it or the confidence falls
+/// as the score rises. Every score does for a threshold of 0
+fn raw_score_for(coefficients: LogisticCoefficients, min_confidence: f32) -> Option<f32> {
+    if min_confidence <= 0.0 {
+        return Some(f32::NEG_INFINITY);
+    }
+    if min_confidence >= 1.0 || coefficients.slope <= 0.0 {
+        return None;
+    }
+    let logit = (min_confidence / (1.0 - min_confidence)).ln();
+    Some((logit - coefficients.intercept) / coefficients.slope)
+}
+
+/// A layer of [`DetectionConfig`] overrides for [`DetectionConfig::merge`]:
+/// `None` fields leave the config's value alone. Fields that are optional in
+/// the config are doubly so here, `Some(None)` unsets them
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PartialDetectionConfig {
+    pub min_score: Option<f32>,
+    pub strict_margin: Option<Option<f32>>,
+    pub normalize_translation: Option<bool>,
+    pub type_mapper: Option<Option<TypeMapper>>,
+    pub calibration: Option<Option<Calibration>>,
+    pub defringe: Option<bool>,
+    pub skeletonize: Option<bool>,
+    pub size_buckets: Option<SizeBuckets>,
+}
+
+impl Default for DetectionConfig {
+    fn default() -> Self {
+        Self {
+            min_score: MATCH_THRESHOLD,
+            strict_margin: None,
+            normalize_translation: false,
+            type_mapper: None,
+            calibration: None,
+            defringe: false,
+            skeletonize: false,
+            size_buckets: SizeBuckets::default(),
+        }
+    }
+}
+
+/// How clearly `value` clears a lower bound: [`MATCH_THRESHOLD`] exactly at `min`,
+/// rising to 1.0 once it is `range` above and falling to 0.0 when `range` below
+fn at_least(value: f32, min: f32, range: f32) -> f32 {
+    (MATCH_THRESHOLD + (value - min) / range * 0.5).clamp(0.0, 1.0)
+}
+
+/// Like [`at_least`], for an upper bound
+fn at_most(value: f32, max: f32, range: f32) -> f32 {
+    at_least(-value, -max, range)
+}
+
+fn within(value: f32, min: f32, max: f32, range: f32) -> f32 {
+    at_least(value, min, range).min(at_most(value, max, range))
+}
+
+/// A pattern is only as good a match as its weakest condition
+fn all_of(scores: &[f32]) -> f32 {
+    scores.iter().copied().fold(1.0, f32::min)
+}
+
+/// Peak opacity below which a cursor is reported as dimmed
+pub const DIMMED_OPACITY: f32 = 0.9;
+
+/// Result of analyzing a captured cursor bitmap. Serializable so a
+/// recording's analyses can be saved with [`crate::cursor::analysis_log`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CursorAnalysis {
+    pub cursor_type: Option<CommonCursorType>,
+    /// Score of the best candidate in [`CommonCursorType::detect_ranked`],
+    /// which is `cursor_type` when it clears [`MATCH_THRESHOLD`]. Kept even
+    /// below it, so a replayed log shows how close a miss was. 0.0 when
+    /// nothing could be scored
+    pub score: f32,
+    /// Bounds of the cursor's opaque pixels, once dimmed cursors are brought
+    /// back to full opacity. `None` for a blank bitmap
+    pub bounds: Option<Rect>,
+    /// Pixel in the bitmap the detected type's hotspot falls on, its
+    /// [`CommonCursorType::hotspot`] scaled to the bitmap's size and rounded.
+    /// `None` without a type
+    pub hotspot: Option<Point>,
+    /// Opacity of the cursor's most opaque pixels, 1.0 for a normal cursor
+    pub opacity: f32,
+    /// Whether the whole cursor is drawn translucent, as apps do to show that
+    /// the UI under it is disabled. The renderer should dim the cursor to match
+    pub dimmed: bool,
+    /// Whether the cursor's shape touches the bitmap's border, which is what a
+    /// cursor cut off at the edge of the screen looks like. The bitmap is then
+    /// only part of the cursor and its type is less reliable
+    pub is_clipped: bool,
+    /// Whether a crosshair or I-beam is drawn with a small glyph next to it,
+    /// like the coordinate readouts and tool hints of CAD apps or the link
+    /// hint over selectable link text, or an arrow with a text readout as in
+    /// the precision mode of design tools. The glyph is ignored for
+    /// `cursor_type`, this only reports that it's there
+    pub has_attached_glyph: bool,
+    /// Whether the bitmap holds two cursors side by side, as in a capture
+    /// taken mid-swap. `cursor_type` is then the better match of the two
+    /// shapes scored separately
+    pub is_stacked: bool,
+    /// Whether the cursor is blown up by the OS screen magnifier, see
+    /// [`is_magnified`]. Bundled SVGs are drawn at the renderer's scale
+    /// anyway; a caller drawing the captured bitmap may want to shrink it
+    pub magnified: bool,
+    /// Whether a spinner badge is drawn next to the cursor, the busy state
+    /// Windows overlays on whatever cursor is current. `cursor_type` is that
+    /// of the base shape, except for the arrow: a busy arrow is
+    /// [`CommonCursorType::Progress`], whose bundled SVG draws the spinner
+    pub busy: bool,
+}
+
+/// Detect a cursor's type along with the state it's shown in
+pub fn analyze_cursor(image_data: &[u8], width: u32, height: u32) -> CursorAnalysis {
+    // The type detect_from_image picks, with the score it's picked by
+    let best = CommonCursorType::detect_ranked(image_data, width, height)
+        .first()
+        .copied();
+    let cursor_type = best
+        .filter(|(_, score)| *score >= MATCH_THRESHOLD)
+        .map(|(cursor_type, _)| cursor_type);
+    let hotspot = cursor_type
+        .map(|cursor_type| Size::new(width, height).scale_hotspot(cursor_type.hotspot()));
+
+    let (normalized, opacity) = normalize_alpha(image_data, width, height);
+    let features = CursorFeatures::extract(&normalized, width, height);
+    let is_clipped = features
+        .as_ref()
+        .is_some_and(|features| features.touches_border());
+    let bounds = features.map(|features| {
+        let bounds = features.bounds;
+        Rect::new(
+            (bounds.min_x as i32, bounds.min_y as i32),
+            (bounds.width(), bounds.height()),
+        )
+    });
+
+    // The components are labeled on the shrunk copy of a magnified cursor,
+    // as detection does
+    let mut buffer = Vec::new();
+    let (shape, shape_width, shape_height) = demagnify_in(&normalized, width, height, &mut buffer);
+    let thresholds = SizeBuckets::default().for_size(shape_width, shape_height);
+    let magnified = (shape_width, shape_height) != (width, height);
+
+    let has_attached_glyph = match cursor_type {
+        Some(CommonCursorType::Crosshair | CommonCursorType::IBeam) => {
+            ComponentMap::from_rgba(shape, shape_width, shape_height)
+                .is_some_and(|map| has_attached_glyph(&map))
+        }
+        Some(CommonCursorType::Arrow) => ComponentMap::from_rgba(shape, shape_width, shape_height)
+            .is_some_and(|map| has_text_readout(&map)),
+        _ => false,
+    };
+
+    // Same steps as detection, which only looks for two cursors when the
+    // components match nothing structurally
+    let mut map = ComponentMap::default();
+    let (mut isolated, mut feature_scratch) = (Vec::new(), FeatureScratch::default());
+    let is_stacked = CommonCursorType::detect_by_components(
+        shape,
+        shape_width,
+        shape_height,
+        &thresholds,
+        &mut map,
+        &mut isolated,
+        &mut feature_scratch,
+    )
+    .is_none()
+        && CommonCursorType::detect_stacked(
+            shape,
+            &map,
+            &thresholds,
+            &mut isolated,
+            &mut feature_scratch,
+        )
+        .is_some();
+    let busy = has_spinner_badge(&map);
+
+    CursorAnalysis {
+        cursor_type,
+        score: best.map_or(0.0, |(_, score)| score),
+        bounds,
+        hotspot,
+        opacity,
+        dimmed: opacity < DIMMED_OPACITY,
+        is_clipped,
+        has_attached_glyph,
+        is_stacked,
+        magnified,
+        busy,
+    }
+}
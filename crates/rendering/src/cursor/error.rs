@@ -0,0 +1,37 @@
+use crate::cursor::detection::CommonCursorType;
+use std::path::PathBuf;
+
+/// Errors from loading, rasterizing and compositing cursors
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum CursorError {
+    #[error("Invalid SVG: {0}")]
+    InvalidSvg(String),
+    #[error("SVG is {len} bytes, more than the {max} byte limit")]
+    SvgTooLarge { len: usize, max: usize },
+    #[error("SVG rendering isn't available, it needs the svg feature")]
+    SvgUnavailable,
+    #[error("No SVG available for cursor type: {0:?}")]
+    MissingSvg(CommonCursorType),
+    #[error("Invalid target size: {width}x{height}")]
+    InvalidSize { width: u32, height: u32 },
+    #[error("Invalid cursor scale: {0}")]
+    InvalidScale(f32),
+    #[error("Invalid crossfade weight: {0}")]
+    InvalidCrossfadeWeight(f32),
+    #[error("Invalid cursor opacity: {0}")]
+    InvalidOpacity(f32),
+    #[error("Invalid cursor tile: {0}")]
+    InvalidTile(String),
+    #[error("Failed to read {}: {message}", path.display())]
+    Read { path: PathBuf, message: String },
+    #[error("Failed to write {}: {message}", path.display())]
+    Write { path: PathBuf, message: String },
+    #[error("Invalid cursor archive: {0}")]
+    InvalidArchive(String),
+    #[error("Invalid ANI cursor: {0}")]
+    InvalidAni(String),
+    #[error("Invalid cursor analysis log: {0}")]
+    InvalidAnalysisLog(String),
+    #[error("System cursors can't be captured on {0}")]
+    UnsupportedPlatform(String),
+}
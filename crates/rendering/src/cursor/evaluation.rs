@@ -0,0 +1,185 @@
+use crate::cursor::detection::{CommonCursorType, DetectionConfig};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Something that tells the type of a captured cursor from its RGBA pixels.
+///
+/// Lets differently configured detectors, or ones built entirely outside this
+/// crate, be compared on the same labeled corpus with [`evaluate_detector`].
+/// Closures taking the image data, width and height are detectors too.
+pub trait CursorDetector {
+    /// The detected type, `None` if no cursor could be made out
+    fn detect(&self, image_data: &[u8], width: u32, height: u32) -> Option<CommonCursorType>;
+}
+
+/// The heuristic detector with these thresholds, see
+/// [`CommonCursorType::detect_with_config`]
+impl CursorDetector for DetectionConfig {
+    fn detect(&self, image_data: &[u8], width: u32, height: u32) -> Option<CommonCursorType> {
+        CommonCursorType::detect_with_config(image_data, width, height, self)
+    }
+}
+
+impl<F> CursorDetector for F
+where
+    F: Fn(&[u8], u32, u32) -> Option<CommonCursorType>,
+{
+    fn detect(&self, image_data: &[u8], width: u32, height: u32) -> Option<CommonCursorType> {
+        self(image_data, width, height)
+    }
+}
+
+/// How well a detector did on one cursor type
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TypeMetrics {
+    pub cursor_type: CommonCursorType,
+    /// Cursors of this type detected as it
+    pub true_positives: usize,
+    /// Cursors of other types detected as this one
+    pub false_positives: usize,
+    /// Cursors of this type detected as another type or as nothing
+    pub false_negatives: usize,
+    /// Share of the detections of this type that were right, `None` if the
+    /// type was never detected
+    pub precision: Option<f32>,
+    /// Share of the cursors of this type that were detected as it, `None` if
+    /// the corpus has none
+    pub recall: Option<f32>,
+}
+
+/// Results of [`evaluate_detector`]. Serializable, so results of different
+/// detectors or runs can be kept and compared later
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalReport {
+    /// Number of labeled cursors evaluated
+    pub samples: usize,
+    /// Number of them detected as their label
+    pub correct: usize,
+    /// Share of the samples detected as their label, 0.0 for an empty corpus
+    pub accuracy: f32,
+    /// Every type that was expected or detected at least once, in type order
+    pub per_type: Vec<TypeMetrics>,
+}
+
+impl EvalReport {
+    /// Metrics of one type, `None` if it was neither expected nor detected
+    pub fn metrics(&self, cursor_type: CommonCursorType) -> Option<&TypeMetrics> {
+        self.per_type
+            .iter()
+            .find(|metrics| metrics.cursor_type == cursor_type)
+    }
+}
+
+/// Run a detector on every labeled cursor of a corpus and count how often it
+/// got each type right, for choosing between detectors on e.g. one OS's
+/// cursors. A cursor detected as nothing only counts against its label's
+/// recall
+pub fn evaluate_detector(
+    detector: &dyn CursorDetector,
+    corpus: &[(RgbaImage, CommonCursorType)],
+) -> EvalReport {
+    // True positives, false positives and false negatives of every type seen
+    let mut counts: BTreeMap<CommonCursorType, [usize; 3]> = BTreeMap::new();
+    let mut correct = 0;
+
+    for (image, expected) in corpus {
+        let detected = detector.detect(image.as_raw(), image.width(), image.height());
+        if detected == Some(*expected) {
+            correct += 1;
+            counts.entry(*expected).or_default()[0] += 1;
+            continue;
+        }
+
+        counts.entry(*expected).or_default()[2] += 1;
+        if let Some(detected) = detected {
+            counts.entry(detected).or_default()[1] += 1;
+        }
+    }
+
+    EvalReport {
+        samples: corpus.len(),
+        correct,
+        accuracy: ratio(correct, corpus.len()).unwrap_or(0.0),
+        per_type: counts
+            .into_iter()
+            .map(|(cursor_type, [tp, fp, fn_])| TypeMetrics {
+                cursor_type,
+                true_positives: tp,
+                false_positives: fp,
+                false_negatives: fn_,
+                precision: ratio(tp, tp + fp),
+                recall: ratio(tp, tp + fn_),
+            })
+            .collect(),
+    }
+}
+
+fn ratio(part: usize, whole: usize) -> Option<f32> {
+    (whole > 0).then(|| part as f32 / whole as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::rendering::rasterize_cursor;
+
+    fn bundled_corpus(types: &[CommonCursorType]) -> Vec<(RgbaImage, CommonCursorType)> {
+        types
+            .iter()
+            .map(|&cursor_type| (rasterize_cursor(&cursor_type, 32).unwrap(), cursor_type))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_detector_gets_bundled_cursors_right() {
+        let corpus = bundled_corpus(CommonCursorType::ALL);
+        let report = evaluate_detector(&DetectionConfig::default(), &corpus);
+
+        assert_eq!(
+            (report.samples, report.correct),
+            (corpus.len(), corpus.len())
+        );
+        assert_eq!(report.accuracy, 1.0);
+        assert_eq!(report.per_type.len(), CommonCursorType::ALL.len());
+        assert!(report
+            .per_type
+            .iter()
+            .all(|m| m.precision == Some(1.0) && m.recall == Some(1.0)));
+    }
+
+    #[test]
+    fn test_report_counts_confusions() {
+        let corpus = bundled_corpus(&[
+            CommonCursorType::Arrow,
+            CommonCursorType::Arrow,
+            CommonCursorType::IBeam,
+            CommonCursorType::Crosshair,
+        ]);
+        // Calls every I-beam an arrow and finds no crosshairs
+        let detector = |image_data: &[u8], width, height| {
+            let detected = CommonCursorType::detect_from_image(image_data, width, height);
+            match detected {
+                Some(CommonCursorType::IBeam) => Some(CommonCursorType::Arrow),
+                Some(CommonCursorType::Crosshair) => None,
+                _ => detected,
+            }
+        };
+        let report = evaluate_detector(&detector, &corpus);
+
+        assert_eq!((report.samples, report.correct), (4, 2));
+        assert_eq!(report.accuracy, 0.5);
+        let arrow = report.metrics(CommonCursorType::Arrow).unwrap();
+        assert_eq!(arrow.precision, Some(2.0 / 3.0));
+        assert_eq!(arrow.recall, Some(1.0));
+        let ibeam = report.metrics(CommonCursorType::IBeam).unwrap();
+        assert_eq!((ibeam.precision, ibeam.recall), (None, Some(0.0)));
+        let crosshair = report.metrics(CommonCursorType::Crosshair).unwrap();
+        assert_eq!(crosshair.false_negatives, 1);
+        assert_eq!(report.metrics(CommonCursorType::Grab), None);
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(serde_json::from_str::<EvalReport>(&json).unwrap(), report);
+        assert!(json.contains(r#""precision":null"#));
+    }
+}
@@ -0,0 +1,1228 @@
+use crate::cursor::components::ComponentBounds;
+use crate::cursor::detection::OPAQUE_ALPHA_THRESHOLD;
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Share of the covered pixels at or below the alpha taken as a cursor's peak
+/// opacity, so a few stray pixels can't set it
+const PEAK_ALPHA_PERCENTILE: f32 = 0.98;
+
+/// Peak alphas this low are indistinguishable from noise and aren't rescaled
+const MIN_RESCALED_PEAK_ALPHA: u8 = 24;
+
+/// Luminance difference from the background above which a pixel of a
+/// grayscale capture belongs to the cursor
+const SILHOUETTE_LUMA_THRESHOLD: u8 = 40;
+
+/// Colors whose channels are all within this of each other are black, white
+/// or a gray in between rather than the cursor's color, see
+/// [`dominant_cursor_color`]
+const NEUTRAL_CHROMA: u8 = 24;
+
+/// Longest side of the opaque bounding box, in pixels, from which a cursor
+/// with soft edges is taken to be blown up by a screen magnifier
+pub const MAGNIFIED_MIN_EXTENT: u32 = 96;
+
+/// Partly transparent pixels per opaque one from which a cursor's edges are
+/// soft, as interpolated upscaling leaves them
+const SOFT_EDGE_RATIO: f32 = 0.15;
+
+/// Alphas counted as partly transparent for [`SOFT_EDGE_RATIO`]
+const SOFT_ALPHA: std::ops::RangeInclusive<u8> = 32..=224;
+
+/// Longest side a magnified cursor is shrunk to before detection, so it's
+/// measured at the scale its shapes were designed for
+const DEMAGNIFIED_SIZE: u32 = 48;
+
+/// Enclosed transparent pixels per opaque one from which a cursor is an
+/// outline around an empty interior rather than a shape with a few holes
+const HOLLOW_INTERIOR_RATIO: f32 = 0.5;
+
+/// Alphas below which a colored pixel can be a subpixel anti-aliasing
+/// fringe, see [`defringe`]. Fringes only ever sit on partly covered edges
+const FRINGE_MAX_ALPHA: u8 = 224;
+
+/// Spread between a pixel's largest and smallest channel from which it's
+/// colored rather than a gray
+const FRINGE_MIN_CHROMA: u8 = 48;
+
+/// Distance in RGB from every blend of two neighboring colors above which an
+/// edge pixel's color can't be the anti-aliasing between them
+const FRINGE_MIN_DEVIATION: f32 = 80.0;
+
+/// Shape statistics of a cursor's opaque pixels.
+///
+/// Ratios are shares of the opaque pixel count and, unless noted otherwise,
+/// positions are measured relative to the opaque bounding box so the features
+/// don't depend on how much transparent padding the capture has.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CursorFeatures {
+    pub width: u32,
+    pub height: u32,
+    pub opaque_pixels: u32,
+    pub bounds: ComponentBounds,
+    /// Centroid of the opaque pixels, normalized to the full image
+    pub image_centroid: (f32, f32),
+    /// Centroid of the opaque pixels, normalized to the bounding box
+    pub centroid: (f32, f32),
+    /// Opaque pixels / bounding box area
+    pub fill_ratio: f32,
+    /// Pixels in the vertical band through the box center
+    pub center_column_ratio: f32,
+    /// Pixels in the horizontal band through the box center
+    pub center_row_ratio: f32,
+    /// Pixels in either center band
+    pub cross_ratio: f32,
+    /// Share of box rows the vertical center band reaches (1.0 = unbroken line)
+    pub column_coverage: f32,
+    /// Share of box columns the horizontal center band reaches
+    pub row_coverage: f32,
+    /// Pixels close to the top-left/bottom-right diagonal
+    pub diagonal_ratio: f32,
+    /// Pixels close to the top-right/bottom-left diagonal
+    pub anti_diagonal_ratio: f32,
+    /// Pixels in the four corner squares of a third of the box's size
+    pub corner_ratio: f32,
+    /// Pixels per corner square, in the order of `quadrant_ratios`. Unlike
+    /// the quadrants these leave out the middle of the box, so a handle drawn
+    /// at the center of a resize cursor doesn't count
+    pub corner_ratios: [f32; 4],
+    /// Pixels per box quadrant: top-left, top-right, bottom-left, bottom-right
+    pub quadrant_ratios: [f32; 4],
+    /// Pixels per quarter of the way along the box's top-right/bottom-left
+    /// diagonal, starting from the bottom-left corner. Shows which end of a
+    /// diagonal shape is the heavier, e.g. an eyedropper's bulb top-right
+    pub anti_diagonal_quarters: [f32; 4],
+    /// Pixels in the bottom half of the box
+    pub bottom_half_ratio: f32,
+    /// Share of rows in the top 60% of the box whose leftmost pixel sits on the box's left edge
+    pub left_aligned_rows: f32,
+    /// Pixels outside the ellipse inscribed in the box (0.0 for a disc)
+    pub outside_ellipse_ratio: f32,
+    /// Opaque share of the box's central ellipse of half the size (1.0 for a
+    /// filled disc, 0.0 for a ring)
+    pub core_fill: f32,
+    /// Most separate runs of opaque pixels on any row in the top 40% of the
+    /// box, e.g. 4 for the splayed fingers of an open hand
+    pub top_runs: u32,
+    /// Widest row in the top quarter of the box over the narrowest row from
+    /// there to 40% down, above 1.0 for a shaft tipped with an arrowhead like
+    /// the arms of a move cursor. 0.0 without rows to compare
+    pub head_flare: f32,
+    /// Share of the pixels in the leftmost and rightmost eighth of the box
+    /// that lie in its middle third of rows, near 1.0 when both ends taper
+    /// to a point on the center line like a horizontal double arrow's. 0.0
+    /// without pixels there
+    pub horizontal_tip_ratio: f32,
+    /// Bands of rows spanning most of the box's width with much narrower
+    /// rows right above and below, like the divider of a row resize cursor.
+    /// Bands at the top or bottom edge of the box don't count, so an I-beam's
+    /// serifs aren't bars
+    pub horizontal_bars: u32,
+    /// Rows above the first bar or below the last one, whichever are fewer,
+    /// over the box width: how far the shape reaches out from its bars. 0.0
+    /// without a bar
+    pub bar_arm_ratio: f32,
+    /// Share of the opaque pixels in the ellipse inscribed in the box that
+    /// are clearly colored rather than black, white or gray
+    pub saturated_ratio: f32,
+    /// How widely the hues of those colored pixels spread around the color
+    /// wheel: 0.0 when they're all one hue, near 1.0 for every hue in equal
+    /// measure like a rainbow disc. 0.0 without colored pixels
+    pub hue_spread: f32,
+}
+
+/// Per-row and per-column accumulators of [`CursorFeatures::extract_in`],
+/// kept between calls so extraction doesn't allocate
+#[derive(Debug, Clone, Default)]
+pub struct FeatureScratch {
+    column_rows: Vec<bool>,
+    row_columns: Vec<bool>,
+}
+
+impl CursorFeatures {
+    /// Extract features from an RGBA buffer, `None` if it's malformed or fully transparent
+    pub fn extract(image_data: &[u8], width: u32, height: u32) -> Option<Self> {
+        Self::extract_in(image_data, width, height, &mut FeatureScratch::default())
+    }
+
+    /// Like [`Self::extract`], reusing `scratch`'s buffers
+    pub fn extract_in(
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+        scratch: &mut FeatureScratch,
+    ) -> Option<Self> {
+        if width == 0 || height == 0 || image_data.len() < width as usize * height as usize * 4 {
+            return None;
+        }
+
+        let is_opaque = |x: u32, y: u32| {
+            image_data[((y * width + x) * 4 + 3) as usize] > OPAQUE_ALPHA_THRESHOLD
+        };
+
+        // First pass: bounding box, so the remaining features can be box-relative
+        let bounds = opaque_bounds(image_data, width, height)?;
+
+        let (bw, bh) = (bounds.width(), bounds.height());
+        let center_x = (bounds.min_x + bounds.max_x) as f32 / 2.0;
+        let center_y = (bounds.min_y + bounds.max_y) as f32 / 2.0;
+        let column_band = (bw as f32 / 10.0).round().max(1.0);
+        let row_band = (bh as f32 / 10.0).round().max(1.0);
+        let align_tolerance = (bw / 10).max(1);
+
+        let mut opaque = 0u32;
+        let mut sum_x = 0u64;
+        let mut sum_y = 0u64;
+        let mut center_column = 0u32;
+        let mut center_row = 0u32;
+        let mut cross = 0u32;
+        let mut diagonal = 0u32;
+        let mut anti_diagonal = 0u32;
+        let mut corners = [0u32; 4];
+        let mut quadrants = [0u32; 4];
+        let mut anti_diagonal_quarters = [0u32; 4];
+        let mut bottom_half = 0u32;
+        let FeatureScratch {
+            column_rows,
+            row_columns,
+        } = scratch;
+        column_rows.clear();
+        column_rows.resize(bh as usize, false);
+        row_columns.clear();
+        row_columns.resize(bw as usize, false);
+        let mut aligned_rows = 0u32;
+        let mut counted_rows = 0u32;
+        let mut outside_ellipse = 0u32;
+        let mut core_pixels = 0u32;
+        let mut core_opaque = 0u32;
+        // Squared distance of a pixel center from the box center, in units of
+        // the box's half extents
+        let ellipse_distance = |x: u32, y: u32| {
+            let ex = (x as f32 + 0.5 - bounds.min_x as f32 - bw as f32 / 2.0) / (bw as f32 / 2.0);
+            let ey = (y as f32 + 0.5 - bounds.min_y as f32 - bh as f32 / 2.0) / (bh as f32 / 2.0);
+            ex * ex + ey * ey
+        };
+        let top_rows_end = bounds.min_y + (bh * 6).div_ceil(10);
+        let run_rows_end = bounds.min_y + (bh * 4).div_ceil(10);
+        let mut top_runs = 0u32;
+        let head_rows_end = bounds.min_y + bh.div_ceil(4);
+        let (mut head_span, mut neck_span) = (0u32, None::<u32>);
+        let tip_columns = bw.div_ceil(8);
+        let (mut tip_pixels, mut centered_tip_pixels) = (0u32, 0u32);
+        // Rows at least this full are part of a bar, and ones at most this
+        // full can border one
+        let (bar_pixels, bar_border_pixels) = (bw as f32 * 0.8, bw as f32 * 0.5);
+        let mut horizontal_bars = 0u32;
+        // First row of the bar band being walked, if it started below a
+        // narrow row, and the first and last rows of the bars found so far
+        let mut bar_start = None::<u32>;
+        let (mut in_band, mut previous_narrow) = (false, false);
+        let mut bar_rows = None::<(u32, u32)>;
+        let (mut ellipse_pixels, mut saturated) = (0u32, 0u32);
+        // Sum of the saturated pixels' hues as unit vectors on the color wheel
+        let mut hue_sum = (0.0f32, 0.0f32);
+
+        for y in bounds.min_y..=bounds.max_y {
+            let (mut leftmost, mut rightmost) = (None, 0);
+            let (mut runs, mut previous_opaque) = (0u32, false);
+            let mut row_pixels = 0u32;
+
+            for x in bounds.min_x..=bounds.max_x {
+                let distance = ellipse_distance(x, y);
+                let in_core = distance <= 0.25;
+                if in_core {
+                    core_pixels += 1;
+                }
+
+                let pixel_opaque = is_opaque(x, y);
+                if pixel_opaque && !previous_opaque {
+                    runs += 1;
+                }
+                previous_opaque = pixel_opaque;
+                if !pixel_opaque {
+                    continue;
+                }
+                leftmost.get_or_insert(x);
+                rightmost = x;
+                row_pixels += 1;
+
+                if in_core {
+                    core_opaque += 1;
+                }
+                // A little slack so a disc's anti-aliased rim still counts as inside
+                if distance > 1.1 {
+                    outside_ellipse += 1;
+                }
+                if distance <= 1.0 {
+                    ellipse_pixels += 1;
+                    let i = (y as usize * width as usize + x as usize) * 4;
+                    if let Some(hue) = hue_vector(&image_data[i..i + 3]) {
+                        saturated += 1;
+                        hue_sum = (hue_sum.0 + hue.0, hue_sum.1 + hue.1);
+                    }
+                }
+
+                opaque += 1;
+                sum_x += x as u64;
+                sum_y += y as u64;
+
+                let in_column = (x as f32 - center_x).abs() <= column_band;
+                let in_row = (y as f32 - center_y).abs() <= row_band;
+                if in_column {
+                    center_column += 1;
+                    column_rows[(y - bounds.min_y) as usize] = true;
+                }
+                if in_row {
+                    center_row += 1;
+                    row_columns[(x - bounds.min_x) as usize] = true;
+                }
+                if in_column || in_row {
+                    cross += 1;
+                }
+
+                // Box-relative coordinates in 0..=1
+                let nx = (x - bounds.min_x) as f32 / (bw - 1).max(1) as f32;
+                let ny = (y - bounds.min_y) as f32 / (bh - 1).max(1) as f32;
+                if (nx - ny).abs() <= 0.2 {
+                    diagonal += 1;
+                }
+                if (nx + ny - 1.0).abs() <= 0.2 {
+                    anti_diagonal += 1;
+                }
+                let along = (nx + 1.0 - ny) / 2.0;
+                anti_diagonal_quarters[((along * 4.0) as usize).min(3)] += 1;
+                let right = x as f32 > center_x;
+                let bottom = y as f32 > center_y;
+                let quadrant = (bottom as usize) * 2 + right as usize;
+                let near_edge = |n: f32| !(1.0 / 3.0..=2.0 / 3.0).contains(&n);
+                if near_edge(nx) && near_edge(ny) {
+                    corners[quadrant] += 1;
+                }
+                quadrants[quadrant] += 1;
+                if bottom {
+                    bottom_half += 1;
+                }
+
+                if x < bounds.min_x + tip_columns || x > bounds.max_x - tip_columns {
+                    tip_pixels += 1;
+                    if (y as f32 - center_y).abs() <= bh as f32 / 6.0 {
+                        centered_tip_pixels += 1;
+                    }
+                }
+            }
+
+            if y < run_rows_end {
+                top_runs = top_runs.max(runs);
+            }
+            let (wide, narrow) = (
+                row_pixels as f32 >= bar_pixels,
+                row_pixels as f32 <= bar_border_pixels,
+            );
+            if wide && !in_band {
+                in_band = true;
+                bar_start = previous_narrow.then_some(y);
+            } else if !wide && in_band {
+                in_band = false;
+                if let (Some(start), true) = (bar_start, narrow) {
+                    horizontal_bars += 1;
+                    bar_rows = Some(bar_rows.map_or((start, y - 1), |(first, _)| (first, y - 1)));
+                }
+            }
+            previous_narrow = narrow;
+            if let Some(leftmost) = leftmost {
+                let span = rightmost - leftmost + 1;
+                if y < head_rows_end {
+                    head_span = head_span.max(span);
+                } else if y < run_rows_end {
+                    neck_span = Some(neck_span.map_or(span, |neck| neck.min(span)));
+                }
+            }
+            if y < top_rows_end {
+                if let Some(leftmost) = leftmost {
+                    counted_rows += 1;
+                    if leftmost - bounds.min_x <= align_tolerance {
+                        aligned_rows += 1;
+                    }
+                }
+            }
+        }
+
+        let ratio = |count: u32| count as f32 / opaque as f32;
+        let coverage =
+            |hits: &[bool]| hits.iter().filter(|&&hit| hit).count() as f32 / hits.len() as f32;
+
+        Some(Self {
+            width,
+            height,
+            opaque_pixels: opaque,
+            bounds,
+            image_centroid: (
+                (sum_x as f32 / opaque as f32 + 0.5) / width as f32,
+                (sum_y as f32 / opaque as f32 + 0.5) / height as f32,
+            ),
+            centroid: (
+                (sum_x as f32 / opaque as f32 - bounds.min_x as f32 + 0.5) / bw as f32,
+                (sum_y as f32 / opaque as f32 - bounds.min_y as f32 + 0.5) / bh as f32,
+            ),
+            fill_ratio: opaque as f32 / bounds.area() as f32,
+            center_column_ratio: ratio(center_column),
+            center_row_ratio: ratio(center_row),
+            cross_ratio: ratio(cross),
+            column_coverage: coverage(column_rows),
+            row_coverage: coverage(row_columns),
+            diagonal_ratio: ratio(diagonal),
+            anti_diagonal_ratio: ratio(anti_diagonal),
+            corner_ratio: ratio(corners.iter().sum()),
+            corner_ratios: corners.map(ratio),
+            quadrant_ratios: quadrants.map(ratio),
+            anti_diagonal_quarters: anti_diagonal_quarters.map(ratio),
+            bottom_half_ratio: ratio(bottom_half),
+            left_aligned_rows: if counted_rows == 0 {
+                0.0
+            } else {
+                aligned_rows as f32 / counted_rows as f32
+            },
+            outside_ellipse_ratio: ratio(outside_ellipse),
+            core_fill: if core_pixels == 0 {
+                0.0
+            } else {
+                core_opaque as f32 / core_pixels as f32
+            },
+            top_runs,
+            head_flare: neck_span.map_or(0.0, |neck| head_span as f32 / neck as f32),
+            horizontal_tip_ratio: if tip_pixels == 0 {
+                0.0
+            } else {
+                centered_tip_pixels as f32 / tip_pixels as f32
+            },
+            horizontal_bars,
+            bar_arm_ratio: bar_rows.map_or(0.0, |(first, last)| {
+                (first - bounds.min_y).min(bounds.max_y - last) as f32 / bw as f32
+            }),
+            saturated_ratio: if ellipse_pixels == 0 {
+                0.0
+            } else {
+                saturated as f32 / ellipse_pixels as f32
+            },
+            hue_spread: if saturated == 0 {
+                0.0
+            } else {
+                1.0 - hue_sum.0.hypot(hue_sum.1) / saturated as f32
+            },
+        })
+    }
+
+    /// Bounding box width / height
+    pub fn aspect_ratio(&self) -> f32 {
+        self.bounds.width() as f32 / self.bounds.height() as f32
+    }
+
+    /// Whether opaque pixels reach any edge of the image
+    pub fn touches_border(&self) -> bool {
+        self.bounds.min_x == 0
+            || self.bounds.min_y == 0
+            || self.bounds.max_x == self.width - 1
+            || self.bounds.max_y == self.height - 1
+    }
+}
+
+/// Least difference between a pixel's strongest and weakest channel for it
+/// to count as colored in [`CursorFeatures::hue_spread`]
+const SATURATED_MIN_CHROMA: u8 = 80;
+
+/// Hue of an RGB color as a unit vector on the color wheel, red pointing
+/// along x, `None` for colors too close to gray to have a meaningful hue
+fn hue_vector(rgb: &[u8]) -> Option<(f32, f32)> {
+    let (max, min) = (*rgb.iter().max()?, *rgb.iter().min()?);
+    if max - min < SATURATED_MIN_CHROMA {
+        return None;
+    }
+    let [r, g, b] = [rgb[0], rgb[1], rgb[2]].map(f32::from);
+    let (x, y) = (r - (g + b) / 2.0, (g - b) * 3f32.sqrt() / 2.0);
+    let length = x.hypot(y);
+    (length > 0.0).then(|| (x / length, y / length))
+}
+
+/// Bounding box of the pixels above [`OPAQUE_ALPHA_THRESHOLD`], `None` if the
+/// buffer is malformed or has none
+pub(crate) fn opaque_bounds(image_data: &[u8], width: u32, height: u32) -> Option<ComponentBounds> {
+    if width == 0 || height == 0 || image_data.len() < width as usize * height as usize * 4 {
+        return None;
+    }
+
+    let mut bounds: Option<ComponentBounds> = None;
+    for y in 0..height {
+        for x in 0..width {
+            if image_data[((y * width + x) * 4 + 3) as usize] > OPAQUE_ALPHA_THRESHOLD {
+                let b = bounds.get_or_insert(ComponentBounds {
+                    min_x: x,
+                    min_y: y,
+                    max_x: x,
+                    max_y: y,
+                });
+                b.min_x = b.min_x.min(x);
+                b.min_y = b.min_y.min(y);
+                b.max_x = b.max_x.max(x);
+                b.max_y = b.max_y.max(y);
+            }
+        }
+    }
+    bounds
+}
+
+/// Copy of an RGBA buffer with the opaque bounding box moved to the top-left
+/// corner, `None` if the buffer is malformed or has no opaque pixels.
+///
+/// Capture compositors sometimes shift a static cursor by a pixel between
+/// frames. Re-centering on a canonical origin makes every shifted copy
+/// byte-identical, and with it the features, detection and hashes computed
+/// from it. Content left of or above the bounding box (anti-aliasing fringes
+/// below [`OPAQUE_ALPHA_THRESHOLD`]) is dropped.
+pub fn normalize_translation(image_data: &[u8], width: u32, height: u32) -> Option<Vec<u8>> {
+    if width == 0 || height == 0 || image_data.len() < width as usize * height as usize * 4 {
+        return None;
+    }
+
+    let row_bytes = (width * 4) as usize;
+    let rows = || image_data[..row_bytes * height as usize].chunks_exact(row_bytes);
+    let is_opaque = |pixel: &[u8]| pixel[3] > OPAQUE_ALPHA_THRESHOLD;
+
+    let min_y = rows().position(|row| row.chunks_exact(4).any(is_opaque))?;
+    let min_x = rows()
+        .filter_map(|row| row.chunks_exact(4).position(is_opaque))
+        .min()?;
+
+    let mut normalized = vec![0u8; row_bytes * height as usize];
+    for (target, source) in normalized
+        .chunks_exact_mut(row_bytes)
+        .zip(rows().skip(min_y))
+    {
+        let kept = row_bytes - min_x * 4;
+        target[..kept].copy_from_slice(&source[min_x * 4..]);
+    }
+
+    Some(normalized)
+}
+
+/// Copy of an RGBA buffer flipped left to right, `None` if it's malformed
+pub(crate) fn mirror_horizontally(image_data: &[u8], width: u32, height: u32) -> Option<Vec<u8>> {
+    let row_bytes = width as usize * 4;
+    if width == 0 || height == 0 || image_data.len() < row_bytes * height as usize {
+        return None;
+    }
+
+    let mut mirrored = Vec::with_capacity(row_bytes * height as usize);
+    for row in image_data[..row_bytes * height as usize].chunks_exact(row_bytes) {
+        mirrored.extend(row.chunks_exact(4).rev().flatten());
+    }
+    Some(mirrored)
+}
+
+/// Copy of an RGBA buffer with subpixel anti-aliasing fringes desaturated,
+/// borrowed when there are none or the buffer is malformed.
+///
+/// Cursors rendered with LCD subpixel anti-aliasing carry red and blue
+/// fringes on their edges, which a black-and-white cursor shows as colors it
+/// doesn't have, e.g. to [`dominant_cursor_color`]. A fringe pixel is a
+/// partly transparent, clearly colored one far from any blend of the colors
+/// around it, so not the anti-aliased edge of a colored shape; it's replaced
+/// by a gray of the same luminance. Alpha is left alone, so shapes measure
+/// the same.
+pub fn defringe(image_data: &[u8], width: u32, height: u32) -> Cow<'_, [u8]> {
+    let len = width as usize * height as usize * 4;
+    if width == 0 || height == 0 || image_data.len() < len {
+        return Cow::Borrowed(image_data);
+    }
+
+    let pixel = |x: u32, y: u32| {
+        let i = (y * width + x) as usize * 4;
+        &image_data[i..i + 4]
+    };
+    // Partly transparent and clearly colored, what a fringe looks like
+    let is_candidate = |p: &[u8]| {
+        let chroma = p[..3].iter().max().unwrap() - p[..3].iter().min().unwrap();
+        p[3] < FRINGE_MAX_ALPHA && chroma >= FRINGE_MIN_CHROMA
+    };
+    let mut defringed: Option<Vec<u8>> = None;
+
+    for y in 0..height {
+        for x in 0..width {
+            let center = pixel(x, y);
+            if center[3] == 0 || !is_candidate(center) {
+                continue;
+            }
+
+            // A colored cursor's own edges blend the colors on either side
+            // of them; a fringe's color is far from any such blend. Other
+            // candidates can't vouch for it, they're as suspect
+            let references: Vec<&[u8]> = (y.saturating_sub(1)..=(y + 1).min(height - 1))
+                .flat_map(|ny| {
+                    (x.saturating_sub(1)..=(x + 1).min(width - 1)).map(move |nx| (nx, ny))
+                })
+                .map(|(nx, ny)| pixel(nx, ny))
+                .filter(|neighbor| neighbor[3] > 0 && !is_candidate(neighbor))
+                .collect();
+            let nearest_blend = references
+                .iter()
+                .enumerate()
+                .flat_map(|(i, a)| references[i..].iter().map(move |b| (*a, *b)))
+                .map(|(a, b)| distance_to_blend(center, a, b))
+                .fold(f32::INFINITY, f32::min);
+            if nearest_blend < FRINGE_MIN_DEVIATION {
+                continue;
+            }
+
+            let luma =
+                (0.2126 * center[0] as f32 + 0.7152 * center[1] as f32 + 0.0722 * center[2] as f32)
+                    .round() as u8;
+            let i = (y * width + x) as usize * 4;
+            let defringed = defringed.get_or_insert_with(|| image_data[..len].to_vec());
+            defringed[i..i + 3].fill(luma);
+        }
+    }
+
+    defringed.map_or(Cow::Borrowed(image_data), Cow::Owned)
+}
+
+/// Copy of an RGBA buffer thinned to its one pixel wide skeleton with the
+/// Zhang-Suen algorithm, `None` if the buffer is malformed.
+///
+/// Themes draw the same line-art cursor with strokes of different weights,
+/// and a heavy crosshair or I-beam fills its box like a solid shape would.
+/// Their skeletons are the same thin lines whatever the weight. Skeleton
+/// pixels keep their color and are made fully opaque, the rest of the image
+/// is transparent.
+pub fn skeletonize(image_data: &[u8], width: u32, height: u32) -> Option<Vec<u8>> {
+    if width == 0 || height == 0 || image_data.len() < width as usize * height as usize * 4 {
+        return None;
+    }
+
+    let (w, h) = (width as usize, height as usize);
+    let mut mask: Vec<bool> = image_data[..w * h * 4]
+        .chunks_exact(4)
+        .map(|pixel| pixel[3] > OPAQUE_ALPHA_THRESHOLD)
+        .collect();
+    let mut removed = Vec::new();
+
+    loop {
+        let mut changed = false;
+        for step in 0..2 {
+            removed.clear();
+            for y in 0..h {
+                for x in 0..w {
+                    if !mask[y * w + x] {
+                        continue;
+                    }
+                    // Neighbors clockwise from the one above, off-image ones unset
+                    let at = |dx: isize, dy: isize| {
+                        let (nx, ny) = (x as isize + dx, y as isize + dy);
+                        (0..w as isize).contains(&nx)
+                            && (0..h as isize).contains(&ny)
+                            && mask[ny as usize * w + nx as usize]
+                    };
+                    let ring = [
+                        at(0, -1),
+                        at(1, -1),
+                        at(1, 0),
+                        at(1, 1),
+                        at(0, 1),
+                        at(-1, 1),
+                        at(-1, 0),
+                        at(-1, -1),
+                    ];
+                    let neighbors = ring.iter().filter(|&&set| set).count();
+                    let transitions = (0..8).filter(|&i| !ring[i] && ring[(i + 1) % 8]).count();
+                    let [up, _, right, _, down, _, left, _] = ring;
+                    // First the south-east boundary, then the north-west one
+                    let clear = if step == 0 {
+                        !(right && down && (up || left))
+                    } else {
+                        !(up && left && (right || down))
+                    };
+                    if (2..=6).contains(&neighbors) && transitions == 1 && clear {
+                        removed.push(y * w + x);
+                    }
+                }
+            }
+            for &i in &removed {
+                mask[i] = false;
+            }
+            changed |= !removed.is_empty();
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut skeleton = vec![0u8; w * h * 4];
+    for ((target, source), _) in skeleton
+        .chunks_exact_mut(4)
+        .zip(image_data.chunks_exact(4))
+        .zip(&mask)
+        .filter(|(_, &kept)| kept)
+    {
+        target[..3].copy_from_slice(&source[..3]);
+        target[3] = 255;
+    }
+    Some(skeleton)
+}
+
+/// RGB distance of a color from the nearest mix of colors `a` and `b`
+fn distance_to_blend(color: &[u8], a: &[u8], b: &[u8]) -> f32 {
+    let channel = |p: &[u8], c: usize| p[c] as f32;
+    let span: f32 = (0..3)
+        .map(|c| (channel(b, c) - channel(a, c)).powi(2))
+        .sum();
+    let t = if span == 0.0 {
+        0.0
+    } else {
+        ((0..3)
+            .map(|c| (channel(color, c) - channel(a, c)) * (channel(b, c) - channel(a, c)))
+            .sum::<f32>()
+            / span)
+            .clamp(0.0, 1.0)
+    };
+    (0..3)
+        .map(|c| {
+            let mix = channel(a, c) + t * (channel(b, c) - channel(a, c));
+            (channel(color, c) - mix).powi(2)
+        })
+        .sum::<f32>()
+        .sqrt()
+}
+
+/// Rescale a uniformly translucent cursor so its most opaque pixels are fully
+/// opaque again, returning the rescaled buffer and the cursor's peak opacity
+/// in 0..=1.
+///
+/// Disabled UI often shows a dimmed copy of a normal cursor, whose pixels all
+/// fall below [`OPAQUE_ALPHA_THRESHOLD`] and so look like an empty bitmap to
+/// the shape features. The peak is a high percentile of the alpha histogram
+/// rather than its maximum. Cursors that are already opaque are returned
+/// unchanged with an opacity of 1.0.
+pub fn normalize_alpha(image_data: &[u8], width: u32, height: u32) -> (Cow<'_, [u8]>, f32) {
+    let (gain, opacity) = alpha_gain(image_data, width, height);
+    let Some(gain) = gain else {
+        return (Cow::Borrowed(image_data), opacity);
+    };
+
+    let mut rescaled = image_data.to_vec();
+    apply_alpha_gain(&mut rescaled, width, height, gain);
+    (Cow::Owned(rescaled), opacity)
+}
+
+/// Like [`normalize_alpha`], copying into `buffer` instead of allocating when
+/// the cursor needs rescaling
+pub(crate) fn normalize_alpha_in<'a>(
+    image_data: &'a [u8],
+    width: u32,
+    height: u32,
+    buffer: &'a mut Vec<u8>,
+) -> &'a [u8] {
+    let Some(gain) = alpha_gain(image_data, width, height).0 else {
+        return image_data;
+    };
+
+    buffer.clear();
+    buffer.extend_from_slice(image_data);
+    apply_alpha_gain(buffer, width, height, gain);
+    buffer
+}
+
+/// Factor [`normalize_alpha`] scales the alpha channel by, `None` if it's
+/// left unchanged, plus the cursor's opacity
+fn alpha_gain(image_data: &[u8], width: u32, height: u32) -> (Option<f32>, f32) {
+    let len = (width as usize * height as usize * 4).min(image_data.len());
+    let mut histogram = [0u32; 256];
+    for pixel in image_data[..len].chunks_exact(4) {
+        histogram[pixel[3] as usize] += 1;
+    }
+
+    let covered: u32 = histogram[1..].iter().sum();
+    let allowed_above = ((1.0 - PEAK_ALPHA_PERCENTILE) * covered as f32) as u32;
+    let mut above = 0;
+    let Some(peak) = (1..=255u8).rev().find(|&alpha| {
+        above += histogram[alpha as usize];
+        above > allowed_above
+    }) else {
+        return (None, 1.0);
+    };
+
+    let opacity = peak as f32 / 255.0;
+    if peak == 255 || peak <= MIN_RESCALED_PEAK_ALPHA {
+        return (None, opacity);
+    }
+
+    (Some(255.0 / peak as f32), opacity)
+}
+
+fn apply_alpha_gain(image_data: &mut [u8], width: u32, height: u32, gain: f32) {
+    let len = (width as usize * height as usize * 4).min(image_data.len());
+    for pixel in image_data[..len].chunks_exact_mut(4) {
+        pixel[3] = (pixel[3] as f32 * gain).round().min(255.0) as u8;
+    }
+}
+
+/// Whether a cursor looks scaled up by the OS screen magnifier: far larger
+/// than cursors are drawn, with the blurred edges interpolation leaves.
+/// Crisp large cursors, like accessibility sizes, don't count.
+///
+/// Detection shrinks magnified cursors with a box filter before measuring
+/// them, which smooths out the blur along with the size.
+pub fn is_magnified(image_data: &[u8], width: u32, height: u32) -> bool {
+    let (image_data, _) = normalize_alpha(image_data, width, height);
+    magnification(&image_data, width, height).is_some()
+}
+
+/// Factor a magnified cursor is shrunk by, `None` if it isn't magnified
+fn magnification(image_data: &[u8], width: u32, height: u32) -> Option<u32> {
+    let bounds = opaque_bounds(image_data, width, height)?;
+    if bounds.width().max(bounds.height()) < MAGNIFIED_MIN_EXTENT {
+        return None;
+    }
+
+    let (mut opaque, mut soft) = (0u32, 0u32);
+    for pixel in image_data[..width as usize * height as usize * 4].chunks_exact(4) {
+        if pixel[3] > OPAQUE_ALPHA_THRESHOLD {
+            opaque += 1;
+        }
+        if SOFT_ALPHA.contains(&pixel[3]) {
+            soft += 1;
+        }
+    }
+    (soft as f32 >= opaque as f32 * SOFT_EDGE_RATIO)
+        .then(|| width.max(height).div_ceil(DEMAGNIFIED_SIZE))
+}
+
+/// A magnified cursor averaged down to about [`DEMAGNIFIED_SIZE`] in
+/// `buffer`, with its new dimensions. Anything else is returned as it is.
+/// Colors are weighted by alpha, so transparent pixels don't darken them
+pub(crate) fn demagnify_in<'a>(
+    image_data: &'a [u8],
+    width: u32,
+    height: u32,
+    buffer: &'a mut Vec<u8>,
+) -> (&'a [u8], u32, u32) {
+    let Some(factor) = magnification(image_data, width, height) else {
+        return (image_data, width, height);
+    };
+
+    let (shrunk_width, shrunk_height) = (width.div_ceil(factor), height.div_ceil(factor));
+    buffer.clear();
+    buffer.resize((shrunk_width * shrunk_height * 4) as usize, 0);
+    for (i, target) in buffer.chunks_exact_mut(4).enumerate() {
+        let (tx, ty) = (i as u32 % shrunk_width, i as u32 / shrunk_width);
+        let (mut sums, mut count) = ([0u32; 4], 0u32);
+        for y in ty * factor..((ty + 1) * factor).min(height) {
+            for x in tx * factor..((tx + 1) * factor).min(width) {
+                let pixel = &image_data[((y * width + x) * 4) as usize..][..4];
+                let alpha = pixel[3] as u32;
+                for channel in 0..3 {
+                    sums[channel] += pixel[channel] as u32 * alpha;
+                }
+                sums[3] += alpha;
+                count += 1;
+            }
+        }
+        for channel in 0..3 {
+            target[channel] = sums[channel].checked_div(sums[3]).unwrap_or(0) as u8;
+        }
+        target[3] = (sums[3] / count) as u8;
+    }
+    (buffer, shrunk_width, shrunk_height)
+}
+
+/// Copy of an outline-only cursor with the transparent interior its outline
+/// encloses filled in, so it can be measured like the filled shapes the
+/// matchers expect. `None` unless the outline encloses a larger interior
+/// than [`HOLLOW_INTERIOR_RATIO`] of its own pixels, or for a malformed
+/// buffer.
+///
+/// The interior is whatever transparent pixels can't be reached from the
+/// image border without crossing an opaque one, so a gap in the outline
+/// leaves the cursor unfilled.
+pub fn fill_hollow(image_data: &[u8], width: u32, height: u32) -> Option<Vec<u8>> {
+    let mut scratch = HollowScratch::default();
+    fill_hollow_in(image_data, width, height, &mut scratch)?;
+    Some(scratch.filled)
+}
+
+/// Buffers of [`fill_hollow_in`], kept between calls
+#[derive(Debug, Clone, Default)]
+pub(crate) struct HollowScratch {
+    filled: Vec<u8>,
+    outside: Vec<bool>,
+    stack: Vec<u32>,
+}
+
+/// Like [`fill_hollow`], filling into `scratch` instead of allocating
+pub(crate) fn fill_hollow_in<'a>(
+    image_data: &[u8],
+    width: u32,
+    height: u32,
+    scratch: &'a mut HollowScratch,
+) -> Option<&'a [u8]> {
+    let len = width as usize * height as usize;
+    if len == 0 || image_data.len() < len * 4 {
+        return None;
+    }
+    let is_opaque = |i: usize| image_data[i * 4 + 3] > OPAQUE_ALPHA_THRESHOLD;
+
+    // Flood the transparent pixels reachable from the border, 4-connected so
+    // a diagonal step through a 1px outline doesn't count as a way out
+    let HollowScratch {
+        filled,
+        outside,
+        stack,
+    } = scratch;
+    outside.clear();
+    outside.resize(len, false);
+    stack.clear();
+    let (w, h) = (width as usize, height as usize);
+    for i in (0..w)
+        .chain(len - w..len)
+        .chain((0..h).flat_map(|y| [y * w, y * w + w - 1]))
+    {
+        if !is_opaque(i) && !outside[i] {
+            outside[i] = true;
+            stack.push(i as u32);
+        }
+    }
+    while let Some(i) = stack.pop() {
+        let i = i as usize;
+        let (x, y) = (i % w, i / w);
+        let neighbors = [
+            (x > 0).then(|| i - 1),
+            (x + 1 < w).then(|| i + 1),
+            (y > 0).then(|| i - w),
+            (y + 1 < h).then(|| i + w),
+        ];
+        for neighbor in neighbors.into_iter().flatten() {
+            if !outside[neighbor] && !is_opaque(neighbor) {
+                outside[neighbor] = true;
+                stack.push(neighbor as u32);
+            }
+        }
+    }
+
+    let opaque = (0..len).filter(|&i| is_opaque(i)).count();
+    let interior = (0..len).filter(|&i| !outside[i] && !is_opaque(i)).count();
+    if opaque == 0 || (interior as f32) < opaque as f32 * HOLLOW_INTERIOR_RATIO {
+        return None;
+    }
+
+    filled.clear();
+    filled.extend_from_slice(&image_data[..len * 4]);
+    for (i, pixel) in filled.chunks_exact_mut(4).enumerate() {
+        if !outside[i] && pixel[3] <= OPAQUE_ALPHA_THRESHOLD {
+            pixel.copy_from_slice(&[0, 0, 0, 255]);
+        }
+    }
+    Some(filled)
+}
+
+/// Order of the color bytes of a 4-byte pixel. Alpha comes last in both, so
+/// shape detection reads any bitmap the same; only steps that look at colors
+/// need to know
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChannelOrder {
+    #[default]
+    Rgba,
+    /// Blue first, as Windows capture APIs deliver pixels
+    Bgra,
+}
+
+impl ChannelOrder {
+    /// A pixel in this order as RGBA
+    pub fn to_rgba(self, [first, second, third, alpha]: [u8; 4]) -> [u8; 4] {
+        match self {
+            ChannelOrder::Rgba => [first, second, third, alpha],
+            ChannelOrder::Bgra => [third, second, first, alpha],
+        }
+    }
+
+    /// Convert a buffer of pixels from RGBA to this order in place, or back:
+    /// swapping red and blue undoes itself
+    pub fn reorder(self, image_data: &mut [u8]) {
+        if self == ChannelOrder::Bgra {
+            for pixel in image_data.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+    }
+}
+
+/// Most common color of the cursor's opaque pixels, e.g. to draw click
+/// highlights in a contrasting color. `None` if nothing is opaque, even after
+/// [`normalize_alpha`].
+///
+/// Colors are counted in buckets of 16 levels per channel so anti-aliasing
+/// doesn't split a flat color, and the winning bucket's mean is returned.
+/// Near-black outlines, near-white fills and the grays anti-aliasing blends
+/// between them are skipped as long as anything else is drawn, so a
+/// black-and-white arrow with a blue badge reports blue; a plain
+/// black-and-white cursor reports whichever of the two covers more.
+pub fn dominant_cursor_color(image_data: &[u8], width: u32, height: u32) -> Option<[u8; 3]> {
+    dominant_cursor_color_with(image_data, width, height, ChannelOrder::Rgba)
+}
+
+/// Like [`dominant_cursor_color`], for a bitmap whose pixels are in `order`.
+/// The color is returned as RGB either way
+pub fn dominant_cursor_color_with(
+    image_data: &[u8],
+    width: u32,
+    height: u32,
+    order: ChannelOrder,
+) -> Option<[u8; 3]> {
+    let len = (width as usize * height as usize * 4).min(image_data.len());
+    let (image_data, _) = normalize_alpha(&image_data[..len], width, height);
+
+    // Pixel count and channel sums by 4-bit-per-channel bucket
+    let mut buckets: HashMap<u16, (u32, [u32; 3])> = HashMap::new();
+    for pixel in image_data.chunks_exact(4) {
+        let pixel = order.to_rgba([pixel[0], pixel[1], pixel[2], pixel[3]]);
+        if pixel[3] <= OPAQUE_ALPHA_THRESHOLD {
+            continue;
+        }
+
+        let key = (pixel[0] as u16 >> 4) << 8 | (pixel[1] as u16 >> 4) << 4 | pixel[2] as u16 >> 4;
+        let (count, sums) = buckets.entry(key).or_default();
+        *count += 1;
+        for channel in 0..3 {
+            sums[channel] += pixel[channel] as u32;
+        }
+    }
+
+    let is_neutral = |&(count, sums): &(u32, [u32; 3])| {
+        let mean = sums.map(|sum| sum / count);
+        let (min, max) = (mean.iter().min().unwrap(), mean.iter().max().unwrap());
+        max - min <= NEUTRAL_CHROMA as u32
+    };
+    let most_common = |neutral: bool| {
+        buckets
+            .iter()
+            .filter(|(_, bucket)| is_neutral(bucket) == neutral)
+            // Ties go to the lower bucket, so the result doesn't depend on hashing
+            .max_by_key(|(key, (count, _))| (*count, std::cmp::Reverse(**key)))
+            .map(|(_, bucket)| *bucket)
+    };
+
+    let (count, sums) = most_common(false).or_else(|| most_common(true))?;
+    Some(sums.map(|sum| (sum as f32 / count as f32).round() as u8))
+}
+
+/// Side length of the mask produced by [`canonicalize`]
+pub const CANONICAL_SIZE: u32 = 32;
+
+/// Transparent border [`canonicalize`] leaves around the glyph, so the
+/// canonical form never looks clipped
+const CANONICAL_MARGIN: u32 = 2;
+
+/// How [`canonicalize_with`] combines the source pixels under one mask pixel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CanonicalPooling {
+    /// Area-average, which keeps anti-aliased edges smooth
+    Average,
+    /// The most opaque pixel under each mask pixel. A line even one source
+    /// pixel wide stays fully opaque however far it's scaled down, where
+    /// averaging would fade it below the opacity threshold
+    MaxAlpha,
+    /// `Average`, unless scaling down faded the glyph to under half the opaque
+    /// area it should keep, which is what happens to an I-beam stem or
+    /// crosshair lines of a high DPI capture. Those are pooled with `MaxAlpha`
+    /// instead. Bolder glyphs average the same at every capture size, so
+    /// their masks stay shared across sizes
+    #[default]
+    Adaptive,
+}
+
+/// The cursor's alpha as a 32x32 mask, row-major, with the glyph scaled to fit
+/// and centered. Blank or malformed buffers give an all-zero mask.
+///
+/// This one representation replaces both the size normalization before
+/// detection and the cache key for the result: cursors that look the same at
+/// different capture sizes or paddings (or only differ in opacity, see
+/// [`normalize_alpha`]) canonicalize to the same mask, so caching detection
+/// results by it gives them one shared entry. The mask can also be detected
+/// itself with [`CommonCursorType::detect_canonical`], though resampling can
+/// cost thin strokes the detail detecting the original pixels would see.
+///
+/// The glyph is the bounding box of every pixel with any coverage, so the
+/// anti-aliased edge is kept, scaled so that its longer side spans the mask
+/// minus a small margin. Colour is dropped, which detection ignores anyway.
+/// Scaling uses [`CanonicalPooling::Adaptive`], see [`canonicalize_with`].
+///
+/// [`CommonCursorType::detect_canonical`]: crate::cursor::detection::CommonCursorType::detect_canonical
+pub fn canonicalize(image_data: &[u8], width: u32, height: u32) -> [u8; 1024] {
+    canonicalize_with(image_data, width, height, CanonicalPooling::default())
+}
+
+/// Like [`canonicalize`], combining source pixels with `pooling`
+pub fn canonicalize_with(
+    image_data: &[u8],
+    width: u32,
+    height: u32,
+    pooling: CanonicalPooling,
+) -> [u8; 1024] {
+    if width == 0 || height == 0 || image_data.len() < width as usize * height as usize * 4 {
+        return [0; 1024];
+    }
+
+    let (image_data, _) = normalize_alpha(image_data, width, height);
+    let alpha = |x: u32, y: u32| image_data[((y * width + x) * 4 + 3) as usize];
+
+    let Some(bounds) = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|&(x, y)| alpha(x, y) > 0)
+        .fold(None, |bounds: Option<(u32, u32, u32, u32)>, (x, y)| {
+            Some(match bounds {
+                None => (x, y, x, y),
+                Some((min_x, min_y, max_x, max_y)) => {
+                    (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+                }
+            })
+        })
+    else {
+        return [0; 1024];
+    };
+    let (min_x, min_y, max_x, max_y) = bounds;
+    let (box_width, box_height) = (max_x - min_x + 1, max_y - min_y + 1);
+
+    // Source pixels per mask pixel, the same on both axes to keep the aspect
+    // ratio. Positions are relative to the box, so padding can't change them
+    let span = (CANONICAL_SIZE - 2 * CANONICAL_MARGIN) as f32;
+    let step = box_width.max(box_height) as f32 / span;
+    let origin_x = -(CANONICAL_SIZE as f32 * step - box_width as f32) / 2.0;
+    let origin_y = -(CANONICAL_SIZE as f32 * step - box_height as f32) / 2.0;
+
+    // Coverage of each box pixel by the mask pixel spanning `start..start + step`
+    let overlaps = |start: f32, len: u32| {
+        let first = start.floor().max(0.0) as u32;
+        let last = ((start + step).ceil().max(0.0) as u32).min(len);
+        (first..last).map(move |p| {
+            let overlap = (start + step).min(p as f32 + 1.0) - start.max(p as f32);
+            (p, overlap.max(0.0))
+        })
+    };
+
+    let pool = |max_alpha: bool| {
+        let mut mask = [0u8; 1024];
+        for my in 0..CANONICAL_SIZE {
+            let start_y = origin_y + my as f32 * step;
+            for mx in 0..CANONICAL_SIZE {
+                let start_x = origin_x + mx as f32 * step;
+                let pixels = overlaps(start_y, box_height).flat_map(|(y, wy)| {
+                    overlaps(start_x, box_width).map(move |(x, wx)| (x, y, wx, wy))
+                });
+                mask[(my * CANONICAL_SIZE + mx) as usize] = if max_alpha {
+                    // Only pixels mostly under this mask pixel, otherwise every
+                    // shape would grow by a pixel into its neighbours. Each
+                    // source pixel is at least half under one of them once
+                    // `step` is 1 or more, so none is dropped
+                    pixels
+                        .filter(|&(_, _, wx, wy)| wx >= 0.5 && wy >= 0.5)
+                        .map(|(x, y, _, _)| alpha(min_x + x, min_y + y))
+                        .max()
+                        .unwrap_or(0)
+                } else {
+                    let sum: f32 = pixels
+                        .map(|(x, y, wx, wy)| alpha(min_x + x, min_y + y) as f32 * wx * wy)
+                        .sum();
+                    (sum / (step * step)).round().min(255.0) as u8
+                };
+            }
+        }
+        mask
+    };
+
+    match pooling {
+        CanonicalPooling::Average => pool(false),
+        CanonicalPooling::MaxAlpha => pool(true),
+        CanonicalPooling::Adaptive => {
+            let averaged = pool(false);
+            if step <= 1.0 {
+                return averaged;
+            }
+
+            // Averaging keeps about as much opaque area as the glyph has,
+            // scaled down, unless its lines are too thin to survive it
+            let opaque = |alpha: u8| alpha >= OPAQUE_ALPHA_THRESHOLD;
+            let source_area = (min_y..=max_y)
+                .flat_map(|y| (min_x..=max_x).map(move |x| (x, y)))
+                .filter(|&(x, y)| opaque(alpha(x, y)))
+                .count() as f32;
+            let kept_area = averaged.iter().filter(|&&alpha| opaque(alpha)).count() as f32;
+            if kept_area * 2.0 < source_area / (step * step) {
+                pool(true)
+            } else {
+                averaged
+            }
+        }
+    }
+}
+
+/// Expand a [`canonicalize`]d mask into a black RGBA bitmap detection can read
+pub(crate) fn canonical_rgba(mask: &[u8; 1024]) -> Vec<u8> {
+    mask.iter().flat_map(|&alpha| [0, 0, 0, alpha]).collect()
+}
+
+/// Cut the cursor out of a grayscale capture, e.g. a crop of a video frame's
+/// luminance plane, as an RGBA buffer with a fully opaque or fully transparent
+/// alpha. `None` if the buffer is malformed.
+///
+/// Frames have no alpha, so the background is estimated as the median
+/// luminance of the crop's border and flood-filled inwards through pixels
+/// close to it; whatever the fill doesn't reach is the cursor. That keeps a
+/// cursor's interior even where it matches the background, as long as its
+/// outline is visible.
+pub fn luma_silhouette(luma: &[u8], width: u32, height: u32) -> Option<Vec<u8>> {
+    let (w, h) = (width as usize, height as usize);
+    if w == 0 || h == 0 || luma.len() < w * h {
+        return None;
+    }
+
+    let mut border: Vec<u8> = (0..w)
+        .flat_map(|x| [luma[x], luma[(h - 1) * w + x]])
+        .chain((0..h).flat_map(|y| [luma[y * w], luma[y * w + w - 1]]))
+        .collect();
+    let mid = border.len() / 2;
+    let background = *border.select_nth_unstable(mid).1;
+    let near_background = |i: usize| luma[i].abs_diff(background) <= SILHOUETTE_LUMA_THRESHOLD;
+
+    let mut is_background = vec![false; w * h];
+    let mut stack: Vec<usize> = (0..w)
+        .flat_map(|x| [x, (h - 1) * w + x])
+        .chain((0..h).flat_map(|y| [y * w, y * w + w - 1]))
+        .filter(|&i| near_background(i))
+        .collect();
+    while let Some(i) = stack.pop() {
+        if is_background[i] {
+            continue;
+        }
+        is_background[i] = true;
+
+        let (x, y) = (i % w, i / w);
+        let neighbours = [
+            (x > 0).then(|| i - 1),
+            (x + 1 < w).then(|| i + 1),
+            (y > 0).then(|| i - w),
+            (y + 1 < h).then(|| i + w),
+        ];
+        stack.extend(
+            neighbours
+                .into_iter()
+                .flatten()
+                .filter(|&n| !is_background[n] && near_background(n)),
+        );
+    }
+
+    Some(
+        (0..w * h)
+            .flat_map(|i| {
+                let alpha = if is_background[i] { 0 } else { 255 };
+                [luma[i], luma[i], luma[i], alpha]
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,231 @@
+use crate::cursor::components::ComponentMap;
+
+/// Side length of the coverage grid the perceptual hash is computed from
+const HASH_GRID: u32 = 8;
+
+/// Fingerprint of a bitmap without any opaque pixels
+const BLANK_FINGERPRINT: &str = "0000000000000000-0000";
+
+/// How far from the centroid, in standard deviations, the hashed window reaches
+const HASH_WINDOW_SPREAD: f32 = 2.5;
+
+/// Alpha-weighted first and second moments of a cursor bitmap. Unlike a
+/// thresholded bounding box they change smoothly with anti-aliasing noise.
+struct AlphaMoments {
+    total: f32,
+    center: (f32, f32),
+    spread: (f32, f32),
+}
+
+impl AlphaMoments {
+    fn compute(image_data: &[u8], width: u32, height: u32) -> Option<Self> {
+        if width == 0 || height == 0 || image_data.len() < width as usize * height as usize * 4 {
+            return None;
+        }
+
+        let samples = || {
+            (0..height).flat_map(move |y| {
+                (0..width).map(move |x| {
+                    let alpha = image_data[((y * width + x) * 4 + 3) as usize] as f32 / 255.0;
+                    (x as f32 + 0.5, y as f32 + 0.5, alpha)
+                })
+            })
+        };
+
+        let (total, sum_x, sum_y) = samples()
+            .fold((0.0f32, 0.0f32, 0.0f32), |(t, sx, sy), (x, y, a)| {
+                (t + a, sx + a * x, sy + a * y)
+            });
+        if total <= 0.0 {
+            return None;
+        }
+        let center = (sum_x / total, sum_y / total);
+
+        let (var_x, var_y) = samples().fold((0.0f32, 0.0f32), |(vx, vy), (x, y, a)| {
+            (
+                vx + a * (x - center.0).powi(2),
+                vy + a * (y - center.1).powi(2),
+            )
+        });
+
+        Some(Self {
+            total,
+            center,
+            spread: (
+                (var_x / total).sqrt().max(0.5),
+                (var_y / total).sqrt().max(0.5),
+            ),
+        })
+    }
+}
+
+/// Average hash of a cursor's alpha coverage.
+///
+/// A square window around the alpha-weighted centroid, sized from the spread of
+/// the coverage, is averaged down to an 8x8 grid; each bit is set when its cell
+/// is more covered than the mean. Framing the glyph by its moments rather than
+/// a thresholded bounding box keeps the hash independent of canvas padding and
+/// capture size, and stops single edge pixels from shifting the whole grid.
+pub fn perceptual_hash(image_data: &[u8], width: u32, height: u32) -> Option<u64> {
+    let moments = AlphaMoments::compute(image_data, width, height)?;
+    Some(coverage_hash(image_data, width, height, &moments))
+}
+
+fn coverage_hash(image_data: &[u8], width: u32, height: u32, moments: &AlphaMoments) -> u64 {
+    let half_side = moments.spread.0.max(moments.spread.1) * HASH_WINDOW_SPREAD;
+    let cell = |p: u32, center: f32| {
+        ((p as f32 + 0.5 - center + half_side) / (2.0 * half_side) * HASH_GRID as f32).floor()
+    };
+
+    let mut coverage = [0.0f32; (HASH_GRID * HASH_GRID) as usize];
+    for y in 0..height {
+        for x in 0..width {
+            let (cell_x, cell_y) = (cell(x, moments.center.0), cell(y, moments.center.1));
+            let cells = 0.0..HASH_GRID as f32;
+            if cells.contains(&cell_x) && cells.contains(&cell_y) {
+                let alpha = image_data[((y * width + x) * 4 + 3) as usize] as f32 / 255.0;
+                coverage[(cell_y as u32 * HASH_GRID + cell_x as u32) as usize] += alpha;
+            }
+        }
+    }
+
+    let mean = coverage.iter().sum::<f32>() / coverage.len() as f32;
+
+    coverage
+        .iter()
+        .enumerate()
+        .filter(|(_, &c)| c > mean)
+        .fold(0u64, |hash, (bit, _)| hash | 1 << bit)
+}
+
+/// Stable, appearance-based identifier for grouping cursors across recordings.
+///
+/// The string is the 64-bit [`perceptual_hash`] in hex followed by four coarse,
+/// quantized features: aspect ratio, density, number of connected components
+/// and mean luminance. It only depends on the pixels, so the same cursor
+/// produces the same fingerprint in every session and at any canvas padding.
+///
+/// Collision characteristics:
+/// - Anti-aliasing noise rarely changes it, since the hash averages coverage
+///   over cells of an eighth of the glyph's extent and features are bucketed coarsely.
+/// - Shapes that only differ by details smaller than a hash cell (e.g. a
+///   one-pixel notch) and land in the same feature buckets collide.
+/// - Hue is ignored, so recolored copies of a cursor collide unless their
+///   brightness differs.
+/// - Values sitting right on a bucket edge can flip between neighbouring
+///   buckets, splitting near-identical cursors into two fingerprints.
+/// - Bitmaps without opaque pixels all share a single fingerprint.
+pub fn cursor_fingerprint(image_data: &[u8], width: u32, height: u32) -> String {
+    let (Some(moments), Some(components)) = (
+        AlphaMoments::compute(image_data, width, height),
+        ComponentMap::from_rgba(image_data, width, height),
+    ) else {
+        return BLANK_FINGERPRINT.to_string();
+    };
+    let hash = coverage_hash(image_data, width, height, &moments);
+
+    let (spread_x, spread_y) = moments.spread;
+    // Log scale around 1:1, so tall and wide shapes get as many buckets
+    let aspect = ((spread_x / spread_y).log2() * 2.0 + 4.0)
+        .round()
+        .clamp(0.0, 8.0) as u32;
+    // A solid rectangle of the same spread would be 2 * sqrt(3) standard
+    // deviations on each side, so this is 1.0 for a filled box
+    let fill = moments.total / (12.0 * spread_x * spread_y);
+    let fill = (fill * 4.0).floor().min(4.0) as u32;
+    // Components so small they're likely anti-aliasing specks don't count
+    let min_area = ((moments.total / 100.0) as u32).max(2);
+    let blobs = components
+        .components()
+        .iter()
+        .filter(|c| c.area >= min_area)
+        .count()
+        .min(9);
+    let luminance = mean_luminance(image_data, width, height);
+    let brightness = (luminance * 4.0).floor().min(3.0) as u32;
+
+    format!("{hash:016x}-{aspect}{fill}{blobs}{brightness}")
+}
+
+/// Mean luminance in 0..=1 of the opaque pixels, weighted by alpha
+fn mean_luminance(image_data: &[u8], width: u32, height: u32) -> f32 {
+    let (sum, weight) = image_data[..width as usize * height as usize * 4]
+        .chunks_exact(4)
+        .filter(|pixel| pixel[3] > 0)
+        .fold((0.0f32, 0.0f32), |(sum, weight), pixel| {
+            let alpha = pixel[3] as f32 / 255.0;
+            let luma =
+                (0.2126 * pixel[0] as f32 + 0.7152 * pixel[1] as f32 + 0.0722 * pixel[2] as f32)
+                    / 255.0;
+            (sum + luma * alpha, weight + alpha)
+        });
+
+    if weight > 0.0 {
+        sum / weight
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::detection::CommonCursorType;
+    use crate::cursor::rendering::rasterize_cursor;
+
+    fn fingerprint_of(cursor_type: CommonCursorType, size: u32) -> String {
+        let image = rasterize_cursor(&cursor_type, size).unwrap();
+        cursor_fingerprint(image.as_raw(), size, size)
+    }
+
+    #[test]
+    fn test_fingerprint_is_stable() {
+        assert_eq!(
+            fingerprint_of(CommonCursorType::Arrow, 32),
+            fingerprint_of(CommonCursorType::Arrow, 32)
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_antialiasing_noise() {
+        let image = rasterize_cursor(&CommonCursorType::PointingHand, 32).unwrap();
+        let mut noisy = image.clone();
+        // Nudge every partially transparent edge pixel a little
+        for (i, pixel) in noisy.pixels_mut().enumerate() {
+            if pixel[3] > 0 && pixel[3] < 255 {
+                let delta = if i % 2 == 0 { 12 } else { -12 };
+                pixel[3] = (pixel[3] as i32 + delta).clamp(1, 254) as u8;
+            }
+        }
+
+        assert_eq!(
+            cursor_fingerprint(image.as_raw(), 32, 32),
+            cursor_fingerprint(noisy.as_raw(), 32, 32)
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_distinguishes_shapes() {
+        let fingerprints = [
+            CommonCursorType::Arrow,
+            CommonCursorType::IBeam,
+            CommonCursorType::Crosshair,
+            CommonCursorType::PointingHand,
+            CommonCursorType::ResizeNWSE,
+            CommonCursorType::ResizeEW,
+        ]
+        .map(|cursor_type| fingerprint_of(cursor_type, 32));
+
+        for (i, a) in fingerprints.iter().enumerate() {
+            for b in &fingerprints[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_blank_fingerprint() {
+        let blank = vec![0u8; 16 * 16 * 4];
+        assert_eq!(cursor_fingerprint(&blank, 16, 16), BLANK_FINGERPRINT);
+    }
+}
@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, Sub};
+
+/// A pixel position, e.g. where a cursor's hotspot goes on a frame.
+///
+/// Converts from and to `(x, y)` tuples, so callers passing those keep
+/// working wherever the API takes `impl Into<Point>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub const ORIGIN: Self = Self::new(0, 0);
+
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<(i32, i32)> for Point {
+    fn from((x, y): (i32, i32)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<Point> for (i32, i32) {
+    fn from(point: Point) -> Self {
+        (point.x, point.y)
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+/// Pixel dimensions of an image, e.g. a captured cursor bitmap.
+///
+/// Converts from and to `(w, h)` tuples like [`Point`] does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Size {
+    pub w: u32,
+    pub h: u32,
+}
+
+impl Size {
+    pub const fn new(w: u32, h: u32) -> Self {
+        Self { w, h }
+    }
+
+    /// A `size` by `size` square, the shape every rasterized cursor has
+    pub const fn square(size: u32) -> Self {
+        Self::new(size, size)
+    }
+
+    /// Where a hotspot given as a fraction of this size lands, relative to
+    /// the top-left corner, rounded to the nearest pixel
+    pub fn scale_hotspot(self, (x, y): (f32, f32)) -> Point {
+        Point::new(
+            (x * self.w as f32).round() as i32,
+            (y * self.h as f32).round() as i32,
+        )
+    }
+}
+
+impl From<(u32, u32)> for Size {
+    fn from((w, h): (u32, u32)) -> Self {
+        Self { w, h }
+    }
+}
+
+impl From<Size> for (u32, u32) {
+    fn from(size: Size) -> Self {
+        (size.w, size.h)
+    }
+}
+
+/// A rectangle of pixels, e.g. a damaged region of a captured cursor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub struct Rect {
+    /// Top-left corner
+    pub origin: Point,
+    pub size: Size,
+}
+
+impl Rect {
+    pub fn new(origin: impl Into<Point>, size: impl Into<Size>) -> Self {
+        Self {
+            origin: origin.into(),
+            size: size.into(),
+        }
+    }
+}
+
+/// The smallest window of a `frame_size` frame sure to hold a cursor up to
+/// `expected_max_size` pixels on a side whose hotspot is at `approx_pos`, for
+/// copying no more of the frame than detection needs.
+///
+/// The hotspot can be anywhere in the cursor, so the window reaches
+/// `expected_max_size` past it on every side. Near an edge it's shifted back
+/// inside the frame rather than cut off, and it's never larger than the frame
+pub fn detection_crop_rect(
+    approx_pos: impl Into<Point>,
+    expected_max_size: u32,
+    frame_size: impl Into<Size>,
+) -> Rect {
+    let (approx_pos, frame_size) = (approx_pos.into(), frame_size.into());
+    let side = |frame: u32| expected_max_size.saturating_mul(2).min(frame);
+    let size = Size::new(side(frame_size.w), side(frame_size.h));
+    let start = |pos: i32, window: u32, frame: u32| {
+        let start = pos as i64 - expected_max_size as i64;
+        start.clamp(0, (frame - window) as i64) as i32
+    };
+
+    Rect::new(
+        Point::new(
+            start(approx_pos.x, size.w, frame_size.w),
+            start(approx_pos.y, size.h, frame_size.h),
+        ),
+        size,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tuple_conversions_keep_axes() {
+        let point = Point::from((3, -7));
+        assert_eq!((point.x, point.y), (3, -7));
+        assert_eq!(<(i32, i32)>::from(point), (3, -7));
+
+        let size = Size::from((40, 20));
+        assert_eq!((size.w, size.h), (40, 20));
+        assert_eq!(<(u32, u32)>::from(size), (40, 20));
+
+        assert_eq!(Point::new(5, 5) - Point::new(2, 8), Point::new(3, -3));
+        assert_eq!(
+            Size::new(40, 20).scale_hotspot((0.5, 0.25)),
+            Point::new(20, 5)
+        );
+    }
+
+    #[test]
+    fn test_detection_crop_rect_stays_inside_the_frame() {
+        let frame = (1920, 1080);
+        assert_eq!(
+            detection_crop_rect((500, 400), 64, frame),
+            Rect::new((436, 336), (128, 128))
+        );
+
+        // Shifted inward at the edges, not cut off
+        assert_eq!(
+            detection_crop_rect((10, 1075), 64, frame),
+            Rect::new((0, 952), (128, 128))
+        );
+        assert_eq!(
+            detection_crop_rect((-30, 2000), 64, frame),
+            Rect::new((0, 952), (128, 128))
+        );
+        assert_eq!(
+            detection_crop_rect((1919, 0), 64, frame),
+            Rect::new((1792, 0), (128, 128))
+        );
+
+        // A frame smaller than the window is the window
+        assert_eq!(
+            detection_crop_rect((20, 20), 64, (100, 40)),
+            Rect::new((0, 0), (100, 40))
+        );
+        assert_eq!(
+            detection_crop_rect((0, 0), 64, (0, 0)),
+            Rect::new((0, 0), (0, 0))
+        );
+    }
+}
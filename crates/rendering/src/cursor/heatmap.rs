@@ -0,0 +1,255 @@
+use crate::cursor::error::CursorError;
+use cap_project::CursorEvents;
+use image::RgbaImage;
+use std::path::Path;
+
+/// Gaussian splats are cut off this many standard deviations from their center
+const SPLAT_RADIUS_SIGMAS: f32 = 3.0;
+
+/// Colors the normalized presence is mapped through, from barely visited to
+/// the hottest cell. Unvisited cells stay transparent
+const HEATMAP_GRADIENT: [(f32, [u8; 3]); 4] = [
+    (0.0, [0, 0, 255]),
+    (0.33, [0, 255, 255]),
+    (0.66, [255, 255, 0]),
+    (1.0, [255, 0, 0]),
+];
+
+/// What [`PresenceHeatmap`] does with cursor positions outside the frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutOfBounds {
+    /// Drop them, e.g. when the cursor left the recorded display
+    #[default]
+    Ignore,
+    /// Count them at the nearest frame edge
+    Clamp,
+}
+
+/// Grid resolution and splatting of a [`PresenceHeatmap`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeatmapConfig {
+    /// Grid size in cells, usually the frame size divided down
+    pub width: u32,
+    pub height: u32,
+    /// Standard deviation of each position's Gaussian splat, in cells
+    pub sigma: f32,
+    pub out_of_bounds: OutOfBounds,
+}
+
+impl Default for HeatmapConfig {
+    fn default() -> Self {
+        Self {
+            width: 160,
+            height: 90,
+            sigma: 2.0,
+            out_of_bounds: OutOfBounds::Ignore,
+        }
+    }
+}
+
+/// Where on screen the cursor spent its time over a recording.
+///
+/// Positions are in the recording's normalized display space, 0..1 on both
+/// axes like [`CursorEvents`] stores them, and are splatted onto a downscaled
+/// grid weighted by how long the cursor stayed there.
+pub struct PresenceHeatmap {
+    config: HeatmapConfig,
+    cells: Vec<f32>,
+}
+
+impl PresenceHeatmap {
+    pub fn new(config: HeatmapConfig) -> Self {
+        let config = HeatmapConfig {
+            width: config.width.max(1),
+            height: config.height.max(1),
+            sigma: config.sigma.max(0.1),
+            ..config
+        };
+
+        Self {
+            cells: vec![0.0; (config.width * config.height) as usize],
+            config,
+        }
+    }
+
+    /// Add every move of a recording's cursor track, each weighted by the
+    /// seconds until the next move. The last position has no known end, so
+    /// it isn't counted; add it with [`Self::add_position`] if the recording's
+    /// duration is known.
+    pub fn add_track(&mut self, track: &CursorEvents) {
+        for pair in track.moves.windows(2) {
+            let dwell_secs = (pair[1].time_ms - pair[0].time_ms) / 1000.0;
+            if dwell_secs > 0.0 {
+                self.add_position(pair[0].x, pair[0].y, dwell_secs as f32);
+            }
+        }
+    }
+
+    /// Splat a single normalized position with the given weight
+    pub fn add_position(&mut self, x: f64, y: f64, weight: f32) {
+        if !x.is_finite() || !y.is_finite() || !weight.is_finite() || weight <= 0.0 {
+            return;
+        }
+
+        let (x, y) = match self.config.out_of_bounds {
+            OutOfBounds::Ignore if !(0.0..=1.0).contains(&x) || !(0.0..=1.0).contains(&y) => {
+                return;
+            }
+            OutOfBounds::Ignore => (x, y),
+            OutOfBounds::Clamp => (x.clamp(0.0, 1.0), y.clamp(0.0, 1.0)),
+        };
+
+        let HeatmapConfig {
+            width,
+            height,
+            sigma,
+            ..
+        } = self.config;
+        let center = (x as f32 * width as f32, y as f32 * height as f32);
+        let radius = sigma * SPLAT_RADIUS_SIGMAS;
+        let span = |center: f32, len: u32| {
+            let first = (center - radius).floor().max(0.0) as u32;
+            let last = ((center + radius).ceil().max(0.0) as u32).min(len);
+            first..last
+        };
+
+        for cy in span(center.1, height) {
+            let dy = cy as f32 + 0.5 - center.1;
+            for cx in span(center.0, width) {
+                let dx = cx as f32 + 0.5 - center.0;
+                let falloff = (-(dx * dx + dy * dy) / (2.0 * sigma * sigma)).exp();
+                self.cells[(cy * width + cx) as usize] += weight * falloff;
+            }
+        }
+    }
+
+    /// Accumulated presence of a cell, in weighted seconds
+    pub fn value(&self, x: u32, y: u32) -> f32 {
+        if x >= self.config.width || y >= self.config.height {
+            return 0.0;
+        }
+
+        self.cells[(y * self.config.width + x) as usize]
+    }
+
+    /// The heatmap as an image the size of the grid, colored relative to its
+    /// hottest cell and transparent where the cursor never went
+    pub fn to_image(&self) -> RgbaImage {
+        let peak = self.cells.iter().copied().fold(0.0f32, f32::max);
+
+        RgbaImage::from_fn(self.config.width, self.config.height, |x, y| {
+            if peak <= 0.0 {
+                return image::Rgba([0, 0, 0, 0]);
+            }
+
+            let t = self.value(x, y) / peak;
+            let [r, g, b] = gradient_color(t);
+            // Square root so the rarely visited areas stay visible
+            image::Rgba([r, g, b, (t.sqrt() * 255.0).round() as u8])
+        })
+    }
+
+    /// Export the heatmap as a PNG, see [`Self::to_image`]
+    pub fn render(&self, out: &Path) -> Result<(), CursorError> {
+        self.to_image()
+            .save_with_format(out, image::ImageFormat::Png)
+            .map_err(|e| CursorError::Write {
+                path: out.to_path_buf(),
+                message: e.to_string(),
+            })
+    }
+}
+
+fn gradient_color(t: f32) -> [u8; 3] {
+    let t = t.clamp(0.0, 1.0);
+    let upper = HEATMAP_GRADIENT
+        .iter()
+        .position(|(stop, _)| *stop >= t)
+        .unwrap_or(HEATMAP_GRADIENT.len() - 1)
+        .max(1);
+    let (low, low_color) = HEATMAP_GRADIENT[upper - 1];
+    let (high, high_color) = HEATMAP_GRADIENT[upper];
+    let mix = (t - low) / (high - low);
+
+    std::array::from_fn(|i| {
+        (low_color[i] as f32 + (high_color[i] as f32 - low_color[i] as f32) * mix).round() as u8
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cap_project::CursorMoveEvent;
+
+    fn track(moves: &[(f64, f64, f64)]) -> CursorEvents {
+        CursorEvents {
+            clicks: vec![],
+            moves: moves
+                .iter()
+                .map(|&(time_ms, x, y)| CursorMoveEvent {
+                    active_modifiers: vec![],
+                    cursor_id: "0".to_string(),
+                    time_ms,
+                    x,
+                    y,
+                })
+                .collect(),
+        }
+    }
+
+    fn small_heatmap(out_of_bounds: OutOfBounds) -> PresenceHeatmap {
+        PresenceHeatmap::new(HeatmapConfig {
+            width: 20,
+            height: 10,
+            sigma: 1.0,
+            out_of_bounds,
+        })
+    }
+
+    #[test]
+    fn test_weights_positions_by_dwell_time() {
+        let mut heatmap = small_heatmap(OutOfBounds::Ignore);
+        // Two seconds top-left, half a second bottom-right
+        heatmap.add_track(&track(&[
+            (0.0, 0.125, 0.25),
+            (2000.0, 0.875, 0.75),
+            (2500.0, 0.5, 0.5),
+        ]));
+
+        let (top_left, bottom_right) = (heatmap.value(2, 2), heatmap.value(17, 7));
+        assert!(top_left > 0.0 && bottom_right > 0.0);
+        assert!((top_left / bottom_right - 4.0).abs() < 0.01);
+        // The last position has no dwell time
+        assert_eq!(heatmap.value(10, 5), 0.0);
+    }
+
+    #[test]
+    fn test_out_of_bounds_positions() {
+        let mut ignored = small_heatmap(OutOfBounds::Ignore);
+        ignored.add_position(1.5, 0.5, 1.0);
+        assert!(ignored.cells.iter().all(|&c| c == 0.0));
+
+        let mut clamped = small_heatmap(OutOfBounds::Clamp);
+        clamped.add_position(1.5, 0.5, 1.0);
+        assert!(clamped.value(19, 5) > 0.5);
+        assert_eq!(clamped.value(0, 5), 0.0);
+    }
+
+    #[test]
+    fn test_render_heatmap_png() {
+        let mut heatmap = small_heatmap(OutOfBounds::Ignore);
+        heatmap.add_position(0.5, 0.5, 1.0);
+
+        let image = heatmap.to_image();
+        assert_eq!(image.dimensions(), (20, 10));
+        // Hottest cells are red and opaque, untouched ones transparent
+        let hottest = image.get_pixel(10, 5);
+        assert_eq!((hottest[0], hottest[3]), (255, 255));
+        assert_eq!(image.get_pixel(0, 0)[3], 0);
+
+        let out = std::env::temp_dir().join(format!("cursor-heatmap-{}.png", std::process::id()));
+        heatmap.render(&out).unwrap();
+        assert_eq!(image::open(&out).unwrap().to_rgba8(), image);
+        std::fs::remove_file(&out).unwrap();
+    }
+}
@@ -0,0 +1,357 @@
+use crate::cursor::assets::{CursorAssetSource, EmbeddedSource};
+use crate::cursor::detection::CommonCursorType;
+use crate::cursor::error::CursorError;
+#[cfg(feature = "svg")]
+use crate::cursor::rendering::parse_svg;
+use crate::cursor::rendering::{rasterize_cursor, CursorRenderSource};
+use image::GenericImageView;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// Custom SVGs larger than this are rejected outright
+pub const MAX_CURSOR_SVG_BYTES: usize = 1024 * 1024;
+
+/// Custom SVGs larger than this are accepted but flagged, see [`SvgMeta::over_budget`]
+pub const CURSOR_SVG_BUDGET_BYTES: usize = 64 * 1024;
+
+/// Size [`self_test`] rasterizes the bundled cursors at
+pub const SELF_TEST_SIZE: u32 = 32;
+
+/// Environment variable naming a directory [`self_test`] dumps its
+/// mismatches to, see [`dump_self_test_mismatches`]. Unset, nothing is written
+pub const SELF_TEST_DUMP_DIR_ENV: &str = "CAP_CURSOR_SELF_TEST_DUMP_DIR";
+
+/// Largest width or height of a custom SVG's canvas, in SVG user units
+const MAX_CURSOR_SVG_DIMENSION: f32 = 1024.0;
+
+/// Most elongated canvas accepted, as the ratio of its long to its short side
+const MAX_CURSOR_SVG_ASPECT: f32 = 4.0;
+
+/// Map to store detected cursor types for cached lookup
+pub type CursorTypeMap = HashMap<String, CommonCursorType>;
+
+/// Load SVG content for a cursor type from the SVGs embedded in the binary
+pub fn load_cursor_svg(cursor_type: &CommonCursorType) -> Option<Vec<u8>> {
+    load_cursor_svg_from(&EmbeddedSource, cursor_type)
+}
+
+/// Load SVG content for a cursor type from `source`, e.g. a
+/// [`ChainedSource`](crate::cursor::assets::ChainedSource) of the user's
+/// cursor pack, their theme and the embedded SVGs
+pub fn load_cursor_svg_from(
+    source: &dyn CursorAssetSource,
+    cursor_type: &CommonCursorType,
+) -> Option<Vec<u8>> {
+    source.read_cursor(cursor_type)
+}
+
+/// Whether an SVG is available for a cursor type, which with only the
+/// embedded SVGs is every type
+pub fn has_svg(cursor_type: &CommonCursorType) -> bool {
+    has_svg_from(&EmbeddedSource, cursor_type)
+}
+
+/// Whether `source` has an SVG for a cursor type, e.g. to gray out the types
+/// a configured chain of packs and themes can't draw. Doesn't load the SVG
+/// where the source can tell without
+pub fn has_svg_from(source: &dyn CursorAssetSource, cursor_type: &CommonCursorType) -> bool {
+    source.contains_cursor(cursor_type)
+}
+
+/// Analyze a cursor image and try to detect its type.
+///
+/// Decoders hand out pixels in RGB order whatever the file stores, BMP and
+/// ICO included, so the image is read as
+/// [`ChannelOrder::Rgba`](crate::cursor::detection::ChannelOrder::Rgba); only raw
+/// capture buffers need a different order passed explicitly.
+pub fn analyze_cursor_image(image_path: &Path) -> Option<CommonCursorType> {
+    // Load the image and analyze it
+    if let Ok(img) = image::open(image_path) {
+        let rgba = img.to_rgba8();
+        let (width, height) = img.dimensions();
+        CommonCursorType::detect_from_image(&rgba.into_raw(), width, height)
+    } else {
+        None
+    }
+}
+
+/// Analyze every image in `dir`, e.g. a folder of captured cursors, returning
+/// each file's path with its detected type in path order. Files the `image`
+/// crate doesn't recognize by extension are skipped, while images that fail
+/// to decode are listed as undetected. Subdirectories aren't searched
+pub fn analyze_cursor_directory(
+    dir: &Path,
+) -> Result<Vec<(PathBuf, Option<CommonCursorType>)>, CursorError> {
+    analyze_cursor_directory_with_progress(dir, |_, _| {})
+}
+
+/// Like [`analyze_cursor_directory`], calling `progress` with the number of
+/// files analyzed so far and the total as each one completes.
+///
+/// The files are spread over a thread per core, so `progress` is called from
+/// several threads, but never from two at once and always with `done` one
+/// higher than the call before
+pub fn analyze_cursor_directory_with_progress(
+    dir: &Path,
+    progress: impl Fn(usize, usize) + Sync,
+) -> Result<Vec<(PathBuf, Option<CommonCursorType>)>, CursorError> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .map_err(|e| CursorError::Read {
+            path: dir.to_path_buf(),
+            message: e.to_string(),
+        })?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && image::ImageFormat::from_path(path).is_ok())
+        .collect();
+    paths.sort();
+
+    let total = paths.len();
+    let workers = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(total);
+    let next = AtomicUsize::new(0);
+    let done = Mutex::new(0usize);
+    let mut cursor_types = vec![None; total];
+
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut analyzed = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = paths.get(index) else {
+                            break analyzed;
+                        };
+                        analyzed.push((index, analyze_cursor_image(path)));
+
+                        // A panicking callback only poisons the count for the
+                        // others, it's still right
+                        let mut done = done.lock().unwrap_or_else(PoisonError::into_inner);
+                        *done += 1;
+                        progress(*done, total);
+                    }
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            let analyzed = worker
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+            for (index, cursor_type) in analyzed {
+                cursor_types[index] = cursor_type;
+            }
+        }
+    });
+
+    Ok(paths.into_iter().zip(cursor_types).collect())
+}
+
+/// Analyze a cursor image and pick what to draw for it: the bundled SVG when
+/// the type is recognized with a score of at least
+/// [`MATCH_THRESHOLD`](crate::cursor::detection::MATCH_THRESHOLD), otherwise
+/// the image itself with the given hotspot (relative to the image size).
+/// `None` if the image can't be loaded.
+pub fn analyze_cursor_image_detailed(
+    image_path: &Path,
+    hotspot: (f32, f32),
+) -> Option<CursorRenderSource> {
+    let rgba = image::open(image_path).ok()?.to_rgba8();
+    let (width, height) = rgba.dimensions();
+
+    Some(
+        match CommonCursorType::detect_from_image(rgba.as_raw(), width, height) {
+            Some(cursor_type) => CursorRenderSource::KnownType(cursor_type),
+            None => CursorRenderSource::RawBitmap(rgba, hotspot),
+        },
+    )
+}
+
+/// Rasterize every bundled cursor and check that detection classifies it as
+/// its own type, returning the `(expected, got)` pairs that don't.
+///
+/// Catches threshold changes that make one type's matcher shadow another's.
+/// A type whose SVG isn't recognized at all, or doesn't rasterize, is
+/// reported paired with itself. With [`SELF_TEST_DUMP_DIR_ENV`] set, the
+/// mismatches are also dumped there for CI to keep; a dump that fails to
+/// write doesn't change the result.
+pub fn self_test() -> Result<(), Vec<(CommonCursorType, CommonCursorType)>> {
+    let mismatches: Vec<_> = CommonCursorType::ALL
+        .iter()
+        .filter_map(|&expected| {
+            let detected = rasterize_cursor(&expected, SELF_TEST_SIZE)
+                .ok()
+                .and_then(|image| {
+                    CommonCursorType::detect_from_image(
+                        image.as_raw(),
+                        SELF_TEST_SIZE,
+                        SELF_TEST_SIZE,
+                    )
+                });
+
+            match detected {
+                Some(got) if got == expected => None,
+                got => Some((expected, got.unwrap_or(expected))),
+            }
+        })
+        .collect();
+
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+    if let Some(dir) = std::env::var_os(SELF_TEST_DUMP_DIR_ENV) {
+        let _ = dump_self_test_mismatches(Path::new(&dir), &mismatches);
+    }
+    Err(mismatches)
+}
+
+/// Write what the self-test saw for each `(expected, got)` mismatch into
+/// `dir`, created if needed: the bundled cursor rasterized at
+/// [`SELF_TEST_SIZE`] as `<stem>.png` and its full
+/// [`CommonCursorType::detect_ranked`] output as `<stem>-ranked.txt`
+pub fn dump_self_test_mismatches(
+    dir: &Path,
+    mismatches: &[(CommonCursorType, CommonCursorType)],
+) -> Result<(), CursorError> {
+    let write_error = |path: &Path, message: String| CursorError::Write {
+        path: path.to_path_buf(),
+        message,
+    };
+    std::fs::create_dir_all(dir).map_err(|e| write_error(dir, e.to_string()))?;
+
+    for &(expected, got) in mismatches {
+        let stem = expected.svg_filename().trim_end_matches(".svg");
+        let mut report = format!("expected {expected:?}, detected {got:?}\n");
+
+        match rasterize_cursor(&expected, SELF_TEST_SIZE) {
+            Ok(image) => {
+                let png = dir.join(format!("{stem}.png"));
+                image
+                    .save_with_format(&png, image::ImageFormat::Png)
+                    .map_err(|e| write_error(&png, e.to_string()))?;
+                let ranked =
+                    CommonCursorType::detect_ranked(image.as_raw(), SELF_TEST_SIZE, SELF_TEST_SIZE);
+                for (cursor_type, score) in ranked {
+                    report.push_str(&format!("{cursor_type:?} {score:.4}\n"));
+                }
+            }
+            Err(e) => report.push_str(&format!("failed to rasterize: {e}\n")),
+        }
+
+        let txt = dir.join(format!("{stem}-ranked.txt"));
+        std::fs::write(&txt, report).map_err(|e| write_error(&txt, e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// What [`validate_cursor_svg`] found out about an SVG
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SvgMeta {
+    /// Canvas size in SVG user units, from `width`/`height` or the viewBox
+    pub width: f32,
+    pub height: f32,
+    /// Size of the SVG source
+    pub byte_len: usize,
+    /// Whether the source exceeds [`CURSOR_SVG_BUDGET_BYTES`]. Such a cursor
+    /// still works but is slow to parse and rasterize for what it draws
+    pub over_budget: bool,
+}
+
+/// Check that user-supplied SVG content is usable as a cursor: it parses, its
+/// canvas is a plausible cursor size and shape, it draws something and isn't
+/// larger than [`MAX_CURSOR_SVG_BYTES`]. Parsing needs the `svg` feature,
+/// without it every SVG fails with [`CursorError::SvgUnavailable`].
+pub fn validate_cursor_svg(bytes: &[u8]) -> Result<SvgMeta, CursorError> {
+    if bytes.len() > MAX_CURSOR_SVG_BYTES {
+        return Err(CursorError::SvgTooLarge {
+            len: bytes.len(),
+            max: MAX_CURSOR_SVG_BYTES,
+        });
+    }
+
+    let (width, height, draws_anything) = svg_canvas(bytes)?;
+
+    if width > MAX_CURSOR_SVG_DIMENSION || height > MAX_CURSOR_SVG_DIMENSION {
+        return Err(CursorError::InvalidSvg(format!(
+            "canvas of {}x{} is larger than {} units",
+            width, height, MAX_CURSOR_SVG_DIMENSION
+        )));
+    }
+    if width.max(height) / width.min(height) > MAX_CURSOR_SVG_ASPECT {
+        return Err(CursorError::InvalidSvg(format!(
+            "canvas of {}x{} is too elongated for a cursor",
+            width, height
+        )));
+    }
+    if !draws_anything {
+        return Err(CursorError::InvalidSvg("nothing is drawn".to_string()));
+    }
+
+    Ok(SvgMeta {
+        width,
+        height,
+        byte_len: bytes.len(),
+        over_budget: bytes.len() > CURSOR_SVG_BUDGET_BYTES,
+    })
+}
+
+/// Canvas size of an SVG in user units, and whether it draws anything
+#[cfg(feature = "svg")]
+fn svg_canvas(bytes: &[u8]) -> Result<(f32, f32, bool), CursorError> {
+    let tree = parse_svg(bytes)?;
+    Ok((
+        tree.size().width(),
+        tree.size().height(),
+        tree.root().has_children(),
+    ))
+}
+
+#[cfg(not(feature = "svg"))]
+fn svg_canvas(_bytes: &[u8]) -> Result<(f32, f32, bool), CursorError> {
+    Err(CursorError::SvgUnavailable)
+}
+
+/// User-supplied cursor SVGs by name. Only SVGs that pass
+/// [`validate_cursor_svg`] are registered, so everything in here rasterizes.
+#[derive(Debug, Default)]
+pub struct CustomCursorRegistry {
+    cursors: HashMap<String, (Vec<u8>, SvgMeta)>,
+}
+
+impl CustomCursorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validate and register an SVG, replacing any cursor of the same name
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        svg_content: Vec<u8>,
+    ) -> Result<SvgMeta, CursorError> {
+        let meta = validate_cursor_svg(&svg_content)?;
+        self.cursors.insert(name.into(), (svg_content, meta));
+
+        Ok(meta)
+    }
+
+    /// SVG content of a registered cursor
+    pub fn svg(&self, name: &str) -> Option<&[u8]> {
+        self.cursors.get(name).map(|(svg, _)| svg.as_slice())
+    }
+
+    pub fn meta(&self, name: &str) -> Option<&SvgMeta> {
+        self.cursors.get(name).map(|(_, meta)| meta)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cursors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cursors.is_empty()
+    }
+}
@@ -0,0 +1,45 @@
+//! Cursor detection, bundled SVG loading, rasterization and compositing.
+//!
+//! The submodules group the API by task; [`prelude`] re-exports the types most
+//! callers need, so `use cap_rendering::cursor::prelude::*` is enough to detect
+//! and draw a captured cursor.
+
+pub mod analysis_log;
+pub mod ani;
+pub mod assets;
+pub mod atlas;
+pub mod cache;
+pub mod calibration;
+mod components;
+mod corpus_tests;
+pub mod detection;
+mod error;
+pub mod evaluation;
+mod features;
+mod fingerprint;
+pub mod geometry;
+pub mod heatmap;
+pub mod loading;
+pub mod pack;
+pub mod renderer;
+pub mod rendering;
+mod stream;
+mod svg_tests;
+pub mod system;
+pub mod theme;
+pub mod track;
+
+pub use error::CursorError;
+
+/// The commonly used cursor types and traits
+pub mod prelude {
+    pub use super::cache::{CursorCache, DetectionCache, PersistentCursorCache};
+    pub use super::detection::{
+        CommonCursorType, CursorAnalysis, CursorDetectExt, DetectionConfig, DetectionOutcome,
+        PartialDetectionConfig,
+    };
+    pub use super::geometry::{Point, Rect, Size};
+    pub use super::renderer::CursorRenderer;
+    pub use super::rendering::CursorRenderSource;
+    pub use super::CursorError;
+}
@@ -0,0 +1,324 @@
+use crate::cursor::error::CursorError;
+use crate::cursor::loading::{validate_cursor_svg, SvgMeta};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Name of the archive [`CursorPack::load`] looks for in a pack directory
+pub const CURSOR_ARCHIVE_FILE: &str = "cursors.pack";
+
+/// Magic bytes every cursor archive starts with
+const ARCHIVE_MAGIC: &[u8; 8] = b"CAPCURS1";
+
+/// How a [`CursorPack`] got its SVGs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackLoadPath {
+    /// Memory-mapped from the pack's [`CURSOR_ARCHIVE_FILE`]
+    Archive,
+    /// Read from the pack's `*.svg` files one by one
+    Files,
+}
+
+enum PackBytes {
+    Mapped(memmap2::Mmap),
+    Owned(Vec<u8>),
+}
+
+impl PackBytes {
+    fn as_slice(&self) -> &[u8] {
+        match self {
+            PackBytes::Mapped(map) => map,
+            PackBytes::Owned(bytes) => bytes,
+        }
+    }
+}
+
+struct PackEntry {
+    range: Range<usize>,
+    /// Filled in by the first lookup, so unused cursors are never parsed
+    meta: OnceLock<Result<SvgMeta, CursorError>>,
+}
+
+/// A directory of user-supplied cursor SVGs, named after their file stems.
+///
+/// Packs with dozens of cursors can ship a [`CURSOR_ARCHIVE_FILE`] built by
+/// [`write_cursor_archive`], which is memory-mapped instead of opening every
+/// file at startup. Either way an SVG is only validated when it's first looked
+/// up.
+pub struct CursorPack {
+    bytes: PackBytes,
+    entries: HashMap<String, PackEntry>,
+    load_path: PackLoadPath,
+}
+
+impl CursorPack {
+    /// Load the pack in `dir`, from its archive when there is one and from the
+    /// individual `*.svg` files otherwise
+    pub fn load(dir: &Path) -> Result<Self, CursorError> {
+        let archive = dir.join(CURSOR_ARCHIVE_FILE);
+        if archive.is_file() {
+            Self::load_archive(&archive)
+        } else {
+            Self::load_files(dir)
+        }
+    }
+
+    fn load_archive(path: &Path) -> Result<Self, CursorError> {
+        let read_error = |e: std::io::Error| CursorError::Read {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        };
+        let file = std::fs::File::open(path).map_err(read_error)?;
+        // SAFETY: the map is read-only and the archive isn't expected to change
+        // while the app runs. If another process truncates it anyway, reading
+        // the missing pages faults, which is the accepted risk of mapping files.
+        let map = unsafe { memmap2::Mmap::map(&file) }.map_err(read_error)?;
+
+        let entries = parse_archive_index(&map)?;
+
+        Ok(Self {
+            bytes: PackBytes::Mapped(map),
+            entries,
+            load_path: PackLoadPath::Archive,
+        })
+    }
+
+    fn load_files(dir: &Path) -> Result<Self, CursorError> {
+        let read_error = |path: &Path, e: std::io::Error| CursorError::Read {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        };
+
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .map_err(|e| read_error(dir, e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "svg"))
+            .collect();
+        paths.sort();
+
+        // All files go into one buffer, so both load paths share the lookups
+        let mut bytes = Vec::new();
+        let mut entries = HashMap::new();
+        for path in paths {
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let start = bytes.len();
+            bytes.extend(std::fs::read(&path).map_err(|e| read_error(&path, e))?);
+            entries.insert(name.to_string(), PackEntry::new(start..bytes.len()));
+        }
+
+        Ok(Self {
+            bytes: PackBytes::Owned(bytes),
+            entries,
+            load_path: PackLoadPath::Files,
+        })
+    }
+
+    pub fn load_path(&self) -> PackLoadPath {
+        self.load_path
+    }
+
+    /// Names of the pack's cursors, in no particular order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// SVG content of a cursor, validated on first use. `None` if the pack has
+    /// no cursor of that name
+    pub fn svg(&self, name: &str) -> Option<Result<&[u8], CursorError>> {
+        let entry = self.entries.get(name)?;
+        let svg = &self.bytes.as_slice()[entry.range.clone()];
+
+        Some(
+            entry
+                .meta
+                .get_or_init(|| validate_cursor_svg(svg))
+                .clone()
+                .map(|_| svg),
+        )
+    }
+
+    /// What validation found out about a cursor's SVG, see [`Self::svg`]
+    pub fn meta(&self, name: &str) -> Option<Result<SvgMeta, CursorError>> {
+        let entry = self.entries.get(name)?;
+        let svg = &self.bytes.as_slice()[entry.range.clone()];
+
+        Some(entry.meta.get_or_init(|| validate_cursor_svg(svg)).clone())
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl PackEntry {
+    fn new(range: Range<usize>) -> Self {
+        Self {
+            range,
+            meta: OnceLock::new(),
+        }
+    }
+}
+
+/// Write named SVGs into a single archive that [`CursorPack::load`] can map.
+///
+/// The layout is [`ARCHIVE_MAGIC`], a little-endian `u32` entry count, then
+/// per entry a `u16` name length, the UTF-8 name and `u64` offset and length
+/// of its SVG from the start of the file, followed by the SVG data.
+pub fn write_cursor_archive(entries: &[(&str, &[u8])], path: &Path) -> Result<(), CursorError> {
+    let index_len: usize = ARCHIVE_MAGIC.len()
+        + 4
+        + entries
+            .iter()
+            .map(|(name, _)| 2 + name.len() + 16)
+            .sum::<usize>();
+
+    let mut archive =
+        Vec::with_capacity(index_len + entries.iter().map(|(_, svg)| svg.len()).sum::<usize>());
+    archive.extend_from_slice(ARCHIVE_MAGIC);
+    archive.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+
+    let mut offset = index_len as u64;
+    for (name, svg) in entries {
+        let name_len = u16::try_from(name.len())
+            .map_err(|_| CursorError::InvalidArchive(format!("name too long: {}", name)))?;
+        archive.extend_from_slice(&name_len.to_le_bytes());
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(&offset.to_le_bytes());
+        archive.extend_from_slice(&(svg.len() as u64).to_le_bytes());
+        offset += svg.len() as u64;
+    }
+    for (_, svg) in entries {
+        archive.extend_from_slice(svg);
+    }
+
+    std::fs::write(path, archive).map_err(|e| CursorError::Write {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    })
+}
+
+fn parse_archive_index(data: &[u8]) -> Result<HashMap<String, PackEntry>, CursorError> {
+    let truncated = || CursorError::InvalidArchive("truncated index".to_string());
+    let mut cursor = 0usize;
+    let mut take = |len: usize| {
+        let bytes = data.get(cursor..cursor + len).ok_or_else(truncated)?;
+        cursor += len;
+        Ok::<_, CursorError>(bytes)
+    };
+
+    if take(ARCHIVE_MAGIC.len())? != ARCHIVE_MAGIC {
+        return Err(CursorError::InvalidArchive(
+            "not a cursor archive".to_string(),
+        ));
+    }
+    let count = u32::from_le_bytes(take(4)?.try_into().unwrap());
+
+    let mut entries = HashMap::new();
+    for _ in 0..count {
+        let name_len = u16::from_le_bytes(take(2)?.try_into().unwrap()) as usize;
+        let name = std::str::from_utf8(take(name_len)?)
+            .map_err(|_| CursorError::InvalidArchive("entry name isn't UTF-8".to_string()))?
+            .to_string();
+        let offset = u64::from_le_bytes(take(8)?.try_into().unwrap()) as usize;
+        let len = u64::from_le_bytes(take(8)?.try_into().unwrap()) as usize;
+
+        let range = offset..offset.saturating_add(len);
+        if range.end > data.len() {
+            return Err(CursorError::InvalidArchive(format!(
+                "entry {} points past the end of the archive",
+                name
+            )));
+        }
+        entries.insert(name, PackEntry::new(range));
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::detection::CommonCursorType;
+    use std::path::PathBuf;
+
+    fn pack_dir(label: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("cursor-pack-{}-{}", label, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_load_pack_from_files() {
+        let dir = pack_dir("files");
+        let arrow = CommonCursorType::Arrow.svg_bytes();
+        std::fs::write(dir.join("arrow.svg"), arrow).unwrap();
+        std::fs::write(dir.join("broken.svg"), b"<svg").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"not a cursor").unwrap();
+
+        let pack = CursorPack::load(&dir).unwrap();
+        assert_eq!(pack.load_path(), PackLoadPath::Files);
+        assert_eq!(pack.len(), 2);
+        assert_eq!(pack.svg("arrow").unwrap().unwrap(), arrow);
+        // Loading succeeds, the broken SVG only fails once it's used
+        assert!(matches!(
+            pack.svg("broken"),
+            Some(Err(CursorError::InvalidSvg(_)))
+        ));
+        assert!(pack.svg("notes").is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_load_pack_from_archive() {
+        let dir = pack_dir("archive");
+        let arrow = CommonCursorType::Arrow.svg_bytes();
+        let ibeam = CommonCursorType::IBeam.svg_bytes();
+        write_cursor_archive(
+            &[("arrow", arrow), ("ibeam", ibeam)],
+            &dir.join(CURSOR_ARCHIVE_FILE),
+        )
+        .unwrap();
+        // Ignored in favor of the archive
+        std::fs::write(dir.join("stray.svg"), arrow).unwrap();
+
+        let pack = CursorPack::load(&dir).unwrap();
+        assert_eq!(pack.load_path(), PackLoadPath::Archive);
+        assert_eq!(pack.len(), 2);
+        assert_eq!(pack.svg("arrow").unwrap().unwrap(), arrow);
+        assert_eq!(pack.svg("ibeam").unwrap().unwrap(), ibeam);
+        assert_eq!(
+            pack.meta("ibeam").unwrap().map(|meta| meta.height),
+            Ok(24.0)
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_reject_corrupt_archive() {
+        let dir = pack_dir("corrupt");
+        let archive = dir.join(CURSOR_ARCHIVE_FILE);
+        write_cursor_archive(&[("arrow", CommonCursorType::Arrow.svg_bytes())], &archive).unwrap();
+        let bytes = std::fs::read(&archive).unwrap();
+        std::fs::write(&archive, &bytes[..bytes.len() - 10]).unwrap();
+
+        assert!(matches!(
+            CursorPack::load(&dir),
+            Err(CursorError::InvalidArchive(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,461 @@
+use crate::cursor::assets::CursorAssetSource;
+use crate::cursor::cache::CursorCache;
+use crate::cursor::detection::{ChannelOrder, CommonCursorType, CursorAnalysis, TypeMapper};
+use crate::cursor::error::CursorError;
+use crate::cursor::geometry::{Point, Size};
+use crate::cursor::rendering::{
+    blend_layers, blend_onto, overlaps_frame, scaled_cursor_size, CompositeResult, ScaleLimits,
+};
+use crate::cursor::theme::{apply_theme, CursorTheme};
+use image::RgbaImage;
+
+/// Rasters a [`CursorRenderer`] keeps unless told otherwise, enough for a
+/// recording switching between a handful of cursors
+pub const DEFAULT_RENDERER_CACHE_CAPACITY: usize = 16;
+
+/// Configures a [`CursorRenderer`], see [`CursorRenderer::builder`]
+pub struct CursorRendererBuilder {
+    source: Option<Box<dyn CursorAssetSource + Send + Sync>>,
+    theme: Option<CursorTheme>,
+    scale: f32,
+    scale_limits: ScaleLimits,
+    type_mapper: Option<TypeMapper>,
+    match_dimming: bool,
+    crossfade_secs: f64,
+    cache_capacity: usize,
+    channel_order: ChannelOrder,
+}
+
+impl Default for CursorRendererBuilder {
+    fn default() -> Self {
+        Self {
+            source: None,
+            theme: None,
+            scale: 1.0,
+            scale_limits: ScaleLimits::default(),
+            type_mapper: None,
+            match_dimming: false,
+            crossfade_secs: 0.0,
+            cache_capacity: DEFAULT_RENDERER_CACHE_CAPACITY,
+            channel_order: ChannelOrder::default(),
+        }
+    }
+}
+
+impl CursorRendererBuilder {
+    /// Read cursor SVGs from `source`, e.g. a [`crate::cursor::assets::ChainedSource`]
+    /// of a cursor pack over the bundled SVGs. The bundled cursors otherwise
+    pub fn source(mut self, source: impl CursorAssetSource + Send + Sync + 'static) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    /// Draw cursors light or dark, as they're drawn otherwise
+    pub fn theme(mut self, theme: CursorTheme) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    /// Draw cursors at [`crate::cursor::rendering::CURSOR_BASE_SIZE`] times
+    /// `scale`, 1.0 otherwise
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Clamp the scale to `scale_limits` rather than the default ones
+    pub fn scale_limits(mut self, scale_limits: ScaleLimits) -> Self {
+        self.scale_limits = scale_limits;
+        self
+    }
+
+    /// Draw every detected type as `type_mapper` maps it, e.g. with
+    /// [`CommonCursorType::canonical_resize`]
+    pub fn type_mapper(mut self, type_mapper: TypeMapper) -> Self {
+        self.type_mapper = Some(type_mapper);
+        self
+    }
+
+    /// Draw cursors the capture shows translucent, see
+    /// [`CursorAnalysis::dimmed`], at the opacity they were captured with
+    pub fn match_dimming(mut self, enabled: bool) -> Self {
+        self.match_dimming = enabled;
+        self
+    }
+
+    /// Crossfade from the previous cursor over `secs` seconds whenever the
+    /// type changes, rather than switching at once
+    pub fn crossfade(mut self, secs: f64) -> Self {
+        self.crossfade_secs = secs;
+        self
+    }
+
+    /// Keep at most `capacity` rasters around, at least one
+    pub fn cache_capacity(mut self, capacity: usize) -> Self {
+        self.cache_capacity = capacity;
+        self
+    }
+
+    /// Draw onto frames whose pixels are in `order`, e.g. BGRA captures, which
+    /// the theme's auto-pick reads and the cursor's colors are swapped to.
+    /// RGBA otherwise
+    pub fn channel_order(mut self, order: ChannelOrder) -> Self {
+        self.channel_order = order;
+        self
+    }
+
+    /// Fails if the scale isn't positive and finite, see [`ScaleLimits::clamp`]
+    pub fn build(self) -> Result<CursorRenderer, CursorError> {
+        Ok(CursorRenderer {
+            scale: self.scale_limits.clamp(self.scale)?,
+            source: self.source,
+            theme: self.theme,
+            type_mapper: self.type_mapper,
+            match_dimming: self.match_dimming,
+            // Anything but a positive duration means no crossfade
+            crossfade_secs: self.crossfade_secs.max(0.0),
+            channel_order: self.channel_order,
+            cache: CursorCache::new(self.cache_capacity),
+            current: None,
+            transition: None,
+        })
+    }
+}
+
+/// Draws analyzed cursors onto frame after frame with one fixed set of
+/// options: asset source, theme, scale, type mapper and effects.
+///
+/// Keeps what it rasterizes in a [`CursorCache`], so a cursor that stays the
+/// same is only rasterized once, and remembers the type it drew last to
+/// crossfade into the next one. Frames are expected in playback order; a
+/// frame earlier than the one before, e.g. after seeking, just ends any
+/// crossfade in progress.
+pub struct CursorRenderer {
+    source: Option<Box<dyn CursorAssetSource + Send + Sync>>,
+    theme: Option<CursorTheme>,
+    /// Already clamped to the builder's limits
+    scale: f32,
+    type_mapper: Option<TypeMapper>,
+    match_dimming: bool,
+    crossfade_secs: f64,
+    /// Order of the frames' pixels; rasters are cached RGBA either way
+    channel_order: ChannelOrder,
+    cache: CursorCache,
+    /// Type drawn last
+    current: Option<CommonCursorType>,
+    /// Type a crossfade into `current` started from, and when
+    transition: Option<(CommonCursorType, f64)>,
+}
+
+impl CursorRenderer {
+    pub fn builder() -> CursorRendererBuilder {
+        CursorRendererBuilder::default()
+    }
+
+    /// The rasters drawn so far, e.g. to check how often they're reused
+    pub fn cache(&self) -> &CursorCache {
+        &self.cache
+    }
+
+    /// Draw the cursor `analysis` describes with its hotspot on `position`,
+    /// for the frame `t` seconds into the recording.
+    ///
+    /// Undetected and [`CommonCursorType::Blank`] cursors draw nothing, and
+    /// the next cursor after them appears without a crossfade. Like
+    /// [`crate::cursor::rendering::render_cursor_onto_frame`], reports the
+    /// clamped scale and whether anything landed on the frame.
+    pub fn draw(
+        &mut self,
+        frame: &mut RgbaImage,
+        analysis: &CursorAnalysis,
+        position: impl Into<Point>,
+        t: f64,
+    ) -> Result<CompositeResult, CursorError> {
+        let not_drawn = CompositeResult {
+            scale: self.scale,
+            drawn: false,
+        };
+        let cursor_type = analysis
+            .cursor_type
+            .map(|detected| self.type_mapper.map_or(detected, |map| map(detected)))
+            .filter(|&cursor_type| cursor_type != CommonCursorType::Blank);
+        let Some(cursor_type) = cursor_type else {
+            self.current = None;
+            self.transition = None;
+            return Ok(not_drawn);
+        };
+
+        let progress = self.advance(cursor_type, t);
+        let opacity = if self.match_dimming && analysis.dimmed {
+            analysis.opacity.clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+
+        let size = scaled_cursor_size(self.scale);
+        let position = position.into();
+        let origin_of = |cursor_type: CommonCursorType| {
+            position - Size::square(size).scale_hotspot(cursor_type.hotspot())
+        };
+        let mut layers = vec![(cursor_type, origin_of(cursor_type), opacity)];
+        if let (Some((from, _)), Some(progress)) = (self.transition, progress) {
+            layers[0].2 *= progress;
+            layers.insert(0, (from, origin_of(from), opacity * (1.0 - progress)));
+        }
+
+        // Off-frame cursors aren't rasterized at all
+        if !layers
+            .iter()
+            .any(|&(_, origin, _)| overlaps_frame(frame, origin, Size::square(size)))
+        {
+            return Ok(not_drawn);
+        }
+
+        let theme = self.theme.map(|theme| {
+            theme.resolve_with(frame, origin_of(cursor_type), size, self.channel_order)
+        });
+        if let ([(cursor_type, origin, opacity)], None, ChannelOrder::Rgba) =
+            (layers.as_slice(), theme, self.channel_order)
+        {
+            if *opacity >= 1.0 {
+                let cursor = self.raster(*cursor_type, size)?;
+                blend_onto(frame, cursor, *origin, None);
+                return Ok(CompositeResult {
+                    scale: self.scale,
+                    drawn: true,
+                });
+            }
+        }
+
+        let mut images = Vec::with_capacity(layers.len());
+        for &(cursor_type, _, _) in &layers {
+            let mut image = self.raster(cursor_type, size)?.clone();
+            if let Some(theme) = theme {
+                apply_theme(&mut image, theme);
+            }
+            images.push(image);
+        }
+        let weighted: Vec<_> = images
+            .iter()
+            .zip(&layers)
+            .map(|(image, &(_, origin, weight))| (image, origin, weight))
+            .collect();
+        let (mut blended, origin) = blend_layers(&weighted);
+        self.channel_order.reorder(&mut blended);
+        blend_onto(frame, &blended, origin, None);
+
+        Ok(CompositeResult {
+            scale: self.scale,
+            drawn: true,
+        })
+    }
+
+    /// Note that `cursor_type` is drawn at `t`, starting or ending a
+    /// crossfade. Returns how far the crossfade into it has got, in 0..1,
+    /// `None` when there's none in progress
+    fn advance(&mut self, cursor_type: CommonCursorType, t: f64) -> Option<f32> {
+        if self.current != Some(cursor_type) {
+            self.transition = self
+                .current
+                .filter(|_| self.crossfade_secs > 0.0)
+                .map(|from| (from, t));
+            self.current = Some(cursor_type);
+        }
+
+        let (_, start) = self.transition?;
+        let progress = (t - start) / self.crossfade_secs;
+        if !(0.0..1.0).contains(&progress) {
+            self.transition = None;
+            return None;
+        }
+        Some(progress as f32)
+    }
+
+    /// A type rasterized at `size` pixels, from the cache if it's been drawn
+    /// at that size before
+    fn raster(
+        &mut self,
+        cursor_type: CommonCursorType,
+        size: u32,
+    ) -> Result<&RgbaImage, CursorError> {
+        // The scale is already applied to `size`, and may be beyond the
+        // cache's own limits
+        match &self.source {
+            Some(source) => self
+                .cache
+                .get_or_render_from(source.as_ref(), cursor_type, size, 1.0),
+            None => self.cache.get_or_render(cursor_type, size, 1.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::rendering::{render_cursor_crossfade, render_cursor_onto_frame};
+
+    fn analysis(cursor_type: Option<CommonCursorType>) -> CursorAnalysis {
+        CursorAnalysis {
+            cursor_type,
+            score: 1.0,
+            bounds: None,
+            hotspot: None,
+            opacity: 1.0,
+            dimmed: false,
+            is_clipped: false,
+            has_attached_glyph: false,
+            is_stacked: false,
+            magnified: false,
+            busy: false,
+        }
+    }
+
+    fn frame() -> RgbaImage {
+        RgbaImage::from_pixel(64, 64, image::Rgba([40, 90, 160, 255]))
+    }
+
+    #[test]
+    fn test_renderer_matches_single_draw_and_reuses_rasters() {
+        let mut renderer = CursorRenderer::builder().scale(1.5).build().unwrap();
+        let arrow = analysis(Some(CommonCursorType::Arrow));
+
+        let mut drawn = frame();
+        for t in [0.0, 1.0 / 60.0] {
+            drawn = frame();
+            let result = renderer.draw(&mut drawn, &arrow, (20, 20), t).unwrap();
+            assert_eq!(
+                result,
+                CompositeResult {
+                    scale: 1.5,
+                    drawn: true
+                }
+            );
+        }
+        assert_eq!((renderer.cache().misses(), renderer.cache().hits()), (1, 1));
+
+        let mut expected = frame();
+        render_cursor_onto_frame(
+            &mut expected,
+            &CommonCursorType::Arrow.into(),
+            (20, 20),
+            1.5,
+            None,
+        )
+        .unwrap();
+        assert_eq!(drawn, expected);
+    }
+
+    #[test]
+    fn test_renderer_crossfades_type_changes() {
+        let mut renderer = CursorRenderer::builder().crossfade(0.25).build().unwrap();
+        let mut drawn = frame();
+        renderer
+            .draw(
+                &mut drawn,
+                &analysis(Some(CommonCursorType::Arrow)),
+                (30, 30),
+                0.0,
+            )
+            .unwrap();
+
+        // The crossfade starts with the first frame of the new type
+        let ibeam = analysis(Some(CommonCursorType::IBeam));
+        let mut drawn = frame();
+        renderer.draw(&mut drawn, &ibeam, (30, 30), 0.25).unwrap();
+        let mut expected = frame();
+        let arrow = CommonCursorType::Arrow.into();
+        render_cursor_onto_frame(&mut expected, &arrow, (30, 30), 1.0, None).unwrap();
+        assert_eq!(drawn, expected);
+
+        let mut drawn = frame();
+        renderer.draw(&mut drawn, &ibeam, (30, 30), 0.375).unwrap();
+        let mut expected = frame();
+        render_cursor_crossfade(
+            &mut expected,
+            &CommonCursorType::Arrow,
+            &CommonCursorType::IBeam,
+            (30, 30),
+            0.5,
+            1.0,
+        )
+        .unwrap();
+        assert_eq!(drawn, expected);
+
+        // And past it
+        let mut drawn = frame();
+        renderer.draw(&mut drawn, &ibeam, (30, 30), 0.5).unwrap();
+        let mut expected = frame();
+        render_cursor_onto_frame(
+            &mut expected,
+            &CommonCursorType::IBeam.into(),
+            (30, 30),
+            1.0,
+            None,
+        )
+        .unwrap();
+        assert_eq!(drawn, expected);
+    }
+
+    #[test]
+    fn test_renderer_draws_onto_bgra_frames() {
+        let draw = |order: ChannelOrder| {
+            let mut renderer = CursorRenderer::builder()
+                .theme(CursorTheme::Auto)
+                .channel_order(order)
+                .build()
+                .unwrap();
+            let mut drawn = frame();
+            order.reorder(&mut drawn);
+            let cursor = analysis(Some(CommonCursorType::PointingHand));
+            renderer.draw(&mut drawn, &cursor, (30, 30), 0.0).unwrap();
+            order.reorder(&mut drawn);
+            drawn
+        };
+
+        // The hand is skin-toned, so an unswapped raster would come out wrong
+        let expected = draw(ChannelOrder::Rgba);
+        assert_ne!(expected, frame());
+        assert_eq!(draw(ChannelOrder::Bgra), expected);
+    }
+
+    #[test]
+    fn test_renderer_effects_and_mapping() {
+        let mut renderer = CursorRenderer::builder()
+            .match_dimming(true)
+            .type_mapper(CommonCursorType::canonical_resize)
+            .build()
+            .unwrap();
+
+        // Nothing to draw for undetected or blank cursors
+        for cursor_type in [None, Some(CommonCursorType::Blank)] {
+            let mut drawn = frame();
+            let result = renderer.draw(&mut drawn, &analysis(cursor_type), (30, 30), 0.0);
+            assert!(!result.unwrap().drawn);
+            assert_eq!(drawn, frame());
+        }
+
+        let mut drawn = frame();
+        let resize = analysis(Some(CommonCursorType::ResizeNWSE));
+        renderer.draw(&mut drawn, &resize, (30, 30), 0.0).unwrap();
+        let mut expected = frame();
+        let resize_ew = CommonCursorType::ResizeEW.into();
+        render_cursor_onto_frame(&mut expected, &resize_ew, (30, 30), 1.0, None).unwrap();
+        assert_eq!(drawn, expected);
+
+        // A dimmed cursor shows through onto a transparent frame at its opacity
+        let dimmed = CursorAnalysis {
+            opacity: 0.5,
+            dimmed: true,
+            ..resize
+        };
+        let mut drawn = RgbaImage::new(64, 64);
+        renderer.draw(&mut drawn, &dimmed, (30, 30), 0.0).unwrap();
+        let max_alpha = drawn.pixels().map(|p| p[3]).max().unwrap();
+        assert!((126..=129).contains(&max_alpha), "{}", max_alpha);
+
+        assert!(matches!(
+            CursorRenderer::builder().scale(f32::NAN).build(),
+            Err(CursorError::InvalidScale(_))
+        ));
+    }
+}
@@ -0,0 +1,2047 @@
+use crate::cursor::assets::CursorAssetSource;
+#[cfg(feature = "svg")]
+use crate::cursor::assets::EmbeddedSource;
+use crate::cursor::detection::{strip_tooltips, CommonCursorType};
+use crate::cursor::error::CursorError;
+use crate::cursor::features::{normalize_alpha, opaque_bounds};
+use crate::cursor::geometry::{Point, Size};
+#[cfg(feature = "svg")]
+use crate::cursor::loading::load_cursor_svg;
+use crate::cursor::loading::load_cursor_svg_from;
+use crate::cursor::theme::{apply_theme, CursorTheme};
+use crate::cursor::track::CursorTrack;
+use image::{GrayImage, RgbaImage};
+use std::borrow::Cow;
+
+/// Size in pixels of a cursor composited at scale 1.0
+pub const CURSOR_BASE_SIZE: u32 = 32;
+
+/// Sizes in pixels of the bundled 1x and 2x PNG fallbacks, see
+/// [`rasterize_cursor_fallback`]
+pub const FALLBACK_PNG_SIZES: [u32; 2] = [CURSOR_BASE_SIZE, CURSOR_BASE_SIZE * 2];
+
+/// Occlusion mask values above this hide the cursor at that pixel
+pub const OCCLUSION_THRESHOLD: u8 = 128;
+
+/// Smallest scale a cursor is composited at by default, see [`ScaleLimits`]
+pub const DEFAULT_MIN_CURSOR_SCALE: f32 = 0.25;
+
+/// Largest scale a cursor is composited at by default, see [`ScaleLimits`]
+pub const DEFAULT_MAX_CURSOR_SCALE: f32 = 4.0;
+
+/// Longest side, in pixels, of a cursor's opaque part at 1x. The standard
+/// arrow of every platform is about this tall
+pub const LOGICAL_CURSOR_SIZE: u32 = 16;
+
+/// Share of a spotlight's radius, at its rim, over which the dimming fades
+/// in, see [`apply_cursor_spotlight`]
+pub const SPOTLIGHT_SOFTNESS: f32 = 0.25;
+
+/// Step [`estimate_scale`] snaps to, the granularity display scaling comes in
+const DISPLAY_SCALE_STEP: f32 = 0.25;
+
+/// Range cursor scales are clamped to before rasterizing or compositing.
+///
+/// An accessibility size multiplier stacked on a HiDPI scale can ask for a
+/// cursor that dwarfs the frame, and since the bitmap grows with the square of
+/// the scale a degenerate value could exhaust memory. Scales outside the range
+/// are drawn at the nearest limit instead, and the compositing functions
+/// return the scale they actually used so callers can tell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScaleLimits {
+    pub min: f32,
+    /// Takes precedence over `min` if the two overlap
+    pub max: f32,
+}
+
+impl Default for ScaleLimits {
+    fn default() -> Self {
+        Self {
+            min: DEFAULT_MIN_CURSOR_SCALE,
+            max: DEFAULT_MAX_CURSOR_SCALE,
+        }
+    }
+}
+
+impl ScaleLimits {
+    /// Clamp `scale` into the limits. Scales that aren't positive and finite
+    /// are rejected rather than clamped, since there's no sensible size for them
+    pub fn clamp(&self, scale: f32) -> Result<f32, CursorError> {
+        if scale <= 0.0 || !scale.is_finite() {
+            return Err(CursorError::InvalidScale(scale));
+        }
+
+        Ok(scale.max(self.min).min(self.max))
+    }
+}
+
+/// Guess the display scale a cursor was captured at from the `width` and
+/// `height` of its opaque bounding box, e.g. about 2.0 for a 32px tall arrow.
+///
+/// This is a heuristic: the longest side is compared to
+/// [`LOGICAL_CURSOR_SIZE`] and snapped to the nearest quarter, so a cursor
+/// drawn unusually large or small at 1x reads as a different scale. The result
+/// is kept between 1.0 and [`DEFAULT_MAX_CURSOR_SCALE`]; an empty box is 1.0.
+pub fn estimate_scale(width: u32, height: u32) -> f32 {
+    let longest = width.max(height) as f32;
+    let scale =
+        (longest / LOGICAL_CURSOR_SIZE as f32 / DISPLAY_SCALE_STEP).round() * DISPLAY_SCALE_STEP;
+    scale.clamp(1.0, DEFAULT_MAX_CURSOR_SCALE)
+}
+
+/// What to draw for a cursor: the bundled SVG of a recognized type, or the
+/// captured bitmap itself when the type couldn't be determined
+#[derive(Debug, Clone, PartialEq)]
+pub enum CursorRenderSource {
+    KnownType(CommonCursorType),
+    /// Captured bitmap and its hotspot, relative to the bitmap size like
+    /// [`CommonCursorType::hotspot`]
+    RawBitmap(RgbaImage, (f32, f32)),
+}
+
+impl From<CommonCursorType> for CursorRenderSource {
+    fn from(cursor_type: CommonCursorType) -> Self {
+        CursorRenderSource::KnownType(cursor_type)
+    }
+}
+
+impl CursorRenderSource {
+    /// This source with any tooltip drawn into a captured bitmap cleared, see
+    /// [`strip_tooltips`], so only the cursor is composited. The cursor is
+    /// the part nearest the hotspot, which stays where it was. Bundled types
+    /// have no tooltips and are returned as they are
+    pub fn without_tooltips(self) -> Self {
+        let CursorRenderSource::RawBitmap(bitmap, hotspot) = self else {
+            return self;
+        };
+        let (width, height) = bitmap.dimensions();
+        let anchor = Size::new(width, height).scale_hotspot(hotspot);
+        let anchor = (
+            (anchor.x.max(0) as u32).min(width.saturating_sub(1)),
+            (anchor.y.max(0) as u32).min(height.saturating_sub(1)),
+        );
+
+        match strip_tooltips(bitmap.as_raw(), width, height, anchor).image_data {
+            Cow::Borrowed(_) => CursorRenderSource::RawBitmap(bitmap, hotspot),
+            Cow::Owned(stripped) => CursorRenderSource::RawBitmap(
+                RgbaImage::from_raw(width, height, stripped)
+                    .expect("stripped bitmap keeps the original size"),
+                hotspot,
+            ),
+        }
+    }
+}
+
+/// Outcome of compositing a cursor onto a frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompositeResult {
+    /// Scale the cursor was drawn at, after clamping to the [`ScaleLimits`]
+    pub scale: f32,
+    /// Whether any part of the cursor landed on the frame. A cursor entirely
+    /// outside it isn't rasterized at all
+    pub drawn: bool,
+}
+
+/// Halo [`render_cursor_onto_frame_filtered`] can draw around a cursor's
+/// silhouette, so it stays visible on busy backgrounds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutlineStyle {
+    /// How far the halo reaches past the cursor's edges, in frame pixels
+    pub width: u32,
+    /// RGB the halo is filled with
+    pub color: [u8; 3],
+}
+
+/// What the position passed to [`render_cursor_onto_frame_filtered`] refers
+/// to, so pipelines that track the cursor's corner and ones that track its
+/// hotspot can both composite without converting first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PositionRef {
+    /// Where the cursor's hotspot lands, as OS cursor positions are reported
+    #[default]
+    Hotspot,
+    /// Where the cursor image's top-left corner goes
+    TopLeft,
+}
+
+impl PositionRef {
+    /// Top-left corner of a `size` cursor with `hotspot`, relative to its size
+    /// like [`CommonCursorType::hotspot`], placed at `position` as this
+    /// convention reads it
+    pub fn origin(
+        self,
+        position: impl Into<Point>,
+        size: impl Into<Size>,
+        hotspot: (f32, f32),
+    ) -> Point {
+        match self {
+            PositionRef::Hotspot => position.into() - size.into().scale_hotspot(hotspot),
+            PositionRef::TopLeft => position.into(),
+        }
+    }
+}
+
+/// Filter used when a captured cursor bitmap has to be resized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResampleFilter {
+    /// Keeps hard pixel edges, for pixel-art cursors
+    Nearest,
+    #[default]
+    Triangle,
+    CatmullRom,
+    Gaussian,
+    /// Sharpest for smooth, anti-aliased cursors
+    Lanczos3,
+}
+
+impl From<ResampleFilter> for image::imageops::FilterType {
+    fn from(filter: ResampleFilter) -> Self {
+        match filter {
+            ResampleFilter::Nearest => image::imageops::FilterType::Nearest,
+            ResampleFilter::Triangle => image::imageops::FilterType::Triangle,
+            ResampleFilter::CatmullRom => image::imageops::FilterType::CatmullRom,
+            ResampleFilter::Gaussian => image::imageops::FilterType::Gaussian,
+            ResampleFilter::Lanczos3 => image::imageops::FilterType::Lanczos3,
+        }
+    }
+}
+
+#[cfg(feature = "svg")]
+pub(crate) fn parse_svg(svg_content: &[u8]) -> Result<resvg::usvg::Tree, CursorError> {
+    let svg_data = std::str::from_utf8(svg_content)
+        .map_err(|e| CursorError::InvalidSvg(format!("not UTF-8: {}", e)))?;
+
+    resvg::usvg::Tree::from_str(svg_data, &resvg::usvg::Options::default())
+        .map_err(|e| CursorError::InvalidSvg(e.to_string()))
+}
+
+/// Transform that fits the SVG into a `size` x `size` square, centered and
+/// keeping its aspect ratio
+#[cfg(feature = "svg")]
+fn fit_transform(tree: &resvg::usvg::Tree, size: u32) -> tiny_skia::Transform {
+    let svg_size = tree.size();
+    let scale = (size as f32 / svg_size.width()).min(size as f32 / svg_size.height());
+
+    tiny_skia::Transform::from_scale(scale, scale).post_translate(
+        (size as f32 - svg_size.width() * scale) / 2.0,
+        (size as f32 - svg_size.height() * scale) / 2.0,
+    )
+}
+
+#[cfg(feature = "svg")]
+fn pixmap_to_rgba(pixmap: &tiny_skia::Pixmap) -> RgbaImage {
+    let data = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+
+    RgbaImage::from_raw(pixmap.width(), pixmap.height(), data)
+        .expect("pixmap dimensions match its pixel data")
+}
+
+/// Rasterize SVG content into a square RGBA image of `size` pixels
+#[cfg(feature = "svg")]
+pub fn rasterize_svg(svg_content: &[u8], size: u32) -> Result<RgbaImage, CursorError> {
+    rasterize_tree(&parse_svg(svg_content)?, size)
+}
+
+/// Rasterize an already parsed SVG like [`rasterize_svg`]
+#[cfg(feature = "svg")]
+pub(crate) fn rasterize_tree(
+    tree: &resvg::usvg::Tree,
+    size: u32,
+) -> Result<RgbaImage, CursorError> {
+    let mut pixmap = tiny_skia::Pixmap::new(size, size).ok_or(CursorError::InvalidSize {
+        width: size,
+        height: size,
+    })?;
+
+    resvg::render(tree, fit_transform(tree, size), &mut pixmap.as_mut());
+
+    Ok(pixmap_to_rgba(&pixmap))
+}
+
+/// Without the `svg` feature there's no renderer for SVG content, so this
+/// always fails with [`CursorError::SvgUnavailable`]
+#[cfg(not(feature = "svg"))]
+pub fn rasterize_svg(_svg_content: &[u8], _size: u32) -> Result<RgbaImage, CursorError> {
+    Err(CursorError::SvgUnavailable)
+}
+
+/// Rasterize the bundled SVG for a cursor type into a square RGBA image.
+///
+/// Without the `svg` feature the bundled PNG closest in size is scaled
+/// instead, see [`rasterize_cursor_fallback`]
+pub fn rasterize_cursor(
+    cursor_type: &CommonCursorType,
+    size: u32,
+) -> Result<RgbaImage, CursorError> {
+    #[cfg(feature = "svg")]
+    return rasterize_cursor_from(&EmbeddedSource, cursor_type, size);
+
+    #[cfg(not(feature = "svg"))]
+    return rasterize_cursor_fallback(cursor_type, size);
+}
+
+/// Rasterize a cursor type from its bundled PNG fallbacks, for builds without
+/// the `svg` feature.
+///
+/// Picks whichever of the 1x and 2x PNGs in [`FALLBACK_PNG_SIZES`] is closest
+/// to `size` by ratio, so a 2x display gets the 2x PNG, and resamples it to
+/// `size` if they differ. The PNGs are renders of the bundled SVGs, see the
+/// README next to them.
+pub fn rasterize_cursor_fallback(
+    cursor_type: &CommonCursorType,
+    size: u32,
+) -> Result<RgbaImage, CursorError> {
+    if size == 0 {
+        return Err(CursorError::InvalidSize {
+            width: size,
+            height: size,
+        });
+    }
+
+    let distance = |png_size: u32| (size as f32 / png_size as f32).ln().abs();
+    let [one_x, two_x] = cursor_type.fallback_pngs();
+    let png = if distance(FALLBACK_PNG_SIZES[1]) < distance(FALLBACK_PNG_SIZES[0]) {
+        two_x
+    } else {
+        one_x
+    };
+
+    let image = image::load_from_memory_with_format(png, image::ImageFormat::Png)
+        .expect("bundled fallback PNGs decode")
+        .into_rgba8();
+    if image.width() == size {
+        return Ok(image);
+    }
+    Ok(image::imageops::resize(
+        &image,
+        size,
+        size,
+        image::imageops::FilterType::Lanczos3,
+    ))
+}
+
+/// Like [`rasterize_cursor`], with the SVG read from `source`. Always needs
+/// the `svg` feature, a source's SVGs have no PNG fallbacks
+pub fn rasterize_cursor_from(
+    source: &dyn CursorAssetSource,
+    cursor_type: &CommonCursorType,
+    size: u32,
+) -> Result<RgbaImage, CursorError> {
+    let svg_content =
+        load_cursor_svg_from(source, cursor_type).ok_or(CursorError::MissingSvg(*cursor_type))?;
+
+    rasterize_svg(&svg_content, size)
+}
+
+/// Solid colors [`rasterize_cursor_recolored`] paints a cursor's SVG with,
+/// e.g. a brand tint for the fill that keeps the outline as it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Recolor {
+    /// RGB every filled shape is painted with
+    pub fill: [u8; 3],
+    /// RGB every stroke is painted with, `None` to keep the strokes' colors
+    pub stroke: Option<[u8; 3]>,
+}
+
+/// A cursor rasterized by [`rasterize_cursor_recolored`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoloredCursor {
+    pub image: RgbaImage,
+    /// Whether `image` has the requested colors. `false` for an SVG that
+    /// paints with gradients or patterns, which is drawn with its own colors
+    /// rather than flattened to a single one, and when no recolor was asked for
+    pub recolored: bool,
+}
+
+/// Like [`rasterize_cursor`], with the solid fill and optionally stroke
+/// paints of the bundled SVG replaced by `recolor`'s before rendering.
+/// Blends, opacities and drop shadows stay as drawn. Recoloring needs the
+/// `svg` feature, without it only `None` succeeds
+pub fn rasterize_cursor_recolored(
+    cursor_type: &CommonCursorType,
+    size: u32,
+    recolor: Option<Recolor>,
+) -> Result<RecoloredCursor, CursorError> {
+    let Some(recolor) = recolor else {
+        return Ok(RecoloredCursor {
+            image: rasterize_cursor(cursor_type, size)?,
+            recolored: false,
+        });
+    };
+    rasterize_svg_recolored(cursor_type, size, recolor)
+}
+
+#[cfg(not(feature = "svg"))]
+fn rasterize_svg_recolored(
+    _cursor_type: &CommonCursorType,
+    _size: u32,
+    _recolor: Recolor,
+) -> Result<RecoloredCursor, CursorError> {
+    Err(CursorError::SvgUnavailable)
+}
+
+/// The bundled SVG with `recolor` applied, by rewriting the paints of its
+/// parsed tree: the tree itself is read-only, so it's written back out as
+/// normalized SVG, where every solid paint is a plain `#rrggbb` attribute,
+/// and parsed again once those are replaced
+#[cfg(feature = "svg")]
+fn rasterize_svg_recolored(
+    cursor_type: &CommonCursorType,
+    size: u32,
+    recolor: Recolor,
+) -> Result<RecoloredCursor, CursorError> {
+    let svg_content = load_cursor_svg(cursor_type).ok_or(CursorError::MissingSvg(*cursor_type))?;
+    let tree = parse_svg(&svg_content)?;
+    if !has_only_solid_paints(tree.root()) {
+        return Ok(RecoloredCursor {
+            image: rasterize_tree(&tree, size)?,
+            recolored: false,
+        });
+    }
+
+    let hex = |[r, g, b]: [u8; 3]| format!("#{:02x}{:02x}{:02x}", r, g, b);
+    let mut normalized = tree.to_string(&resvg::usvg::WriteOptions::default());
+    normalized = replace_solid_paint(&normalized, "fill", &hex(recolor.fill));
+    if let Some(stroke) = recolor.stroke {
+        normalized = replace_solid_paint(&normalized, "stroke", &hex(stroke));
+    }
+
+    Ok(RecoloredCursor {
+        image: rasterize_svg(normalized.as_bytes(), size)?,
+        recolored: true,
+    })
+}
+
+/// Whether every fill and stroke under `group` is a plain color
+#[cfg(feature = "svg")]
+fn has_only_solid_paints(group: &resvg::usvg::Group) -> bool {
+    use resvg::usvg::{Node, Paint};
+
+    let solid = |paint: &Paint| matches!(paint, Paint::Color(_));
+    group.children().iter().all(|node| match node {
+        Node::Group(group) => has_only_solid_paints(group),
+        Node::Path(path) => {
+            path.fill().is_none_or(|fill| solid(fill.paint()))
+                && path.stroke().is_none_or(|stroke| solid(stroke.paint()))
+        }
+        Node::Text(text) => has_only_solid_paints(text.flattened()),
+        Node::Image(_) => true,
+    })
+}
+
+/// Normalized `svg` with the value of every `attribute="#rrggbb"` set to
+/// `color`, another `#rrggbb`. The leading space keeps attributes that only
+/// end in the name from matching
+#[cfg(feature = "svg")]
+fn replace_solid_paint(svg: &str, attribute: &str, color: &str) -> String {
+    let pattern = format!(" {}=\"", attribute);
+    let mut replaced = String::with_capacity(svg.len());
+    let mut rest = svg;
+    while let Some(start) = rest.find(&pattern) {
+        let value_start = start + pattern.len();
+        let value = rest[value_start..].get(.."#rrggbb".len());
+        replaced.push_str(&rest[..value_start]);
+        rest = &rest[value_start..];
+        if value.is_some_and(|value| value.starts_with('#')) {
+            replaced.push_str(color);
+            rest = &rest["#rrggbb".len()..];
+        }
+    }
+    replaced.push_str(rest);
+    replaced
+}
+
+/// Rasterize a cursor rotated clockwise by `angle_degrees` about its hotspot.
+///
+/// The output grows to fit the rotated glyph, so the hotspot moves within the
+/// image; it is returned in pixels alongside the image. Rotating by anything
+/// but whole turns needs the `svg` feature.
+pub fn rasterize_cursor_rotated(
+    cursor_type: &CommonCursorType,
+    size: u32,
+    angle_degrees: f32,
+) -> Result<(RgbaImage, (f32, f32)), CursorError> {
+    let (hotspot_x, hotspot_y) = cursor_type.hotspot();
+    let hotspot = (hotspot_x * size as f32, hotspot_y * size as f32);
+
+    if angle_degrees.rem_euclid(360.0) == 0.0 {
+        return Ok((rasterize_cursor(cursor_type, size)?, hotspot));
+    }
+
+    rasterize_svg_rotated(cursor_type, size, hotspot, angle_degrees)
+}
+
+#[cfg(not(feature = "svg"))]
+fn rasterize_svg_rotated(
+    _cursor_type: &CommonCursorType,
+    _size: u32,
+    _hotspot: (f32, f32),
+    _angle_degrees: f32,
+) -> Result<(RgbaImage, (f32, f32)), CursorError> {
+    Err(CursorError::SvgUnavailable)
+}
+
+/// The bundled SVG rotated about `hotspot`, in pixels at `size`
+#[cfg(feature = "svg")]
+fn rasterize_svg_rotated(
+    cursor_type: &CommonCursorType,
+    size: u32,
+    hotspot: (f32, f32),
+    angle_degrees: f32,
+) -> Result<(RgbaImage, (f32, f32)), CursorError> {
+    let svg_content = load_cursor_svg(cursor_type).ok_or(CursorError::MissingSvg(*cursor_type))?;
+    let tree = parse_svg(&svg_content)?;
+
+    let rotation = tiny_skia::Transform::from_rotate(angle_degrees);
+
+    // Bounding box of the rotated square, relative to the hotspot
+    let mut corners = [
+        tiny_skia::Point::from_xy(-hotspot.0, -hotspot.1),
+        tiny_skia::Point::from_xy(size as f32 - hotspot.0, -hotspot.1),
+        tiny_skia::Point::from_xy(-hotspot.0, size as f32 - hotspot.1),
+        tiny_skia::Point::from_xy(size as f32 - hotspot.0, size as f32 - hotspot.1),
+    ];
+    rotation.map_points(&mut corners);
+
+    let min_x = corners
+        .iter()
+        .map(|p| p.x)
+        .fold(f32::INFINITY, f32::min)
+        .floor();
+    let min_y = corners
+        .iter()
+        .map(|p| p.y)
+        .fold(f32::INFINITY, f32::min)
+        .floor();
+    let max_x = corners
+        .iter()
+        .map(|p| p.x)
+        .fold(f32::NEG_INFINITY, f32::max)
+        .ceil();
+    let max_y = corners
+        .iter()
+        .map(|p| p.y)
+        .fold(f32::NEG_INFINITY, f32::max)
+        .ceil();
+
+    let (width, height) = ((max_x - min_x) as u32, (max_y - min_y) as u32);
+    let mut pixmap =
+        tiny_skia::Pixmap::new(width, height).ok_or(CursorError::InvalidSize { width, height })?;
+
+    let transform = fit_transform(&tree, size)
+        .post_translate(-hotspot.0, -hotspot.1)
+        .post_concat(rotation)
+        .post_translate(-min_x, -min_y);
+
+    resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+    Ok((pixmap_to_rgba(&pixmap), (-min_x, -min_y)))
+}
+
+/// Composite a cursor onto a frame so that its hotspot lands on `position`.
+/// For a position that's the cursor's top-left corner instead, see
+/// [`PositionRef`].
+///
+/// Known types are drawn at [`CURSOR_BASE_SIZE`] times `scale`, raw bitmaps at
+/// their captured size times `scale`, with `scale` clamped to the default
+/// [`ScaleLimits`]. Returns the scale the cursor was drawn at and whether it
+/// was visible at all; a cursor that moved entirely off the frame is skipped
+/// before rasterizing. Frame pixels where `occlusion_mask` is above
+/// [`OCCLUSION_THRESHOLD`] are left untouched, so the cursor appears behind
+/// whatever the mask covers.
+pub fn render_cursor_onto_frame(
+    frame: &mut RgbaImage,
+    source: &CursorRenderSource,
+    position: impl Into<Point>,
+    scale: f32,
+    occlusion_mask: Option<&GrayImage>,
+) -> Result<CompositeResult, CursorError> {
+    render_cursor_onto_frame_filtered(
+        frame,
+        source,
+        position,
+        PositionRef::Hotspot,
+        scale,
+        ScaleLimits::default(),
+        ResampleFilter::default(),
+        1.0,
+        None,
+        occlusion_mask,
+    )
+}
+
+/// Like [`render_cursor_onto_frame`], clamping `scale` to `scale_limits` and
+/// resizing raw bitmaps with `filter`. Known types are rasterized at the
+/// target size and don't need resampling.
+///
+/// Every pixel's alpha is multiplied by `global_opacity` before blending, for
+/// translucent "ghost" cursors: at 0.5 the cursor's edges are half as opaque
+/// as they'd be at 1.0, and at 0.0 nothing is drawn. It's clamped to 0..=1,
+/// a non-finite one is an error.
+///
+/// `position_ref` says whether `position` is where the hotspot lands or
+/// where the cursor's top-left corner goes; for both, the hotspot is taken
+/// from the source, scaled with the cursor.
+///
+/// With an `outline`, the cursor is drawn over its halo, see
+/// [`outline_cursor`], and the two fade together with `global_opacity`.
+#[allow(clippy::too_many_arguments)]
+pub fn render_cursor_onto_frame_filtered(
+    frame: &mut RgbaImage,
+    source: &CursorRenderSource,
+    position: impl Into<Point>,
+    position_ref: PositionRef,
+    scale: f32,
+    scale_limits: ScaleLimits,
+    filter: ResampleFilter,
+    global_opacity: f32,
+    outline: Option<OutlineStyle>,
+    occlusion_mask: Option<&GrayImage>,
+) -> Result<CompositeResult, CursorError> {
+    let scale = scale_limits.clamp(scale)?;
+    if !global_opacity.is_finite() {
+        return Err(CursorError::InvalidOpacity(global_opacity));
+    }
+    let global_opacity = global_opacity.clamp(0.0, 1.0);
+
+    // Size and hotspot first, so an off-frame cursor is never rasterized
+    let size = match source {
+        CursorRenderSource::KnownType(_) => Size::square(scaled_cursor_size(scale)),
+        CursorRenderSource::RawBitmap(bitmap, _) => {
+            let scaled = |dim: u32| (dim as f32 * scale).round().max(1.0) as u32;
+            Size::new(scaled(bitmap.width()), scaled(bitmap.height()))
+        }
+    };
+    let hotspot = match source {
+        CursorRenderSource::KnownType(cursor_type) => cursor_type.hotspot(),
+        CursorRenderSource::RawBitmap(_, hotspot) => *hotspot,
+    };
+    let origin = position_ref.origin(position, size, hotspot);
+    let halo = outline.map_or(0, |style| style.width);
+    let outlined = Size::new(size.w + halo * 2, size.h + halo * 2);
+    let outlined_origin = origin - Point::new(halo as i32, halo as i32);
+
+    if global_opacity <= 0.0 || !overlaps_frame(frame, outlined_origin, outlined) {
+        return Ok(CompositeResult {
+            scale,
+            drawn: false,
+        });
+    }
+
+    let cursor = match source {
+        CursorRenderSource::KnownType(cursor_type) => {
+            Cow::Owned(rasterize_cursor(cursor_type, size.w)?)
+        }
+        CursorRenderSource::RawBitmap(bitmap, _) => resample_cursor(bitmap, size.w, size.h, filter),
+    };
+    let (cursor, origin) = match outline {
+        Some(style) if halo > 0 => (Cow::Owned(outline_cursor(&cursor, style)), outlined_origin),
+        _ => (cursor, origin),
+    };
+    if global_opacity < 1.0 {
+        let (ghost, origin) = blend_layers(&[(&cursor, origin, global_opacity)]);
+        blend_onto(frame, &ghost, origin, occlusion_mask);
+    } else {
+        blend_onto(frame, &cursor, origin, occlusion_mask);
+    }
+
+    Ok(CompositeResult { scale, drawn: true })
+}
+
+/// Like [`render_cursor_onto_frame`] for a known type, drawn light or dark as
+/// `theme` says. [`CursorTheme::Auto`] is resolved for every call against the
+/// frame under the cursor, see [`CursorTheme::resolve`], so the cursor flips
+/// as it moves between light and dark content.
+pub fn render_cursor_onto_frame_themed(
+    frame: &mut RgbaImage,
+    cursor_type: &CommonCursorType,
+    position: impl Into<Point>,
+    scale: f32,
+    theme: CursorTheme,
+) -> Result<CompositeResult, CursorError> {
+    let scale = ScaleLimits::default().clamp(scale)?;
+    let size = Size::square(scaled_cursor_size(scale));
+    let origin = position.into() - size.scale_hotspot(cursor_type.hotspot());
+    if !overlaps_frame(frame, origin, size) {
+        return Ok(CompositeResult {
+            scale,
+            drawn: false,
+        });
+    }
+
+    let theme = theme.resolve(frame, origin, size.w);
+    let mut cursor = rasterize_cursor(cursor_type, size.w)?;
+    apply_theme(&mut cursor, theme);
+    blend_onto(frame, &cursor, origin, None);
+
+    Ok(CompositeResult { scale, drawn: true })
+}
+
+/// Detect a captured cursor and composite it onto `frame` in one go.
+///
+/// A recognized cursor is drawn from its bundled SVG, aligned on the type's
+/// hotspot; anything else is drawn as the captured bitmap with `hotspot`, the
+/// capture's own hotspot relative to its size like [`CommonCursorType::hotspot`],
+/// on `position`. Returns the detected type, `None` both for unrecognized
+/// cursors and when nothing could be drawn because `cursor_data` or `scale` is
+/// invalid. `scale` is clamped to the default [`ScaleLimits`].
+pub fn detect_and_composite(
+    frame: &mut RgbaImage,
+    cursor_data: &[u8],
+    cursor_dims: impl Into<Size>,
+    hotspot: (f32, f32),
+    position: impl Into<Point>,
+    scale: f32,
+) -> Option<CommonCursorType> {
+    let Size {
+        w: width,
+        h: height,
+    } = cursor_dims.into();
+    let position = position.into();
+    let detected = CommonCursorType::detect_from_image(cursor_data, width, height);
+
+    if let Some(cursor_type) = detected {
+        if render_cursor_onto_frame(frame, &cursor_type.into(), position, scale, None).is_ok() {
+            return detected;
+        }
+    }
+
+    // Unknown, or the SVG failed to render: the capture itself is the next best thing
+    let len = (width as usize * height as usize * 4).min(cursor_data.len());
+    let bitmap = RgbaImage::from_raw(width, height, cursor_data[..len].to_vec())?;
+    let source = CursorRenderSource::RawBitmap(bitmap, hotspot);
+    render_cursor_onto_frame(frame, &source, position, scale, None).ok()?;
+
+    detected
+}
+
+/// Draw the cursor of one output frame at time `t`, in seconds, wherever
+/// `track` puts it then.
+///
+/// For output frame rates above the cursor's sample rate, so frames between
+/// two samples show the cursor partway between them rather than repeating the
+/// last sample. The track's normalized position is scaled to the frame's size
+/// and the detected `classifier_result` is drawn there with its hotspot on
+/// it. Like [`render_cursor_onto_frame`], reports the clamped scale and
+/// whether anything was drawn, which it isn't if the track has no position at
+/// `t` or puts the cursor off the frame.
+pub fn render_frame_at(
+    frame: &mut RgbaImage,
+    track: &CursorTrack,
+    classifier_result: CommonCursorType,
+    t: f64,
+    scale: f32,
+) -> Result<CompositeResult, CursorError> {
+    let Some(position) = track.pixel_position_at(t, frame.dimensions()) else {
+        return Ok(CompositeResult {
+            scale: ScaleLimits::default().clamp(scale)?,
+            drawn: false,
+        });
+    };
+
+    render_cursor_onto_frame(frame, &classifier_result.into(), position, scale, None)
+}
+
+/// Draw the part of a cursor that falls on one tile of a frame rendered in
+/// tiles, e.g. on separate threads.
+///
+/// `tile_origin` is where the tile's top-left pixel sits in the frame and
+/// `global_position` where the hotspot goes in frame coordinates. Every tile
+/// rasterizes the same full-size cursor and blends only the pixels over it,
+/// so the tiles put together match a single [`render_cursor_onto_frame`]
+/// with no seams. `drawn` is false for tiles the cursor doesn't reach.
+pub fn render_cursor_onto_tile(
+    tile: &mut RgbaImage,
+    tile_origin: impl Into<Point>,
+    cursor_type: &CommonCursorType,
+    global_position: impl Into<Point>,
+    scale: f32,
+) -> Result<CompositeResult, CursorError> {
+    let position = global_position.into() - tile_origin.into();
+    render_cursor_onto_frame(tile, &(*cursor_type).into(), position, scale, None)
+}
+
+/// Resize a cursor bitmap, e.g. one captured at a different DPI than the output.
+///
+/// Colors are resampled premultiplied so transparent pixels don't bleed dark
+/// fringes into the cursor's edges.
+pub fn resample_cursor<'a>(
+    bitmap: &'a RgbaImage,
+    width: u32,
+    height: u32,
+    filter: ResampleFilter,
+) -> Cow<'a, RgbaImage> {
+    if bitmap.dimensions() == (width, height) {
+        return Cow::Borrowed(bitmap);
+    }
+
+    let premultiplied = image::Rgba32FImage::from_fn(bitmap.width(), bitmap.height(), |x, y| {
+        let [r, g, b, a] = bitmap.get_pixel(x, y).0.map(|c| c as f32 / 255.0);
+        image::Rgba([r * a, g * a, b * a, a])
+    });
+    let resized = image::imageops::resize(&premultiplied, width, height, filter.into());
+
+    Cow::Owned(RgbaImage::from_fn(width, height, |x, y| {
+        let [r, g, b, a] = resized.get_pixel(x, y).0;
+        // Filters with negative lobes can overshoot
+        let a = a.clamp(0.0, 1.0);
+        if a <= 0.0 {
+            return image::Rgba([0, 0, 0, 0]);
+        }
+
+        let channel = |c: f32| ((c / a).clamp(0.0, 1.0) * 255.0).round() as u8;
+        image::Rgba([
+            channel(r),
+            channel(g),
+            channel(b),
+            (a * 255.0).round() as u8,
+        ])
+    }))
+}
+
+/// Square `thumb_size` thumbnail of a captured cursor, e.g. to list every
+/// cursor a recording showed. The bitmap is cropped to its opaque bounding box
+/// and scaled to fit, keeping its aspect ratio and centered on a transparent
+/// background. Dimmed cursors are cropped to the shape they'd have at full
+/// opacity but keep their alpha.
+///
+/// A fully transparent or malformed bitmap gives a transparent thumbnail.
+pub fn cursor_thumbnail(image_data: &[u8], width: u32, height: u32, thumb_size: u32) -> RgbaImage {
+    let mut thumbnail = RgbaImage::new(thumb_size, thumb_size);
+    let (normalized, _) = normalize_alpha(image_data, width, height);
+    let Some(bounds) = opaque_bounds(&normalized, width, height).filter(|_| thumb_size > 0) else {
+        return thumbnail;
+    };
+
+    let cropped = RgbaImage::from_fn(bounds.width(), bounds.height(), |x, y| {
+        let i = (((bounds.min_y + y) * width + bounds.min_x + x) * 4) as usize;
+        image::Rgba([
+            image_data[i],
+            image_data[i + 1],
+            image_data[i + 2],
+            image_data[i + 3],
+        ])
+    });
+    let scale = thumb_size as f32 / bounds.width().max(bounds.height()) as f32;
+    let fitted = |dim: u32| ((dim as f32 * scale).round() as u32).clamp(1, thumb_size);
+    let (fit_width, fit_height) = (fitted(bounds.width()), fitted(bounds.height()));
+
+    let resized = resample_cursor(&cropped, fit_width, fit_height, ResampleFilter::default());
+    image::imageops::overlay(
+        &mut thumbnail,
+        resized.as_ref(),
+        ((thumb_size - fit_width) / 2) as i64,
+        ((thumb_size - fit_height) / 2) as i64,
+    );
+    thumbnail
+}
+
+/// Composite a cursor rotated clockwise by `angle_degrees` about its hotspot.
+/// Parts of the cursor that fall outside the frame are clipped. Like
+/// [`render_cursor_onto_frame`], returns the scale after clamping.
+pub fn render_cursor_onto_frame_rotated(
+    frame: &mut RgbaImage,
+    cursor_type: &CommonCursorType,
+    position: impl Into<Point>,
+    scale: f32,
+    angle_degrees: f32,
+) -> Result<f32, CursorError> {
+    let scale = ScaleLimits::default().clamp(scale)?;
+    let size = scaled_cursor_size(scale);
+    let (cursor, hotspot) = rasterize_cursor_rotated(cursor_type, size, angle_degrees)?;
+
+    let origin = position.into() - Point::new(hotspot.0.round() as i32, hotspot.1.round() as i32);
+    blend_onto(frame, &cursor, origin, None);
+
+    Ok(scale)
+}
+
+/// Composite a crossfade between two cursors, `t` = 0.0 showing only `from` and
+/// 1.0 only `to`. Both cursors are aligned on their own hotspots. Like
+/// [`render_cursor_onto_frame`], returns the scale after clamping.
+pub fn render_cursor_crossfade(
+    frame: &mut RgbaImage,
+    from: &CommonCursorType,
+    to: &CommonCursorType,
+    position: impl Into<Point>,
+    t: f32,
+    scale: f32,
+) -> Result<f32, CursorError> {
+    if !t.is_finite() {
+        return Err(CursorError::InvalidCrossfadeWeight(t));
+    }
+    let t = t.clamp(0.0, 1.0);
+    let scale = ScaleLimits::default().clamp(scale)?;
+    let size = scaled_cursor_size(scale);
+    let position = position.into();
+
+    let layer = |cursor_type: &CommonCursorType, weight: f32| {
+        let origin = position - Size::square(size).scale_hotspot(cursor_type.hotspot());
+        rasterize_cursor(cursor_type, size).map(|image| (image, origin, weight))
+    };
+    let (from, to) = (layer(from, 1.0 - t)?, layer(to, t)?);
+
+    let (blended, origin) = blend_layers(&[(&from.0, from.1, from.2), (&to.0, to.1, to.2)]);
+    blend_onto(frame, &blended, origin, None);
+
+    Ok(scale)
+}
+
+/// Dim `frame` by `dim`, 0.0 leaving it as is and 1.0 turning it black,
+/// everywhere but a circle of `radius` pixels around the cursor's `position`.
+///
+/// The dimming fades in over the outer [`SPOTLIGHT_SOFTNESS`] of the radius
+/// rather than starting at a hard edge. Meant to run before the cursor is
+/// composited, so the cursor itself is never dimmed. A position near or past
+/// the edge of the frame just leaves part of the circle off the frame. Alpha is
+/// kept; `dim` is clamped to 0..=1 and a negative radius dims the whole frame.
+/// Non-finite values leave the frame untouched.
+pub fn apply_cursor_spotlight(
+    frame: &mut RgbaImage,
+    position: impl Into<Point>,
+    radius: f32,
+    dim: f32,
+) {
+    if !radius.is_finite() || !dim.is_finite() {
+        return;
+    }
+    let position = position.into();
+    let radius = radius.max(0.0);
+    let inner = radius * (1.0 - SPOTLIGHT_SOFTNESS);
+    let dim = dim.clamp(0.0, 1.0);
+
+    for (x, y, pixel) in frame.enumerate_pixels_mut() {
+        let dx = x as f32 - position.x as f32;
+        let dy = y as f32 - position.y as f32;
+        let distance = (dx * dx + dy * dy).sqrt();
+        let outside = if distance >= radius {
+            1.0
+        } else if distance <= inner {
+            continue;
+        } else {
+            let t = (distance - inner) / (radius - inner);
+            t * t * (3.0 - 2.0 * t)
+        };
+
+        let factor = 1.0 - dim * outside;
+        for channel in 0..3 {
+            pixel[channel] = (pixel[channel] as f32 * factor).round() as u8;
+        }
+    }
+}
+
+/// `cursor` drawn over a halo of `style`: its alpha mask dilated by a disc
+/// of the style's width and filled with the style's color, so the halo's
+/// edge is as soft as the cursor's. The result is `2 * style.width` pixels
+/// larger on both axes, with the cursor `style.width` pixels in from the
+/// top-left corner
+pub fn outline_cursor(cursor: &RgbaImage, style: OutlineStyle) -> RgbaImage {
+    let radius = style.width as i32;
+    let (width, height) = cursor.dimensions();
+    let disc: Vec<(i32, i32)> = (-radius..=radius)
+        .flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
+        .filter(|(dx, dy)| dx * dx + dy * dy <= radius * radius)
+        .collect();
+    let alpha_at = |x: i32, y: i32| {
+        if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+            return 0;
+        }
+        cursor.get_pixel(x as u32, y as u32)[3]
+    };
+
+    let mut outlined = RgbaImage::new(width + style.width * 2, height + style.width * 2);
+    for (x, y, out) in outlined.enumerate_pixels_mut() {
+        let (cx, cy) = (x as i32 - radius, y as i32 - radius);
+        let halo = disc
+            .iter()
+            .map(|(dx, dy)| alpha_at(cx + dx, cy + dy))
+            .max()
+            .unwrap_or(0) as f32
+            / 255.0;
+        let (pixel, alpha) = if alpha_at(cx, cy) > 0 {
+            let pixel = cursor.get_pixel(cx as u32, cy as u32);
+            (pixel.0, pixel[3] as f32 / 255.0)
+        } else {
+            ([0; 4], 0.0)
+        };
+
+        // The cursor source-over its halo
+        let blended = alpha + halo * (1.0 - alpha);
+        if blended <= 0.0 {
+            continue;
+        }
+        for channel in 0..3 {
+            let color =
+                pixel[channel] as f32 * alpha + style.color[channel] as f32 * halo * (1.0 - alpha);
+            out[channel] = (color / blended).round().clamp(0.0, 255.0) as u8;
+        }
+        out[3] = (blended * 255.0).round() as u8;
+    }
+    outlined
+}
+
+/// Blend weighted cursor layers, each with its top-left corner at its origin,
+/// into one image covering all of them, returned with its own origin.
+///
+/// Compositing the result once keeps the parts where layers overlap from
+/// doubling up, as they would if every layer were composited on its own.
+pub(crate) fn blend_layers(layers: &[(&RgbaImage, Point, f32)]) -> (RgbaImage, Point) {
+    let min_x = layers
+        .iter()
+        .map(|(_, origin, _)| origin.x)
+        .min()
+        .unwrap_or(0);
+    let min_y = layers
+        .iter()
+        .map(|(_, origin, _)| origin.y)
+        .min()
+        .unwrap_or(0);
+    let max_x = layers
+        .iter()
+        .map(|(image, origin, _)| origin.x + image.width() as i32)
+        .max()
+        .unwrap_or(0);
+    let max_y = layers
+        .iter()
+        .map(|(image, origin, _)| origin.y + image.height() as i32)
+        .max()
+        .unwrap_or(0);
+
+    let mut blended = RgbaImage::new((max_x - min_x) as u32, (max_y - min_y) as u32);
+    for (x, y, out) in blended.enumerate_pixels_mut() {
+        let mut premultiplied = [0.0f32; 4];
+
+        for (image, origin, weight) in layers {
+            let sx = min_x + x as i32 - origin.x;
+            let sy = min_y + y as i32 - origin.y;
+            if sx < 0 || sy < 0 || sx >= image.width() as i32 || sy >= image.height() as i32 {
+                continue;
+            }
+
+            let pixel = image.get_pixel(sx as u32, sy as u32);
+            let alpha = pixel[3] as f32 / 255.0 * weight;
+            for channel in 0..3 {
+                premultiplied[channel] += pixel[channel] as f32 * alpha;
+            }
+            premultiplied[3] += alpha;
+        }
+
+        let alpha = premultiplied[3];
+        if alpha > 0.0 {
+            for channel in 0..3 {
+                out[channel] = (premultiplied[channel] / alpha).round().clamp(0.0, 255.0) as u8;
+            }
+            out[3] = (alpha * 255.0).round().clamp(0.0, 255.0) as u8;
+        }
+    }
+
+    (blended, Point::new(min_x, min_y))
+}
+
+/// Whether a cursor of `size` with its top-left corner at `origin` covers
+/// any of the frame
+pub(crate) fn overlaps_frame(frame: &RgbaImage, origin: Point, size: Size) -> bool {
+    origin.x < frame.width() as i32
+        && origin.y < frame.height() as i32
+        && origin.x + size.w as i32 > 0
+        && origin.y + size.h as i32 > 0
+}
+
+/// Pixel size of a cursor composited at an already clamped `scale`
+pub(crate) fn scaled_cursor_size(scale: f32) -> u32 {
+    (CURSOR_BASE_SIZE as f32 * scale).round().max(1.0) as u32
+}
+
+/// Source-over blend `cursor` onto `frame` with its top-left corner at `origin`.
+///
+/// Both images hold straight (non-premultiplied) alpha; every color channel is
+/// blended on its own, so colored cursors keep their RGB instead of being
+/// treated as a black/white mask. Frame pixels the occlusion mask covers are
+/// skipped.
+pub(crate) fn blend_onto(
+    frame: &mut RgbaImage,
+    cursor: &RgbaImage,
+    origin: Point,
+    occlusion_mask: Option<&GrayImage>,
+) {
+    let (frame_width, frame_height) = frame.dimensions();
+
+    for (cx, cy, src) in cursor.enumerate_pixels() {
+        let x = origin.x + cx as i32;
+        let y = origin.y + cy as i32;
+        if x < 0 || y < 0 || x >= frame_width as i32 || y >= frame_height as i32 {
+            continue;
+        }
+
+        let src_a = src[3] as f32 / 255.0;
+        if src_a <= 0.0 {
+            continue;
+        }
+
+        let occluded = occlusion_mask
+            .and_then(|mask| mask.get_pixel_checked(x as u32, y as u32))
+            .is_some_and(|coverage| coverage[0] > OCCLUSION_THRESHOLD);
+        if occluded {
+            continue;
+        }
+
+        let dst = frame.get_pixel_mut(x as u32, y as u32);
+        let dst_a = dst[3] as f32 / 255.0;
+        let out_a = src_a + dst_a * (1.0 - src_a);
+
+        for channel in 0..3 {
+            let value =
+                (src[channel] as f32 * src_a + dst[channel] as f32 * dst_a * (1.0 - src_a)) / out_a;
+            dst[channel] = value.round().clamp(0.0, 255.0) as u8;
+        }
+        dst[3] = (out_a * 255.0).round() as u8;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GenericImage, GenericImageView};
+
+    fn opaque_bounds(image: &RgbaImage) -> Option<(u32, u32, u32, u32)> {
+        image
+            .enumerate_pixels()
+            .filter(|(_, _, p)| p[3] > 128)
+            .fold(None, |bounds, (x, y, _)| {
+                Some(match bounds {
+                    None => (x, y, x, y),
+                    Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+                })
+            })
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_rotated_cursor_grows_to_fit() {
+        let (rotated, hotspot) =
+            rasterize_cursor_rotated(&CommonCursorType::Arrow, 32, 45.0).unwrap();
+        assert!(rotated.width() > 32 && rotated.height() > 32);
+        assert!(hotspot.0 >= 0.0 && hotspot.0 <= rotated.width() as f32);
+        assert!(hotspot.1 >= 0.0 && hotspot.1 <= rotated.height() as f32);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_rotated_ibeam_turns_horizontal() {
+        let upright = rasterize_cursor(&CommonCursorType::IBeam, 32).unwrap();
+        let (x0, y0, x1, y1) = opaque_bounds(&upright).unwrap();
+        assert!(y1 - y0 > x1 - x0);
+
+        let (rotated, _) = rasterize_cursor_rotated(&CommonCursorType::IBeam, 32, 90.0).unwrap();
+        let (x0, y0, x1, y1) = opaque_bounds(&rotated).unwrap();
+        assert!(x1 - x0 > y1 - y0);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_render_rotated_clips_at_frame_edges() {
+        let mut frame = RgbaImage::new(40, 40);
+        render_cursor_onto_frame_rotated(&mut frame, &CommonCursorType::IBeam, (0, 39), 1.0, 30.0)
+            .unwrap();
+
+        assert!(frame.pixels().any(|p| p[3] > 0));
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_fallback_pngs_match_svgs() {
+        for cursor_type in CommonCursorType::ALL {
+            for (png, size) in cursor_type
+                .fallback_pngs()
+                .into_iter()
+                .zip(FALLBACK_PNG_SIZES)
+            {
+                let fallback = image::load_from_memory(png).unwrap().into_rgba8();
+                let rendered = rasterize_cursor(cursor_type, size).unwrap();
+                assert_eq!(fallback.dimensions(), (size, size), "{:?}", cursor_type);
+
+                // Stale PNGs drift from the SVG by more than rounding
+                let drift = fallback
+                    .as_raw()
+                    .iter()
+                    .zip(rendered.as_raw())
+                    .map(|(a, b)| a.abs_diff(*b))
+                    .max();
+                assert!(drift <= Some(2), "{:?} at {}px", cursor_type, size);
+            }
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_recolor_swaps_fill_and_keeps_shape() {
+        let tint = [230, 60, 120];
+        let original = rasterize_cursor(&CommonCursorType::Arrow, 48).unwrap();
+        let fill_only = Recolor {
+            fill: tint,
+            stroke: None,
+        };
+        let recolored =
+            rasterize_cursor_recolored(&CommonCursorType::Arrow, 48, Some(fill_only)).unwrap();
+        assert!(recolored.recolored);
+
+        // Same coverage, with the white body tinted
+        let alpha = |image: &RgbaImage| image.pixels().map(|p| p[3]).collect::<Vec<_>>();
+        assert_eq!(alpha(&recolored.image), alpha(&original));
+        let (x, y) = original
+            .enumerate_pixels()
+            .find(|(_, _, p)| p.0 == [255, 255, 255, 255])
+            .map(|(x, y, _)| (x, y))
+            .unwrap();
+        // Up to rounding through the renderer's premultiplied pixels
+        let pixel = recolored.image.get_pixel(x, y).0;
+        assert!(
+            pixel
+                .iter()
+                .zip([tint[0], tint[1], tint[2], 255])
+                .all(|(a, b)| a.abs_diff(b) <= 1),
+            "{:?}",
+            pixel
+        );
+
+        // Recoloring the strokes too changes the outline, not the shape
+        let outlined = Recolor {
+            stroke: Some([20, 20, 90]),
+            ..fill_only
+        };
+        let outlined =
+            rasterize_cursor_recolored(&CommonCursorType::Arrow, 48, Some(outlined)).unwrap();
+        assert_eq!(alpha(&outlined.image), alpha(&original));
+        assert_ne!(outlined.image, recolored.image);
+        assert_eq!(
+            outlined.image.get_pixel(x, y),
+            recolored.image.get_pixel(x, y)
+        );
+
+        let plain = rasterize_cursor_recolored(&CommonCursorType::Arrow, 48, None).unwrap();
+        assert_eq!(plain.image, original);
+        assert!(!plain.recolored);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_recolor_skips_gradients() {
+        let gradient = br##"<svg width="24" height="24" xmlns="http://www.w3.org/2000/svg">
+            <defs><linearGradient id="g"><stop offset="0" stop-color="#ff0000"/><stop offset="1" stop-color="#0000ff"/></linearGradient></defs>
+            <g><path d="M2 2 L2 20 L14 14 Z" fill="url(#g)" stroke="#000000"/></g>
+        </svg>"##;
+        assert!(!has_only_solid_paints(parse_svg(gradient).unwrap().root()));
+
+        let solid = br##"<svg width="24" height="24" xmlns="http://www.w3.org/2000/svg">
+            <path d="M2 2 L2 20 L14 14 Z" fill="#ffffff" stroke="#000000"/>
+        </svg>"##;
+        assert!(has_only_solid_paints(parse_svg(solid).unwrap().root()));
+
+        assert_eq!(
+            replace_solid_paint(
+                r##"<path fill="#ffffff" stroke="#000000"/><path fill="none" flood-fill="#ffffff"/>"##,
+                "fill",
+                "#e63c78"
+            ),
+            r##"<path fill="#e63c78" stroke="#000000"/><path fill="none" flood-fill="#ffffff"/>"##
+        );
+    }
+
+    #[test]
+    fn test_fallback_picks_nearest_png() {
+        let [one_x, two_x] = CommonCursorType::Arrow.fallback_pngs();
+        let one_x = image::load_from_memory(one_x).unwrap().into_rgba8();
+        let two_x = image::load_from_memory(two_x).unwrap().into_rgba8();
+        let fallback = |size| rasterize_cursor_fallback(&CommonCursorType::Arrow, size).unwrap();
+
+        assert_eq!(fallback(32), one_x);
+        assert_eq!(fallback(64), two_x);
+
+        // 40px is closer to 1x, 48px to 2x
+        let resampled = |image: &RgbaImage, size| {
+            image::imageops::resize(image, size, size, image::imageops::FilterType::Lanczos3)
+        };
+        assert_eq!(fallback(40), resampled(&one_x, 40));
+        assert_eq!(fallback(48), resampled(&two_x, 48));
+        assert_eq!(fallback(128), resampled(&two_x, 128));
+
+        assert!(matches!(
+            rasterize_cursor_fallback(&CommonCursorType::Arrow, 0),
+            Err(CursorError::InvalidSize { .. })
+        ));
+    }
+
+    #[test]
+    fn test_every_cursor_rasterizes() {
+        // What the texture manager uploads, from the SVGs or the PNG fallbacks
+        for cursor_type in CommonCursorType::ALL {
+            let image = rasterize_cursor(cursor_type, 64).unwrap();
+            assert_eq!(image.dimensions(), (64, 64), "{:?}", cursor_type);
+            assert_eq!(
+                image.pixels().any(|p| p[3] > 0),
+                *cursor_type != CommonCursorType::Blank,
+                "{:?}",
+                cursor_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_render_places_hotspot_at_position() {
+        let mut frame = RgbaImage::new(64, 64);
+        render_cursor_onto_frame(
+            &mut frame,
+            &CommonCursorType::Crosshair.into(),
+            (32, 32),
+            1.0,
+            None,
+        )
+        .unwrap();
+
+        // The crosshair's center is its hotspot
+        assert!(frame.get_pixel(32, 32)[3] > 128);
+        assert_eq!(frame.get_pixel(2, 2)[3], 0);
+    }
+
+    #[test]
+    fn test_render_frame_between_samples() {
+        let track = CursorTrack::new([(0.0, 0.25, 0.25), (1.0, 0.75, 0.75)]);
+
+        let mut frame = RgbaImage::new(64, 64);
+        let drawn =
+            render_frame_at(&mut frame, &track, CommonCursorType::Crosshair, 0.5, 1.0).unwrap();
+        assert_eq!(
+            drawn,
+            CompositeResult {
+                scale: 1.0,
+                drawn: true
+            }
+        );
+
+        // Halfway along the track is the middle of the frame
+        let mut expected = RgbaImage::new(64, 64);
+        render_cursor_onto_frame(
+            &mut expected,
+            &CommonCursorType::Crosshair.into(),
+            (32, 32),
+            1.0,
+            None,
+        )
+        .unwrap();
+        assert_eq!(frame, expected);
+
+        let mut untouched = RgbaImage::new(64, 64);
+        let empty = CursorTrack::default();
+        assert_eq!(
+            render_frame_at(&mut untouched, &empty, CommonCursorType::Arrow, 0.5, 1.0)
+                .map(|result| result.drawn),
+            Ok(false)
+        );
+        assert!(untouched.pixels().all(|p| p[3] == 0));
+    }
+
+    #[test]
+    fn test_tiles_match_single_pass() {
+        let background = RgbaImage::from_fn(64, 48, |x, y| {
+            image::Rgba([(x * 4) as u8, (y * 5) as u8, 128, 255])
+        });
+        // Hotspot near a tile corner, so the cursor spans all four tiles
+        let (position, scale) = ((30, 20), 1.5);
+
+        let mut expected = background.clone();
+        render_cursor_onto_frame(
+            &mut expected,
+            &CommonCursorType::Crosshair.into(),
+            position,
+            scale,
+            None,
+        )
+        .unwrap();
+
+        let mut assembled = background.clone();
+        for (x, y, width, height) in [
+            (0, 0, 29, 21),
+            (29, 0, 35, 21),
+            (0, 21, 29, 27),
+            (29, 21, 35, 27),
+        ] {
+            let mut tile = background.view(x, y, width, height).to_image();
+            let result = render_cursor_onto_tile(
+                &mut tile,
+                (x as i32, y as i32),
+                &CommonCursorType::Crosshair,
+                position,
+                scale,
+            )
+            .unwrap();
+            assert!(result.drawn);
+            assembled.copy_from(&tile, x, y).unwrap();
+        }
+        assert_eq!(assembled, expected);
+
+        // A tile the cursor doesn't reach is left as it was
+        let untouched = background.view(56, 40, 8, 8).to_image();
+        let mut far = untouched.clone();
+        let result = render_cursor_onto_tile(
+            &mut far,
+            (56, 40),
+            &CommonCursorType::Crosshair,
+            position,
+            scale,
+        )
+        .unwrap();
+        assert!(!result.drawn);
+        assert_eq!(far, untouched);
+    }
+
+    #[test]
+    fn test_crossfade_endpoints_match_single_cursor() {
+        for (t, expected) in [
+            (0.0, CommonCursorType::Arrow),
+            (1.0, CommonCursorType::IBeam),
+        ] {
+            let mut single = RgbaImage::new(64, 64);
+            render_cursor_onto_frame(&mut single, &expected.into(), (32, 32), 1.0, None).unwrap();
+
+            let mut faded = RgbaImage::new(64, 64);
+            render_cursor_crossfade(
+                &mut faded,
+                &CommonCursorType::Arrow,
+                &CommonCursorType::IBeam,
+                (32, 32),
+                t,
+                1.0,
+            )
+            .unwrap();
+
+            assert_eq!(single, faded);
+        }
+    }
+
+    #[test]
+    fn test_crossfade_midpoint_is_partially_transparent() {
+        let mut frame = RgbaImage::new(64, 64);
+        render_cursor_crossfade(
+            &mut frame,
+            &CommonCursorType::Crosshair,
+            &CommonCursorType::IBeam,
+            (32, 32),
+            0.5,
+            1.0,
+        )
+        .unwrap();
+
+        // Pixels only one of the cursors covers end up at roughly half coverage
+        let max_alpha = frame.pixels().map(|p| p[3]).max().unwrap();
+        assert!(max_alpha > 200);
+        assert!(frame.pixels().any(|p| (100..=160).contains(&p[3])));
+    }
+
+    #[test]
+    fn test_render_raw_bitmap_at_hotspot() {
+        let mut bitmap = RgbaImage::new(10, 10);
+        bitmap.put_pixel(5, 5, image::Rgba([255, 0, 0, 255]));
+        let source = CursorRenderSource::RawBitmap(bitmap, (0.5, 0.5));
+
+        let mut frame = RgbaImage::new(40, 40);
+        render_cursor_onto_frame(&mut frame, &source, (20, 20), 1.0, None).unwrap();
+
+        assert_eq!(*frame.get_pixel(20, 20), image::Rgba([255, 0, 0, 255]));
+        assert_eq!(frame.pixels().filter(|p| p[3] > 0).count(), 1);
+
+        // Scaled bitmaps grow around their hotspot
+        let mut frame = RgbaImage::new(40, 40);
+        render_cursor_onto_frame(&mut frame, &source, (20, 20), 2.0, None).unwrap();
+        assert!(frame.get_pixel(20, 20)[3] > 0);
+        assert!(frame.get_pixel(10, 10)[3] == 0);
+    }
+
+    #[test]
+    fn test_resample_nearest_keeps_hard_edges() {
+        let mut checker = RgbaImage::new(4, 4);
+        for (x, y, pixel) in checker.enumerate_pixels_mut() {
+            if (x + y) % 2 == 0 {
+                *pixel = image::Rgba([0, 0, 0, 255]);
+            }
+        }
+
+        let nearest = resample_cursor(&checker, 8, 8, ResampleFilter::Nearest);
+        assert!(nearest.pixels().all(|p| p[3] == 0 || p[3] == 255));
+
+        let smooth = resample_cursor(&checker, 8, 8, ResampleFilter::Lanczos3);
+        assert!(smooth.pixels().any(|p| p[3] > 0 && p[3] < 255));
+    }
+
+    #[test]
+    fn test_resample_does_not_darken_edges() {
+        let mut bitmap = RgbaImage::new(4, 4);
+        for (x, _, pixel) in bitmap.enumerate_pixels_mut() {
+            if x < 2 {
+                *pixel = image::Rgba([255, 255, 255, 255]);
+            }
+        }
+
+        let resized = resample_cursor(&bitmap, 8, 8, ResampleFilter::default());
+        for pixel in resized.pixels().filter(|p| p[3] > 0) {
+            assert_eq!(pixel.0[..3], [255, 255, 255]);
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_colored_cursor_edges_blend_per_channel() {
+        let red_disc = br##"<svg width="16" height="16" viewBox="0 0 16 16" xmlns="http://www.w3.org/2000/svg"><circle cx="8" cy="8" r="6.3" fill="#FF0000"/></svg>"##;
+        let cursor = rasterize_svg(red_disc, 16).unwrap();
+        let source = CursorRenderSource::RawBitmap(cursor.clone(), (0.0, 0.0));
+
+        let mut frame = RgbaImage::from_pixel(16, 16, image::Rgba([0, 0, 255, 255]));
+        render_cursor_onto_frame(&mut frame, &source, (0, 0), 1.0, None).unwrap();
+
+        let mut edges = 0;
+        for (x, y, src) in cursor.enumerate_pixels() {
+            let alpha = src[3] as f32 / 255.0;
+            let out = frame.get_pixel(x, y);
+            assert_eq!(out[3], 255);
+            assert!((out[0] as f32 - 255.0 * alpha).abs() <= 1.0);
+            assert!((out[2] as f32 - 255.0 * (1.0 - alpha)).abs() <= 1.0);
+            assert_eq!(out[1], 0);
+
+            // Partially covered edge pixels mix to purple rather than darkening
+            if src[3] > 80 && src[3] < 176 {
+                edges += 1;
+                assert!(out[0] > 64 && out[2] > 64);
+            }
+        }
+        assert!(edges > 0);
+    }
+
+    #[test]
+    fn test_occlusion_mask_hides_covered_half() {
+        let source = CursorRenderSource::RawBitmap(
+            RgbaImage::from_pixel(10, 10, image::Rgba([255, 255, 255, 255])),
+            (0.0, 0.0),
+        );
+        // Covers the left half of the frame
+        let mask = GrayImage::from_fn(20, 20, |x, _| image::Luma([if x < 10 { 255 } else { 0 }]));
+
+        let mut frame = RgbaImage::new(20, 20);
+        render_cursor_onto_frame(&mut frame, &source, (5, 5), 1.0, Some(&mask)).unwrap();
+
+        for y in 5..15 {
+            for x in 5..15 {
+                let expected_alpha = if x < 10 { 0 } else { 255 };
+                assert_eq!(
+                    frame.get_pixel(x, y)[3],
+                    expected_alpha,
+                    "at ({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_detect_and_composite() {
+        let arrow = rasterize_cursor(&CommonCursorType::Arrow, 32).unwrap();
+        let mut frame = RgbaImage::new(64, 64);
+        let mut expected = RgbaImage::new(64, 64);
+        render_cursor_onto_frame(
+            &mut expected,
+            &CommonCursorType::Arrow.into(),
+            (10, 10),
+            1.0,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            detect_and_composite(
+                &mut frame,
+                arrow.as_raw(),
+                (32, 32),
+                (0.0, 0.0),
+                (10, 10),
+                1.0
+            ),
+            Some(CommonCursorType::Arrow)
+        );
+        assert_eq!(frame, expected);
+    }
+
+    #[test]
+    fn test_detect_and_composite_draws_unknown_bitmap() {
+        // A hollow square doesn't look like any known cursor
+        let outline = RgbaImage::from_fn(16, 16, |x, y| {
+            let edge = |v: u32| !(2..=13).contains(&v);
+            image::Rgba(if edge(x) || edge(y) {
+                [255, 0, 0, 255]
+            } else {
+                [0, 0, 0, 0]
+            })
+        });
+        let mut frame = RgbaImage::new(32, 32);
+
+        // Drawn with its hotspot, the center here, on the position
+        assert_eq!(
+            detect_and_composite(
+                &mut frame,
+                outline.as_raw(),
+                (16, 16),
+                (0.5, 0.5),
+                (12, 14),
+                1.0
+            ),
+            None
+        );
+        for (x, y, pixel) in outline.enumerate_pixels() {
+            assert_eq!(frame.get_pixel(x + 4, y + 6), pixel);
+        }
+
+        // Nothing is drawn for a truncated buffer
+        let mut untouched = RgbaImage::new(32, 32);
+        assert_eq!(
+            detect_and_composite(
+                &mut untouched,
+                &outline.as_raw()[..100],
+                (16, 16),
+                (0.5, 0.5),
+                (12, 14),
+                1.0
+            ),
+            None
+        );
+        assert_eq!(untouched, RgbaImage::new(32, 32));
+    }
+
+    #[test]
+    fn test_render_rejects_invalid_scale() {
+        let mut frame = RgbaImage::new(8, 8);
+        assert_eq!(
+            render_cursor_onto_frame(
+                &mut frame,
+                &CommonCursorType::Arrow.into(),
+                (0, 0),
+                0.0,
+                None
+            ),
+            Err(CursorError::InvalidScale(0.0))
+        );
+    }
+
+    #[test]
+    fn test_render_clamps_giant_scale() {
+        let mut frame = RgbaImage::new(256, 256);
+        let used = render_cursor_onto_frame(
+            &mut frame,
+            &CommonCursorType::Crosshair.into(),
+            (128, 128),
+            10.0,
+            None,
+        )
+        .unwrap();
+        assert_eq!(used.scale, DEFAULT_MAX_CURSOR_SCALE);
+
+        // The crosshair spans the clamped size, not ten times the base size
+        let drawn = (0..256).filter(|&x| frame.get_pixel(x, 128)[3] > 0).count() as u32;
+        assert!(drawn <= CURSOR_BASE_SIZE * 4, "{} pixels wide", drawn);
+    }
+
+    #[test]
+    fn test_offscreen_cursor_is_skipped() {
+        let mut frame = RgbaImage::new(64, 64);
+        for position in [(-40, 10), (10, -40), (100, 10), (10, 100)] {
+            let result = render_cursor_onto_frame(
+                &mut frame,
+                &CommonCursorType::Arrow.into(),
+                position,
+                1.0,
+                None,
+            )
+            .unwrap();
+            assert!(!result.drawn, "{:?}", position);
+        }
+        assert_eq!(frame, RgbaImage::new(64, 64));
+
+        // Just the cursor's bottom-right corner overlaps the frame
+        let result = render_cursor_onto_frame(
+            &mut frame,
+            &CommonCursorType::Crosshair.into(),
+            (-15, -15),
+            1.0,
+            None,
+        )
+        .unwrap();
+        assert!(result.drawn);
+
+        let bitmap = RgbaImage::from_pixel(8, 8, image::Rgba([255, 0, 0, 255]));
+        let source = CursorRenderSource::RawBitmap(bitmap, (0.5, 0.5));
+        let result = render_cursor_onto_frame(&mut frame, &source, (68, 32), 1.0, None).unwrap();
+        assert!(!result.drawn);
+        let result = render_cursor_onto_frame(&mut frame, &source, (67, 32), 1.0, None).unwrap();
+        assert!(result.drawn);
+        assert_eq!(frame.get_pixel(63, 32)[3], 255);
+    }
+
+    #[test]
+    fn test_auto_theme_contrasts_with_frame() {
+        let render = |background: u8, theme: CursorTheme| {
+            let mut frame = RgbaImage::from_pixel(64, 64, image::Rgba([background; 4]));
+            render_cursor_onto_frame_themed(
+                &mut frame,
+                &CommonCursorType::Arrow,
+                (16, 16),
+                1.0,
+                theme,
+            )
+            .unwrap();
+            frame
+        };
+
+        assert_eq!(
+            render(255, CursorTheme::Auto),
+            render(255, CursorTheme::Dark)
+        );
+        assert_eq!(render(0, CursorTheme::Auto), render(0, CursorTheme::Light));
+        assert_ne!(
+            render(255, CursorTheme::Dark),
+            render(255, CursorTheme::Light)
+        );
+    }
+
+    #[test]
+    fn test_custom_scale_limits() {
+        let limits = ScaleLimits { min: 0.5, max: 2.0 };
+        assert_eq!(limits.clamp(0.1), Ok(0.5));
+        assert_eq!(limits.clamp(1.5), Ok(1.5));
+        assert_eq!(limits.clamp(3.0), Ok(2.0));
+        assert_eq!(
+            limits.clamp(f32::INFINITY),
+            Err(CursorError::InvalidScale(f32::INFINITY))
+        );
+
+        let bitmap = RgbaImage::from_pixel(4, 4, image::Rgba([255, 0, 0, 255]));
+        let source = CursorRenderSource::RawBitmap(bitmap, (0.0, 0.0));
+        let mut frame = RgbaImage::new(32, 32);
+        let used = render_cursor_onto_frame_filtered(
+            &mut frame,
+            &source,
+            (0, 0),
+            PositionRef::Hotspot,
+            5.0,
+            limits,
+            ResampleFilter::Nearest,
+            1.0,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(used.scale, 2.0);
+        assert_eq!(frame.get_pixel(7, 7)[3], 255);
+        assert_eq!(frame.get_pixel(8, 8)[3], 0);
+    }
+
+    #[test]
+    fn test_position_ref_offsets_by_the_hotspot() {
+        let render = |source: &CursorRenderSource, position: Point, position_ref| {
+            let mut frame = RgbaImage::new(64, 64);
+            let result = render_cursor_onto_frame_filtered(
+                &mut frame,
+                source,
+                position,
+                position_ref,
+                1.0,
+                ScaleLimits::default(),
+                ResampleFilter::Nearest,
+                1.0,
+                None,
+                None,
+            )
+            .unwrap();
+            assert!(result.drawn);
+            frame
+        };
+        let opaque_corners = |frame: &RgbaImage| {
+            let opaque = || {
+                frame
+                    .enumerate_pixels()
+                    .filter(|(_, _, pixel)| pixel[3] > 0)
+            };
+            (
+                opaque().map(|(x, y, _)| (x, y)).min(),
+                opaque().map(|(x, y, _)| (x, y)).max(),
+            )
+        };
+
+        // A 4x4 bitmap with its hotspot in the center
+        let bitmap = RgbaImage::from_pixel(4, 4, image::Rgba([255, 0, 0, 255]));
+        let source = CursorRenderSource::RawBitmap(bitmap, (0.5, 0.5));
+        let position = Point::new(10, 10);
+        assert_eq!(
+            opaque_corners(&render(&source, position, PositionRef::Hotspot)),
+            (Some((8, 8)), Some((11, 11)))
+        );
+        assert_eq!(
+            opaque_corners(&render(&source, position, PositionRef::TopLeft)),
+            (Some((10, 10)), Some((13, 13)))
+        );
+
+        // A known type's corner is its hotspot position less the scaled hotspot
+        let source = CursorRenderSource::KnownType(CommonCursorType::Crosshair);
+        let (size, hotspot) = (
+            Size::square(scaled_cursor_size(1.0)),
+            CommonCursorType::Crosshair.hotspot(),
+        );
+        let position = Point::new(30, 30);
+        let corner = PositionRef::Hotspot.origin(position, size, hotspot);
+        assert_eq!(corner, position - size.scale_hotspot(hotspot));
+        assert_eq!(PositionRef::TopLeft.origin(corner, size, hotspot), corner);
+        assert_eq!(
+            render(&source, corner, PositionRef::TopLeft),
+            render(&source, position, PositionRef::Hotspot)
+        );
+    }
+
+    #[test]
+    fn test_outline_rings_the_arrow() {
+        let style = OutlineStyle {
+            width: 2,
+            color: [255, 0, 0],
+        };
+        let cursor = rasterize_cursor(&CommonCursorType::Arrow, 32).unwrap();
+        let outlined = outline_cursor(&cursor, style);
+        assert_eq!(outlined.dimensions(), (36, 36));
+
+        let alpha = |x: i32, y: i32| {
+            if x < 0 || y < 0 || x >= 32 || y >= 32 {
+                return 0;
+            }
+            cursor.get_pixel(x as u32, y as u32)[3]
+        };
+        let near = |x: i32, y: i32, distance: i32, min_alpha: u8| {
+            (-distance..=distance).any(|dy| {
+                (-distance..=distance).any(|dx| {
+                    dx * dx + dy * dy <= distance * distance && alpha(x + dx, y + dy) >= min_alpha
+                })
+            })
+        };
+        let reddish = |pixel: &image::Rgba<u8>| {
+            pixel[3] == 255 && pixel[0] >= pixel[1].max(pixel[2]).saturating_add(64)
+        };
+        let mut ring = 0;
+        for (x, y, pixel) in outlined.enumerate_pixels() {
+            let (cx, cy) = (x as i32 - 2, y as i32 - 2);
+            if alpha(cx, cy) == 255 {
+                assert_eq!(pixel, cursor.get_pixel(cx as u32, cy as u32));
+            } else if alpha(cx, cy) < 128 && near(cx, cy, 2, 255) {
+                // Fully covered, red under the cursor's faint edge and shadow
+                assert!(reddish(pixel), "{x},{y}: {pixel:?}");
+                ring += 1;
+            } else if !near(cx, cy, 2, 1) {
+                assert_eq!(pixel[3], 0, "{x},{y}");
+            }
+        }
+        assert!(ring > 40, "{ring}");
+
+        // On a frame the halo lands around the cursor, which stays on top
+        let source = CursorRenderSource::KnownType(CommonCursorType::Arrow);
+        let background = image::Rgba([0, 0, 255, 255]);
+        let render = |outline| {
+            let mut frame = RgbaImage::from_pixel(48, 48, background);
+            let result = render_cursor_onto_frame_filtered(
+                &mut frame,
+                &source,
+                (8, 8),
+                PositionRef::Hotspot,
+                1.0,
+                ScaleLimits::default(),
+                ResampleFilter::default(),
+                1.0,
+                outline,
+                None,
+            )
+            .unwrap();
+            assert!(result.drawn);
+            frame
+        };
+        let (plain, haloed) = (render(None), render(Some(style)));
+        let origin =
+            Point::new(8, 8) - Size::square(32).scale_hotspot(CommonCursorType::Arrow.hotspot());
+        let red = |frame: &RgbaImage| frame.pixels().filter(|p| reddish(p)).count();
+        assert_eq!(red(&plain), 0);
+        assert!(red(&haloed) >= ring);
+        for (x, y) in (0..32).flat_map(|y| (0..32).map(move |x| (x, y))) {
+            if alpha(x, y) == 255 {
+                let (fx, fy) = ((origin.x + x) as u32, (origin.y + y) as u32);
+                assert_eq!(plain.get_pixel(fx, fy), haloed.get_pixel(fx, fy));
+            }
+        }
+
+        // A zero width halo is no halo
+        assert_eq!(render(Some(OutlineStyle { width: 0, ..style })), plain);
+    }
+
+    #[test]
+    fn test_global_opacity_halves_cursor_alpha() {
+        let source = CursorRenderSource::KnownType(CommonCursorType::Arrow);
+        let render = |opacity| {
+            let mut frame = RgbaImage::new(48, 48);
+            let result = render_cursor_onto_frame_filtered(
+                &mut frame,
+                &source,
+                (8, 8),
+                PositionRef::Hotspot,
+                1.0,
+                ScaleLimits::default(),
+                ResampleFilter::default(),
+                opacity,
+                None,
+                None,
+            )
+            .map(|result| result.drawn);
+            (frame, result)
+        };
+
+        let (opaque, drawn) = render(1.0);
+        assert_eq!(drawn, Ok(true));
+        let (ghost, drawn) = render(0.5);
+        assert_eq!(drawn, Ok(true));
+
+        let mut edges = 0;
+        for (a, b) in opaque.pixels().zip(ghost.pixels()) {
+            assert_eq!(b[3], (a[3] as f32 / 2.0).round() as u8);
+            if a[3] > 0 && a[3] < 255 {
+                edges += 1;
+                assert_eq!(&a.0[..3], &b.0[..3]);
+            }
+        }
+        assert!(edges > 0);
+
+        let (empty, drawn) = render(0.0);
+        assert_eq!(drawn, Ok(false));
+        assert!(empty.pixels().all(|pixel| pixel[3] == 0));
+        assert!(matches!(
+            render(f32::NAN).1,
+            Err(CursorError::InvalidOpacity(opacity)) if opacity.is_nan()
+        ));
+    }
+
+    #[test]
+    fn test_spotlight_dims_outside_soft_circle() {
+        let white = image::Rgba([255, 255, 255, 200]);
+        let mut frame = RgbaImage::from_pixel(64, 48, white);
+        apply_cursor_spotlight(&mut frame, (2, 3), 20.0, 0.5);
+
+        // Untouched inside, half as bright well outside, in between at the rim
+        assert_eq!(*frame.get_pixel(2, 3), white);
+        assert_eq!(*frame.get_pixel(12, 3), white);
+        assert_eq!(*frame.get_pixel(63, 47), image::Rgba([128, 128, 128, 200]));
+        let rim = frame.get_pixel(19, 3)[0];
+        assert!(rim > 128 && rim < 255, "{rim}");
+        assert!(frame.get_pixel(18, 3)[0] > rim);
+
+        // A cursor off the frame still lights the part of the circle on it
+        let mut frame = RgbaImage::from_pixel(32, 32, white);
+        apply_cursor_spotlight(&mut frame, (-5, 16), 12.0, 1.0);
+        assert_eq!(*frame.get_pixel(0, 16), white);
+        assert_eq!(frame.get_pixel(31, 16)[0], 0);
+        assert_eq!(frame.get_pixel(31, 16)[3], 200);
+
+        apply_cursor_spotlight(&mut frame, (16, 16), f32::NAN, 1.0);
+        assert_eq!(*frame.get_pixel(0, 16), white);
+    }
+
+    #[test]
+    fn test_estimate_scale_from_opaque_bounds() {
+        assert_eq!(estimate_scale(11, 17), 1.0);
+        assert_eq!(estimate_scale(21, 33), 2.0);
+        assert_eq!(estimate_scale(30, 47), 3.0);
+        assert_eq!(estimate_scale(14, 23), 1.5);
+
+        // Out of range or degenerate boxes
+        assert_eq!(estimate_scale(4, 6), 1.0);
+        assert_eq!(estimate_scale(0, 0), 1.0);
+        assert_eq!(estimate_scale(256, 400), DEFAULT_MAX_CURSOR_SCALE);
+    }
+
+    #[test]
+    fn test_cursor_thumbnail_crops_and_centers() {
+        // A 4x8 bar off in a corner of a 32x32 capture
+        let mut bitmap = RgbaImage::new(32, 32);
+        for y in 20..28 {
+            for x in 24..28 {
+                bitmap.put_pixel(x, y, image::Rgba([255, 0, 0, 255]));
+            }
+        }
+
+        let thumbnail = cursor_thumbnail(bitmap.as_raw(), 32, 32, 16);
+        assert_eq!(thumbnail.dimensions(), (16, 16));
+        // Scaled to the full height, and centered across
+        assert_eq!(opaque_bounds(&thumbnail), Some((4, 0, 11, 15)));
+        assert_eq!(thumbnail.get_pixel(8, 8).0, [255, 0, 0, 255]);
+        assert_eq!(thumbnail.get_pixel(1, 8)[3], 0);
+    }
+
+    #[test]
+    fn test_cursor_thumbnail_of_blank_and_dimmed_cursors() {
+        let blank = cursor_thumbnail(&[0; 32 * 32 * 4], 32, 32, 12);
+        assert_eq!(blank, RgbaImage::new(12, 12));
+        assert_eq!(cursor_thumbnail(&[], 32, 32, 12), RgbaImage::new(12, 12));
+
+        // Below the opacity threshold, but still cropped to its shape
+        let dimmed = RgbaImage::from_pixel(8, 8, image::Rgba([0, 0, 0, 100]));
+        let mut bitmap = RgbaImage::new(32, 32);
+        image::imageops::overlay(&mut bitmap, &dimmed, 0, 0);
+        let thumbnail = cursor_thumbnail(bitmap.as_raw(), 32, 32, 16);
+        assert!(thumbnail.pixels().all(|p| p[3] == 100));
+    }
+
+    /// Set to rewrite the golden composite instead of comparing against it,
+    /// after a deliberate change to the blend math
+    const UPDATE_GOLDEN_ENV: &str = "CAP_CURSOR_UPDATE_GOLDEN";
+
+    #[test]
+    fn test_composite_matches_golden_image() {
+        // A gradient whose right half is translucent, so both the opaque and
+        // the alpha-compositing paths of the blend are covered
+        let mut frame = RgbaImage::from_fn(64, 64, |x, y| {
+            let alpha = if x < 32 { 255 } else { 128 };
+            image::Rgba([(x * 4) as u8, (y * 4) as u8, 180, alpha])
+        });
+
+        // The bundled PNGs rather than the SVGs, so the golden doesn't move
+        // with the rasterizer. One cursor is clipped by the bottom-right edge,
+        // the other by the top-left
+        for (cursor_type, position) in [
+            (CommonCursorType::Arrow, (50, 50)),
+            (CommonCursorType::Copy, (2, 2)),
+        ] {
+            let bitmap = rasterize_cursor_fallback(&cursor_type, CURSOR_BASE_SIZE).unwrap();
+            let source = CursorRenderSource::RawBitmap(bitmap, cursor_type.hotspot());
+            render_cursor_onto_frame(&mut frame, &source, position, 1.5, None).unwrap();
+        }
+
+        let golden = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/golden/composite.png");
+        if std::env::var_os(UPDATE_GOLDEN_ENV).is_some() {
+            frame.save(&golden).unwrap();
+            return;
+        }
+        let expected = image::open(&golden).unwrap().to_rgba8();
+        assert_eq!(frame.dimensions(), expected.dimensions());
+        for (x, y, pixel) in frame.enumerate_pixels() {
+            assert_eq!(
+                pixel,
+                expected.get_pixel(x, y),
+                "pixel ({x}, {y}) differs from the golden, set {UPDATE_GOLDEN_ENV} to regenerate it"
+            );
+        }
+    }
+}
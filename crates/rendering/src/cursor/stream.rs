@@ -0,0 +1,637 @@
+use crate::cursor::components::detect_spinner_progress;
+use crate::cursor::detection::CommonCursorType;
+use crate::cursor::features::mirror_horizontally;
+use crate::cursor::fingerprint::perceptual_hash;
+use crate::cursor::geometry::{Rect, Size};
+use crate::cursor::CursorError;
+use std::collections::VecDeque;
+
+/// Frames [`AnimatedCursorClassifier`] looks back over by default, two loops
+/// of a typical six-frame busy animation
+pub const DEFAULT_ANIMATION_WINDOW: usize = 12;
+
+/// Changed frames within the window from which a cursor counts as animated.
+/// Switching between two static cursors only changes one
+const MIN_ANIMATED_CHANGES: usize = 3;
+
+/// Bits two frames' [`perceptual_hash`]es may differ in and still count as
+/// the same image, so capture noise doesn't read as animation
+const MAX_STILL_HASH_DISTANCE: u32 = 2;
+
+/// Arrows in a row pointing the same way after which a [`CursorClassifier`]
+/// left on [`Handedness::Auto`] settles on that handedness
+pub const HANDEDNESS_STREAK: usize = 3;
+
+/// Iterator adaptor that classifies each `(rgba, width, height)` frame as it is
+/// pulled, see [`CursorDetectExt::detect_cursors`]
+#[derive(Debug, Clone)]
+pub struct DetectCursors<I> {
+    frames: I,
+}
+
+impl<I> Iterator for DetectCursors<I>
+where
+    I: Iterator<Item = (Vec<u8>, u32, u32)>,
+{
+    type Item = Option<CommonCursorType>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (image_data, width, height) = self.frames.next()?;
+        Some(CommonCursorType::detect_from_image(
+            &image_data,
+            width,
+            height,
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.frames.size_hint()
+    }
+}
+
+/// Iterator adaptor that classifies frames with an [`AnimatedCursorClassifier`],
+/// see [`CursorDetectExt::detect_cursors_animated`]
+#[derive(Debug, Clone)]
+pub struct DetectAnimatedCursors<I> {
+    frames: I,
+    classifier: AnimatedCursorClassifier,
+}
+
+impl<I> Iterator for DetectAnimatedCursors<I>
+where
+    I: Iterator<Item = (Vec<u8>, u32, u32)>,
+{
+    /// A frame's type and whether it's part of an animation
+    type Item = (Option<CommonCursorType>, bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (image_data, width, height) = self.frames.next()?;
+        let cursor_type = self.classifier.classify(&image_data, width, height);
+        Some((cursor_type, self.classifier.is_animating()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.frames.size_hint()
+    }
+}
+
+/// Detect cursor types straight from an iterator of captured bitmaps
+pub trait CursorDetectExt: Iterator<Item = (Vec<u8>, u32, u32)> + Sized {
+    /// Lazily run [`CommonCursorType::detect_from_image`] on every frame, so
+    /// e.g. `take(n)` only classifies the first `n`
+    fn detect_cursors(self) -> DetectCursors<Self> {
+        DetectCursors { frames: self }
+    }
+
+    /// Like [`Self::detect_cursors`], holding one type through animations
+    /// with a default [`AnimatedCursorClassifier`]. Each frame comes with
+    /// whether it's part of an animation
+    fn detect_cursors_animated(self) -> DetectAnimatedCursors<Self> {
+        DetectAnimatedCursors {
+            frames: self,
+            classifier: AnimatedCursorClassifier::default(),
+        }
+    }
+}
+
+impl<I> CursorDetectExt for I where I: Iterator<Item = (Vec<u8>, u32, u32)> {}
+
+/// Classifier for a stream of captured cursor frames that holds one label
+/// while the cursor animates.
+///
+/// Every frame of a spinner looks different, so detecting frames one at a
+/// time flickers between whatever each happens to resemble. The classifier
+/// compares each frame with the one before and, once enough of the last
+/// `window` frames changed, reports the animation's type for every frame
+/// until they stop changing: [`CommonCursorType::Progress`] for a spinner
+/// ring, standalone or as a badge, otherwise the type detected most often
+/// over the window. Static cursors are detected frame by frame as usual.
+#[derive(Debug, Clone)]
+pub struct AnimatedCursorClassifier {
+    window: usize,
+    /// Whether each recent frame differed from the one before it, with what it
+    /// was detected as on its own
+    recent: VecDeque<(bool, Option<CommonCursorType>)>,
+    /// Hash of the last frame, `Some(None)` if it had no opaque pixels
+    previous_hash: Option<Option<u64>>,
+    /// Type held while an animation is detected
+    held: Option<Option<CommonCursorType>>,
+}
+
+impl AnimatedCursorClassifier {
+    /// Classifier watching the last `window` frames for animation. Windows
+    /// too short to tell an animation from a single change are lengthened
+    pub fn new(window: usize) -> Self {
+        let window = window.max(MIN_ANIMATED_CHANGES + 1);
+        Self {
+            window,
+            recent: VecDeque::with_capacity(window),
+            previous_hash: None,
+            held: None,
+        }
+    }
+
+    /// Classify the next frame of the stream
+    pub fn classify(
+        &mut self,
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Option<CommonCursorType> {
+        let detected = CommonCursorType::detect_from_image(image_data, width, height);
+        let hash = perceptual_hash(image_data, width, height);
+        let changed = self
+            .previous_hash
+            .is_some_and(|previous| match (previous, hash) {
+                (Some(previous), Some(hash)) => {
+                    (previous ^ hash).count_ones() > MAX_STILL_HASH_DISTANCE
+                }
+                (previous, hash) => previous != hash,
+            });
+        self.previous_hash = Some(hash);
+
+        if self.recent.len() == self.window {
+            self.recent.pop_front();
+        }
+        self.recent.push_back((changed, detected));
+
+        let changes = self.recent.iter().filter(|(changed, _)| *changed).count();
+        if changes < MIN_ANIMATED_CHANGES {
+            self.held = None;
+            return detected;
+        }
+        *self
+            .held
+            .get_or_insert_with(|| animated_type(&self.recent, image_data, width, height))
+    }
+
+    /// Whether the recent frames are an animation, whose type is being held
+    pub fn is_animating(&self) -> bool {
+        self.held.is_some()
+    }
+
+    /// Forget the frames seen so far, e.g. when the stream seeks
+    pub fn reset(&mut self) {
+        self.recent.clear();
+        self.previous_hash = None;
+        self.held = None;
+    }
+}
+
+impl Default for AnimatedCursorClassifier {
+    fn default() -> Self {
+        Self::new(DEFAULT_ANIMATION_WINDOW)
+    }
+}
+
+/// Which way the system draws its cursors, see [`CursorClassifier`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Handedness {
+    /// Not known yet, told from the arrows seen
+    #[default]
+    Auto,
+    /// Cursors as the bundled SVGs draw them, the arrow pointing top-left
+    Right,
+    /// Left-handed mode, every cursor mirrored and the arrow pointing
+    /// top-right
+    Left,
+}
+
+/// Detects the cursors of one session, keeping track of its handedness.
+///
+/// Left-handed mode mirrors every system cursor, and a mirrored arrow or
+/// eyedropper doesn't match its own type. On [`Handedness::Auto`] the
+/// classifier tries each frame that isn't an upright arrow mirrored too, and
+/// once [`HANDEDNESS_STREAK`] arrows in a row pointed the same way it stops
+/// guessing: from then on every frame is detected upright, or flipped before
+/// detection for a left-handed session, as a fixed [`Handedness`] is from
+/// the start.
+#[derive(Debug, Clone, Default)]
+pub struct CursorClassifier {
+    setting: Handedness,
+    handedness: Handedness,
+    /// Arrows seen in a row while still on `Auto`, and whether they were
+    /// mirrored
+    streak: (usize, bool),
+}
+
+impl CursorClassifier {
+    pub fn new(handedness: Handedness) -> Self {
+        Self {
+            setting: handedness,
+            handedness,
+            streak: (0, false),
+        }
+    }
+
+    /// The session's handedness, [`Handedness::Auto`] until it's established
+    pub fn handedness(&self) -> Handedness {
+        self.handedness
+    }
+
+    /// Classify the next cursor of the session
+    pub fn classify(
+        &mut self,
+        image_data: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Option<CommonCursorType> {
+        let detect_mirrored = || {
+            mirror_horizontally(image_data, width, height)
+                .and_then(|mirrored| CommonCursorType::detect_from_image(&mirrored, width, height))
+        };
+
+        match self.handedness {
+            Handedness::Right => CommonCursorType::detect_from_image(image_data, width, height),
+            Handedness::Left => detect_mirrored(),
+            Handedness::Auto => {
+                let upright = CommonCursorType::detect_from_image(image_data, width, height);
+                if upright == Some(CommonCursorType::Arrow) {
+                    self.observe_arrow(false);
+                    return upright;
+                }
+
+                let mirrored = detect_mirrored();
+                if mirrored == Some(CommonCursorType::Arrow) {
+                    self.observe_arrow(true);
+                    return mirrored;
+                }
+                upright
+            }
+        }
+    }
+
+    /// Forget the established handedness, going back to the one configured
+    pub fn reset(&mut self) {
+        *self = Self::new(self.setting);
+    }
+
+    fn observe_arrow(&mut self, mirrored: bool) {
+        let (count, streak_mirrored) = self.streak;
+        let count = if streak_mirrored == mirrored {
+            count + 1
+        } else {
+            1
+        };
+        self.streak = (count, mirrored);
+
+        if count >= HANDEDNESS_STREAK {
+            self.handedness = if mirrored {
+                Handedness::Left
+            } else {
+                Handedness::Right
+            };
+        }
+    }
+}
+
+/// Puts a cursor bitmap back together from the tiles a capture backend
+/// delivers it in, e.g. the damaged rectangles of a partial-frame capture,
+/// and detects the cursor once every pixel of it has arrived.
+///
+/// Tiles may arrive in any order, overlap or reach past the region, whose
+/// top-left corner is at the origin of their coordinates. A pixel sent again
+/// overwrites what was there, so after the region is complete each tile
+/// updating it detects the cursor anew.
+#[derive(Debug, Clone)]
+pub struct CursorRegionAssembler {
+    size: Size,
+    /// RGBA pixels of the region, transparent where nothing arrived yet
+    pixels: Vec<u8>,
+    covered: Vec<bool>,
+    missing: usize,
+}
+
+impl CursorRegionAssembler {
+    /// Assembler for a cursor region of `size`
+    pub fn new(size: impl Into<Size>) -> Self {
+        let size = size.into();
+        let area = size.w as usize * size.h as usize;
+        Self {
+            size,
+            pixels: vec![0; area * 4],
+            covered: vec![false; area],
+            missing: area,
+        }
+    }
+
+    /// Copy a tile's RGBA pixels into the region and, if that completes it,
+    /// detect the cursor: `Ok(None)` while pixels are still missing,
+    /// otherwise what [`CommonCursorType::detect_from_image`] makes of the
+    /// region. The parts of `rect` outside the region are dropped. Errors if
+    /// `tile` doesn't hold `rect`'s pixels
+    pub fn add_tile(
+        &mut self,
+        rect: Rect,
+        tile: &[u8],
+    ) -> Result<Option<Option<CommonCursorType>>, CursorError> {
+        let (tile_w, tile_h) = (rect.size.w as usize, rect.size.h as usize);
+        if tile.len() != tile_w * tile_h * 4 {
+            return Err(CursorError::InvalidTile(format!(
+                "{} bytes for a {}x{} tile",
+                tile.len(),
+                tile_w,
+                tile_h
+            )));
+        }
+
+        // The tile's rows and columns that fall inside the region
+        let clip = |origin: i32, len: usize, region: u32| {
+            let start = (-(origin as i64)).clamp(0, len as i64) as usize;
+            let end = (region as i64 - origin as i64).clamp(start as i64, len as i64) as usize;
+            start..end
+        };
+        let columns = clip(rect.origin.x, tile_w, self.size.w);
+        for row in clip(rect.origin.y, tile_h, self.size.h) {
+            let y = (rect.origin.y as i64 + row as i64) as usize;
+            let x = (rect.origin.x as i64 + columns.start as i64) as usize;
+            let first = y * self.size.w as usize + x;
+            let source = (row * tile_w + columns.start) * 4;
+
+            self.pixels[first * 4..(first + columns.len()) * 4]
+                .copy_from_slice(&tile[source..source + columns.len() * 4]);
+            for covered in &mut self.covered[first..first + columns.len()] {
+                if !*covered {
+                    *covered = true;
+                    self.missing -= 1;
+                }
+            }
+        }
+
+        Ok(self
+            .is_complete()
+            .then(|| CommonCursorType::detect_from_image(&self.pixels, self.size.w, self.size.h)))
+    }
+
+    /// Whether every pixel of the region has arrived
+    pub fn is_complete(&self) -> bool {
+        self.missing == 0
+    }
+
+    /// Share of the region's pixels that arrived, 1.0 once it's complete
+    pub fn coverage(&self) -> f32 {
+        if self.covered.is_empty() {
+            return 1.0;
+        }
+        1.0 - self.missing as f32 / self.covered.len() as f32
+    }
+
+    /// The region's RGBA pixels, transparent where none arrived yet
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Forget every tile, e.g. when the next cursor starts arriving
+    pub fn reset(&mut self) {
+        self.pixels.fill(0);
+        self.covered.fill(false);
+        self.missing = self.covered.len();
+    }
+}
+
+/// Type to hold for an animation that was just detected, with `frame` its
+/// latest frame
+fn animated_type(
+    recent: &VecDeque<(bool, Option<CommonCursorType>)>,
+    frame: &[u8],
+    width: u32,
+    height: u32,
+) -> Option<CommonCursorType> {
+    if detect_spinner_progress(frame, width, height).is_some() {
+        return Some(CommonCursorType::Progress);
+    }
+
+    // Ties go to the type declared last, so the pick doesn't depend on frame order
+    CommonCursorType::ALL
+        .iter()
+        .map(|&cursor_type| {
+            let count = recent
+                .iter()
+                .filter(|(_, detected)| *detected == Some(cursor_type))
+                .count();
+            (cursor_type, count)
+        })
+        .filter(|&(_, count)| count > 0)
+        .max_by_key(|&(_, count)| count)
+        .map(|(cursor_type, _)| cursor_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::rendering::rasterize_cursor;
+    #[cfg(feature = "svg")]
+    use crate::cursor::rendering::rasterize_svg;
+    use std::cell::Cell;
+
+    fn frame(cursor_type: CommonCursorType) -> (Vec<u8>, u32, u32) {
+        (
+            rasterize_cursor(&cursor_type, 32).unwrap().into_raw(),
+            32,
+            32,
+        )
+    }
+
+    #[test]
+    fn test_detect_cursors_maps_each_frame() {
+        let detected: Vec<_> = vec![
+            frame(CommonCursorType::IBeam),
+            (vec![0; 32 * 32 * 4], 32, 32),
+            frame(CommonCursorType::Crosshair),
+        ]
+        .into_iter()
+        .detect_cursors()
+        .collect();
+
+        assert_eq!(
+            detected,
+            vec![
+                Some(CommonCursorType::IBeam),
+                Some(CommonCursorType::Blank),
+                Some(CommonCursorType::Crosshair)
+            ]
+        );
+    }
+
+    /// A busy spinner, a three-quarter ring turned by `degrees`
+    #[cfg(feature = "svg")]
+    fn spinner(degrees: u32) -> (Vec<u8>, u32, u32) {
+        let svg = format!(
+            r##"<svg width="24" height="24" xmlns="http://www.w3.org/2000/svg">
+                <g transform="rotate({} 12 12)">
+                    <path d="M12 4 A8 8 0 1 1 4 12" stroke="#000000" stroke-width="3" fill="none"/>
+                </g>
+            </svg>"##,
+            degrees
+        );
+        (
+            rasterize_svg(svg.as_bytes(), 32).unwrap().into_raw(),
+            32,
+            32,
+        )
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_animated_classifier_holds_spinner_type() {
+        let spinning = (0..16).map(|i| spinner(i % 8 * 45));
+        let frames: Vec<_> = spinning
+            .chain(std::iter::repeat_with(|| frame(CommonCursorType::Arrow)).take(12))
+            .collect();
+
+        // Alone, the frames read as whatever each turn resembles
+        let single: Vec<_> = frames[..8].iter().cloned().detect_cursors().collect();
+        assert!(single.windows(2).any(|pair| pair[0] != pair[1]));
+
+        let detected: Vec<_> = frames.into_iter().detect_cursors_animated().collect();
+        // Held from the third change on, through the rest of the animation
+        assert_eq!(
+            detected[..3]
+                .iter()
+                .filter(|(_, animating)| *animating)
+                .count(),
+            0
+        );
+        for &(cursor_type, animating) in &detected[3..16] {
+            assert_eq!(cursor_type, Some(CommonCursorType::Progress));
+            assert!(animating);
+        }
+        // Released once the window has seen too few changes
+        assert_eq!(
+            detected.last(),
+            Some(&(Some(CommonCursorType::Arrow), false))
+        );
+    }
+
+    #[test]
+    fn test_animated_classifier_ignores_single_switches() {
+        let mut classifier = AnimatedCursorClassifier::default();
+        for cursor_type in [
+            CommonCursorType::Arrow,
+            CommonCursorType::Arrow,
+            CommonCursorType::IBeam,
+            CommonCursorType::IBeam,
+            CommonCursorType::PointingHand,
+        ] {
+            let (image_data, width, height) = frame(cursor_type);
+            assert_eq!(
+                classifier.classify(&image_data, width, height),
+                Some(cursor_type)
+            );
+            assert!(!classifier.is_animating());
+        }
+    }
+
+    #[test]
+    fn test_assembles_arrow_from_two_halves() {
+        let (arrow, width, height) = frame(CommonCursorType::Arrow);
+        let half = (width * 4 * height / 2) as usize;
+        let mut assembler = CursorRegionAssembler::new((width, height));
+
+        let top = Rect::new((0, 0), (width, height / 2));
+        assert_eq!(assembler.add_tile(top, &arrow[..half]), Ok(None));
+        assert!(!assembler.is_complete());
+        assert_eq!(assembler.coverage(), 0.5);
+
+        // Sending the same half again doesn't complete the region
+        assert_eq!(assembler.add_tile(top, &arrow[..half]), Ok(None));
+        let bottom = Rect::new((0, height as i32 / 2), (width, height / 2));
+        assert_eq!(
+            assembler.add_tile(bottom, &arrow[half..]),
+            Ok(Some(Some(CommonCursorType::Arrow)))
+        );
+        assert!(assembler.is_complete());
+        assert_eq!(assembler.pixels(), &arrow[..]);
+
+        assembler.reset();
+        assert_eq!(assembler.coverage(), 0.0);
+    }
+
+    #[test]
+    fn test_assembler_clips_tiles_to_the_region() {
+        let (ibeam, width, height) = frame(CommonCursorType::IBeam);
+        let mut assembler = CursorRegionAssembler::new((width, height));
+
+        // One damaged rect overhanging the region on every side, the cursor
+        // in its middle
+        let mut damaged = vec![0u8; 40 * 40 * 4];
+        for (row, pixels) in ibeam.chunks_exact(width as usize * 4).enumerate() {
+            let start = ((row + 4) * 40 + 4) * 4;
+            damaged[start..start + pixels.len()].copy_from_slice(pixels);
+        }
+        assert_eq!(
+            assembler.add_tile(Rect::new((-4, -4), (40, 40)), &damaged),
+            Ok(Some(Some(CommonCursorType::IBeam)))
+        );
+        assert_eq!(assembler.pixels(), &ibeam[..]);
+
+        assert!(matches!(
+            assembler.add_tile(Rect::new((0, 0), (2, 2)), &[0; 4]),
+            Err(CursorError::InvalidTile(_))
+        ));
+        // Entirely outside, nothing to copy
+        assert_eq!(
+            assembler.add_tile(Rect::new((64, 0), (1, 1)), &[255; 4]),
+            Ok(Some(Some(CommonCursorType::IBeam)))
+        );
+    }
+
+    fn mirrored(cursor_type: CommonCursorType) -> (Vec<u8>, u32, u32) {
+        let (image_data, width, height) = frame(cursor_type);
+        (
+            mirror_horizontally(&image_data, width, height).unwrap(),
+            width,
+            height,
+        )
+    }
+
+    #[test]
+    fn test_classifier_settles_on_left_handedness() {
+        let (eyedropper, width, height) = mirrored(CommonCursorType::Eyedropper);
+        let (arrow, _, _) = mirrored(CommonCursorType::Arrow);
+
+        let mut classifier = CursorClassifier::default();
+        assert_ne!(
+            classifier.classify(&eyedropper, width, height),
+            Some(CommonCursorType::Eyedropper)
+        );
+        // An upright arrow in between starts the count over
+        for (image_data, handedness) in [
+            (&arrow, Handedness::Auto),
+            (&frame(CommonCursorType::Arrow).0, Handedness::Auto),
+            (&arrow, Handedness::Auto),
+            (&arrow, Handedness::Auto),
+            (&arrow, Handedness::Left),
+        ] {
+            assert_eq!(
+                classifier.classify(image_data, width, height),
+                Some(CommonCursorType::Arrow)
+            );
+            assert_eq!(classifier.handedness(), handedness);
+        }
+
+        // Everything is flipped from now on, not only arrows
+        assert_eq!(
+            classifier.classify(&eyedropper, width, height),
+            Some(CommonCursorType::Eyedropper)
+        );
+        classifier.reset();
+        assert_eq!(classifier.handedness(), Handedness::Auto);
+
+        let mut right_handed = CursorClassifier::new(Handedness::Right);
+        assert_eq!(right_handed.classify(&arrow, width, height), None);
+    }
+
+    #[test]
+    fn test_detect_cursors_is_lazy() {
+        let pulled = Cell::new(0);
+        let frames = std::iter::repeat_with(|| {
+            pulled.set(pulled.get() + 1);
+            frame(CommonCursorType::Arrow)
+        });
+
+        let first: Vec<_> = frames.detect_cursors().take(2).collect();
+        assert_eq!(first, vec![Some(CommonCursorType::Arrow); 2]);
+        assert_eq!(pulled.get(), 2);
+    }
+}
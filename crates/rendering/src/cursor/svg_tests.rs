@@ -0,0 +1,2291 @@
+#[cfg(test)]
+mod tests {
+    use super::super::calibration::Calibration;
+    use super::super::components::*;
+    use super::super::corpus_tests::tests::{fixture, fixture_path};
+    use super::super::detection::*;
+    #[cfg(feature = "svg")]
+    use super::super::features::normalize_translation;
+    #[cfg(feature = "svg")]
+    use super::super::fingerprint::perceptual_hash;
+    use super::super::geometry::{Point, Rect};
+    use super::super::loading::*;
+    #[cfg(feature = "svg")]
+    use super::super::rendering::rasterize_svg;
+    use super::super::rendering::{rasterize_cursor, CursorRenderSource};
+    use super::super::CursorError;
+    use std::borrow::Cow;
+
+    fn fill_rect(image_data: &mut [u8], width: u32, x0: u32, y0: u32, x1: u32, y1: u32) {
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let idx = ((y * width + x) * 4) as usize;
+                image_data[idx + 3] = 255; // A (opaque)
+            }
+        }
+    }
+
+    #[test]
+    fn test_arrow_cursor_detection() {
+        // Create a simple arrow pattern: mostly pixels in top-left
+        let width = 32;
+        let height = 32;
+        let mut image_data = vec![0u8; (width * height * 4) as usize];
+
+        // Add some pixels in the top-left area to simulate arrow
+        for y in 0..height / 3 {
+            for x in 0..width / 3 {
+                let idx = ((y * width + x) * 4) as usize;
+                if idx + 3 < image_data.len() {
+                    image_data[idx] = 0; // R
+                    image_data[idx + 1] = 0; // G
+                    image_data[idx + 2] = 0; // B
+                    image_data[idx + 3] = 255; // A (opaque)
+                }
+            }
+        }
+
+        let detected = CommonCursorType::detect_from_image(&image_data, width, height);
+        assert_eq!(detected, Some(CommonCursorType::Arrow));
+    }
+
+    #[test]
+    fn test_ibeam_cursor_detection() {
+        // Create a vertical line pattern for I-beam
+        let width: u32 = 16;
+        let height = 32;
+        let mut image_data = vec![0u8; (width * height * 4) as usize];
+
+        // Add vertical line in center
+        let center_x = width / 2;
+        for y in 0..height {
+            for x in center_x.saturating_sub(1)..=center_x + 1 {
+                let idx = ((y * width + x) * 4) as usize;
+                if idx + 3 < image_data.len() {
+                    image_data[idx] = 0; // R
+                    image_data[idx + 1] = 0; // G
+                    image_data[idx + 2] = 0; // B
+                    image_data[idx + 3] = 255; // A (opaque)
+                }
+            }
+        }
+
+        let detected = CommonCursorType::detect_from_image(&image_data, width, height);
+        assert_eq!(detected, Some(CommonCursorType::IBeam));
+    }
+
+    #[test]
+    fn test_crosshair_cursor_detection() {
+        // Create a cross pattern
+        let width = 24;
+        let height = 24;
+        let mut image_data = vec![0u8; (width * height * 4) as usize];
+
+        let center_x = width / 2;
+        let center_y = height / 2;
+
+        // Horizontal line
+        for x in 0..width {
+            let idx = ((center_y * width + x) * 4) as usize;
+            if idx + 3 < image_data.len() {
+                image_data[idx + 3] = 255; // A (opaque)
+            }
+        }
+
+        // Vertical line
+        for y in 0..height {
+            let idx = ((y * width + center_x) * 4) as usize;
+            if idx + 3 < image_data.len() {
+                image_data[idx + 3] = 255; // A (opaque)
+            }
+        }
+
+        let detected = CommonCursorType::detect_from_image(&image_data, width, height);
+        assert_eq!(detected, Some(CommonCursorType::Crosshair));
+    }
+
+    #[test]
+    fn test_hand_cursor_detection() {
+        // Create a pattern with more pixels in bottom half (typical of hand cursors)
+        let width = 24;
+        let height = 24;
+        let mut image_data = vec![0u8; (width * height * 4) as usize];
+
+        let mid_y = height / 2;
+
+        // Add more pixels in the bottom half
+        for y in mid_y..height {
+            for x in 4..width - 4 {
+                let idx = ((y * width + x) * 4) as usize;
+                if idx + 3 < image_data.len() {
+                    image_data[idx] = 0; // R
+                    image_data[idx + 1] = 0; // G
+                    image_data[idx + 2] = 0; // B
+                    image_data[idx + 3] = 255; // A (opaque)
+                }
+            }
+        }
+
+        // Add some pixels in top half but fewer
+        for y in 0..mid_y / 2 {
+            for x in 8..width - 8 {
+                let idx = ((y * width + x) * 4) as usize;
+                if idx + 3 < image_data.len() {
+                    image_data[idx + 3] = 255; // A (opaque)
+                }
+            }
+        }
+
+        let detected = CommonCursorType::detect_from_image(&image_data, width, height);
+        assert_eq!(detected, Some(CommonCursorType::PointingHand));
+    }
+
+    #[test]
+    fn test_resize_cursor_detection() {
+        // Create a pattern with corner pixels (typical of resize cursors)
+        let width = 24;
+        let height = 24;
+        let mut image_data = vec![0u8; (width * height * 4) as usize];
+
+        // Add pixels in corners
+        let corner_size = width / 4;
+
+        // Top-left corner
+        for y in 0..corner_size {
+            for x in 0..corner_size {
+                let idx = ((y * width + x) * 4) as usize;
+                if idx + 3 < image_data.len() {
+                    image_data[idx + 3] = 255; // A (opaque)
+                }
+            }
+        }
+
+        // Bottom-right corner
+        for y in (height - corner_size)..height {
+            for x in (width - corner_size)..width {
+                let idx = ((y * width + x) * 4) as usize;
+                if idx + 3 < image_data.len() {
+                    image_data[idx + 3] = 255; // A (opaque)
+                }
+            }
+        }
+
+        let detected = CommonCursorType::detect_from_image(&image_data, width, height);
+        assert_eq!(detected, Some(CommonCursorType::ResizeNWSE));
+    }
+
+    #[test]
+    fn test_resize_cursors_with_handle_square() {
+        // The square at the center spreads pixels into the cross bands and off
+        // the diagonal, but the arrowheads still tell the orientation
+        for (dir, expected) in [
+            ("resize-ew", CommonCursorType::ResizeEW),
+            ("resize-nwse", CommonCursorType::ResizeNWSE),
+        ] {
+            for size in [32, 48] {
+                let image = fixture(dir, "handle-square", size);
+                let ranked = CommonCursorType::detect_ranked(image.as_raw(), size, size);
+                assert_eq!(ranked[0].0, expected, "{dir} at {size}px");
+                assert!(ranked[0].1 >= MATCH_THRESHOLD, "{dir} at {size}px");
+            }
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_busy_cursors_keep_their_base_type() {
+        for size in [32, 48] {
+            let hand = fixture("pointing-hand", "busy-spinner", size);
+            let analysis = analyze_cursor(hand.as_raw(), size, size);
+            assert_eq!(analysis.cursor_type, Some(CommonCursorType::PointingHand));
+            assert!(analysis.busy);
+        }
+
+        let ibeam = rasterize_svg(
+            br##"<svg width="32" height="32" viewBox="0 0 32 32" xmlns="http://www.w3.org/2000/svg">
+                <rect x="5" y="3" width="8" height="2" fill="#000000"/>
+                <rect x="8" y="3" width="2" height="22" fill="#000000"/>
+                <rect x="5" y="23" width="8" height="2" fill="#000000"/>
+                <circle cx="24" cy="24" r="4" stroke="#000000" stroke-width="2" fill="none"/>
+            </svg>"##,
+            32,
+        )
+        .unwrap();
+        let analysis = analyze_cursor(ibeam.as_raw(), 32, 32);
+        assert_eq!(analysis.cursor_type, Some(CommonCursorType::IBeam));
+        assert!(analysis.busy);
+
+        // A busy arrow stays a progress cursor, and nothing else bundled is busy
+        for &cursor_type in CommonCursorType::ALL {
+            let image = rasterize_cursor(&cursor_type, 32).unwrap();
+            let analysis = analyze_cursor(image.as_raw(), 32, 32);
+            assert_eq!(analysis.busy, cursor_type == CommonCursorType::Progress);
+        }
+    }
+
+    #[test]
+    fn test_magnified_cursors() {
+        let arrow = fixture("arrow", "magnified", 200);
+        assert_eq!(
+            CommonCursorType::detect_from_image(arrow.as_raw(), 200, 200),
+            Some(CommonCursorType::Arrow)
+        );
+        let analysis = analyze_cursor(arrow.as_raw(), 200, 200);
+        assert!(analysis.magnified);
+        assert_eq!(analysis.cursor_type, Some(CommonCursorType::Arrow));
+
+        // Too big to segment as they are, the badge cursors are told apart
+        // once shrunk back
+        let copy = rasterize_cursor(&CommonCursorType::Copy, 32).unwrap();
+        let magnified = image::imageops::blur(
+            &image::imageops::resize(&copy, 200, 200, image::imageops::FilterType::Triangle),
+            3.0,
+        );
+        assert!(is_magnified(magnified.as_raw(), 200, 200));
+        assert_eq!(
+            CommonCursorType::detect_from_image(magnified.as_raw(), 200, 200),
+            Some(CommonCursorType::Copy)
+        );
+
+        for &cursor_type in CommonCursorType::ALL {
+            let image = rasterize_cursor(&cursor_type, 32).unwrap();
+            assert!(!analyze_cursor(image.as_raw(), 32, 32).magnified);
+        }
+    }
+
+    #[test]
+    fn test_hollow_cursors_match_their_outline() {
+        for size in [32, 48] {
+            let image = fixture("arrow", "hollow-cyan", size);
+            let data = image.as_raw();
+
+            // Only the outline is opaque, filling it in recovers the arrow
+            let filled = fill_hollow(data, size, size).unwrap();
+            let opaque = |data: &[u8]| data.chunks_exact(4).filter(|p| p[3] > 128).count();
+            assert!(opaque(&filled) > opaque(data) * 3 / 2);
+
+            let arrow = Some(CommonCursorType::Arrow);
+            assert_eq!(CommonCursorType::detect_from_image(data, size, size), arrow);
+            assert_eq!(
+                CommonCursorType::detect_from_image_in(
+                    data,
+                    size,
+                    size,
+                    &mut DetectionScratch::new()
+                ),
+                arrow
+            );
+            let within = CommonCursorType::detect_within(
+                data,
+                size,
+                size,
+                std::time::Duration::from_secs(60),
+            );
+            assert_eq!(within.map(|(cursor_type, _)| cursor_type), arrow);
+        }
+
+        // Filled cursors and broken outlines have nothing to fill
+        let arrow = rasterize_cursor(&CommonCursorType::Arrow, 32).unwrap();
+        assert_eq!(fill_hollow(arrow.as_raw(), 32, 32), None);
+        let mut open = vec![0u8; 24 * 24 * 4];
+        fill_rect(&mut open, 24, 4, 4, 20, 6);
+        fill_rect(&mut open, 24, 4, 4, 6, 20);
+        fill_rect(&mut open, 24, 18, 4, 20, 20);
+        assert_eq!(fill_hollow(&open, 24, 24), None);
+    }
+
+    #[test]
+    fn test_unknown_cursor_pattern() {
+        // Create a random pattern that shouldn't match any known cursor
+        let width = 32;
+        let height = 32;
+        let mut image_data = vec![0u8; (width * height * 4) as usize];
+
+        // Add a few random pixels
+        for i in (0..100).step_by(10) {
+            if i * 4 + 3 < image_data.len() {
+                image_data[i * 4 + 3] = 255; // A (opaque)
+            }
+        }
+
+        let detected = CommonCursorType::detect_from_image(&image_data, width, height);
+        assert_eq!(detected, None);
+    }
+
+    #[test]
+    fn test_empty_image() {
+        // Test with completely transparent image
+        let width = 32;
+        let height = 32;
+        let image_data = vec![0u8; (width * height * 4) as usize]; // All transparent
+
+        let detected = CommonCursorType::detect_from_image(&image_data, width, height);
+        assert_eq!(detected, Some(CommonCursorType::Blank));
+    }
+
+    #[test]
+    fn test_blank_cursor_detection() {
+        let blank = vec![0u8; 32 * 32 * 4];
+        let mut scratch = DetectionScratch::new();
+        assert_eq!(
+            CommonCursorType::detect_from_image_in(&blank, 32, 32, &mut scratch),
+            Some(CommonCursorType::Blank)
+        );
+        assert_eq!(
+            CommonCursorType::detect_within(&blank, 32, 32, std::time::Duration::ZERO),
+            Some((CommonCursorType::Blank, 1.0))
+        );
+        let translated = DetectionConfig {
+            normalize_translation: true,
+            ..DetectionConfig::strict(0.2)
+        };
+        assert_eq!(
+            CommonCursorType::detect_with_config(&blank, 32, 32, &translated),
+            Some(CommonCursorType::Blank)
+        );
+
+        // Blank at any size, even below what shapes are told apart at
+        assert_eq!(
+            CommonCursorType::detect_from_image(&[0; 4], 1, 1),
+            Some(CommonCursorType::Blank)
+        );
+
+        // A stray faint pixel is too little to detect, but not blank either
+        let mut stray = blank.clone();
+        stray[3] = 1;
+        assert_eq!(CommonCursorType::detect_from_image(&stray, 32, 32), None);
+
+        // Neither is a buffer too short for its size, or without any pixels
+        assert_eq!(CommonCursorType::detect_from_image(&blank, 64, 64), None);
+        assert_eq!(CommonCursorType::detect_from_image(&[], 0, 0), None);
+    }
+
+    #[test]
+    fn test_svg_filename_mapping() {
+        assert_eq!(CommonCursorType::Arrow.svg_filename(), "arrow.svg");
+        assert_eq!(CommonCursorType::IBeam.svg_filename(), "ibeam.svg");
+        assert_eq!(CommonCursorType::Crosshair.svg_filename(), "crosshair.svg");
+        assert_eq!(
+            CommonCursorType::PointingHand.svg_filename(),
+            "pointing-hand.svg"
+        );
+        assert_eq!(
+            CommonCursorType::ResizeNWSE.svg_filename(),
+            "resize-nwse.svg"
+        );
+        assert_eq!(CommonCursorType::ResizeEW.svg_filename(), "resize-ew.svg");
+    }
+
+    #[test]
+    fn test_svg_loading() {
+        // Test that we can load SVG content for all cursor types
+        for &cursor_type in CommonCursorType::ALL {
+            let svg_content = load_cursor_svg(&cursor_type);
+            assert!(
+                svg_content.is_some(),
+                "Failed to load SVG for {:?}",
+                cursor_type
+            );
+
+            if let Some(content) = svg_content {
+                assert!(
+                    !content.is_empty(),
+                    "SVG content is empty for {:?}",
+                    cursor_type
+                );
+                // Basic SVG validation
+                let content_str = String::from_utf8_lossy(&content);
+                assert!(
+                    content_str.contains("<svg"),
+                    "Invalid SVG content for {:?}",
+                    cursor_type
+                );
+                assert!(
+                    content_str.contains("</svg>"),
+                    "Invalid SVG content for {:?}",
+                    cursor_type
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[allow(clippy::needless_range_loop)]
+    fn test_cursor_pattern_edge_cases() {
+        // Test with very small images
+        let width = 4;
+        let height = 4;
+        let mut image_data = vec![0u8; (width * height * 4) as usize];
+
+        // Fill with opaque pixels
+        for i in 0..image_data.len() {
+            if i % 4 == 3 {
+                image_data[i] = 255; // Alpha channel
+            }
+        }
+
+        let detected = CommonCursorType::detect_from_image(&image_data, width, height);
+        // Should not crash and return None for very small images
+        assert_eq!(detected, None);
+    }
+
+    #[test]
+    fn test_cursor_pattern_with_invalid_data() {
+        // Test with insufficient data
+        let width = 32;
+        let height = 32;
+        let image_data = vec![0u8; 10]; // Much smaller than required
+
+        let detected = CommonCursorType::detect_from_image(&image_data, width, height);
+        // Should handle gracefully and return None
+        assert_eq!(detected, None);
+    }
+
+    #[test]
+    fn test_component_labeling() {
+        let width = 24;
+        let height = 24;
+        let mut image_data = vec![0u8; (width * height * 4) as usize];
+
+        // A large block, a small block, and a pixel diagonally touching the large one
+        fill_rect(&mut image_data, width, 2, 2, 10, 10);
+        fill_rect(&mut image_data, width, 16, 16, 20, 20);
+        fill_rect(&mut image_data, width, 10, 10, 11, 11);
+
+        let map = ComponentMap::from_rgba(&image_data, width, height).unwrap();
+        let components = map.components();
+        assert_eq!(components.len(), 2);
+
+        assert_eq!(components[0].area, 65);
+        assert_eq!(
+            components[0].bbox,
+            ComponentBounds {
+                min_x: 2,
+                min_y: 2,
+                max_x: 10,
+                max_y: 10
+            }
+        );
+
+        assert_eq!(components[1].area, 16);
+        assert_eq!(components[1].centroid, (17.5, 17.5));
+        assert_eq!(map.component_at(17, 17), Some(1));
+        assert_eq!(map.component_at(12, 12), None);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_validate_cursor_svg() {
+        let meta =
+            validate_cursor_svg(&load_cursor_svg(&CommonCursorType::Arrow).unwrap()).unwrap();
+        assert_eq!((meta.width, meta.height), (24.0, 24.0));
+        assert!(!meta.over_budget);
+
+        assert!(matches!(
+            validate_cursor_svg(b"<svg width=\"32\" height=\"32\"><rect"),
+            Err(CursorError::InvalidSvg(_))
+        ));
+        // Parses, but a 2000x10 banner isn't a cursor
+        assert!(matches!(
+            validate_cursor_svg(
+                br#"<svg width="2000" height="10" xmlns="http://www.w3.org/2000/svg"><rect width="10" height="10"/></svg>"#
+            ),
+            Err(CursorError::InvalidSvg(_))
+        ));
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_validate_oversized_cursor_svg() {
+        let svg_of_len = |len: usize| {
+            let head = br#"<svg width="32" height="32" xmlns="http://www.w3.org/2000/svg"><rect width="10" height="10"/><!--"#;
+            let tail = b"--></svg>";
+            let mut svg = head.to_vec();
+            svg.resize(len - tail.len(), b' ');
+            svg.extend_from_slice(tail);
+            svg
+        };
+
+        let heavy = validate_cursor_svg(&svg_of_len(CURSOR_SVG_BUDGET_BYTES + 1)).unwrap();
+        assert!(heavy.over_budget);
+        assert_eq!(heavy.byte_len, CURSOR_SVG_BUDGET_BYTES + 1);
+
+        assert_eq!(
+            validate_cursor_svg(&svg_of_len(MAX_CURSOR_SVG_BYTES + 1)),
+            Err(CursorError::SvgTooLarge {
+                len: MAX_CURSOR_SVG_BYTES + 1,
+                max: MAX_CURSOR_SVG_BYTES,
+            })
+        );
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_registry_rejects_invalid_svg() {
+        let mut registry = CustomCursorRegistry::new();
+        assert!(registry.register("broken", b"not an svg".to_vec()).is_err());
+        assert!(registry.is_empty());
+
+        let arrow = load_cursor_svg(&CommonCursorType::Arrow).unwrap();
+        registry.register("arrow", arrow.clone()).unwrap();
+        assert_eq!(registry.svg("arrow"), Some(arrow.as_slice()));
+        assert!(registry.svg("broken").is_none());
+    }
+
+    #[test]
+    fn test_component_holes() {
+        let width = 16;
+        let height = 16;
+        let mut image_data = vec![0u8; (width * height * 4) as usize];
+
+        // A square outline encloses a 4x4 hole
+        fill_rect(&mut image_data, width, 4, 4, 10, 10);
+        for y in 5..9 {
+            for x in 5..9 {
+                image_data[((y * width + x) * 4 + 3) as usize] = 0;
+            }
+        }
+
+        let map = ComponentMap::from_rgba(&image_data, width, height).unwrap();
+        assert_eq!(map.components().len(), 1);
+        assert_eq!(map.enclosed_hole_area(0), 16);
+    }
+
+    #[test]
+    fn test_badge_family_detection() {
+        // Arrow body in the top-left with a plus badge in the bottom-right
+        let width = 32;
+        let height = 32;
+        let mut image_data = vec![0u8; (width * height * 4) as usize];
+
+        for y in 2..22 {
+            fill_rect(&mut image_data, width, 2, y, 2 + (y - 2) / 2 + 1, y + 1);
+        }
+        fill_rect(&mut image_data, width, 23, 19, 26, 30);
+        fill_rect(&mut image_data, width, 19, 23, 30, 26);
+
+        let map = ComponentMap::from_rgba(&image_data, width, height).unwrap();
+        assert_eq!(classify_badge(&map), Some(BadgeShape::Plus));
+
+        let detected = CommonCursorType::detect_from_image(&image_data, width, height);
+        assert_eq!(detected, Some(CommonCursorType::Copy));
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_detect_ranked_orders_by_score() {
+        let svg = load_cursor_svg(&CommonCursorType::IBeam).unwrap();
+        let image = rasterize_svg(&svg, 32).unwrap();
+
+        let ranked = CommonCursorType::detect_ranked(image.as_raw(), 32, 32);
+        assert_eq!(ranked[0].0, CommonCursorType::IBeam);
+        assert!(ranked[0].1 >= MATCH_THRESHOLD);
+        assert!(ranked.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+
+        // Blank images have nothing to rank but their blankness
+        let blank = vec![0u8; 32 * 32 * 4];
+        assert_eq!(
+            CommonCursorType::detect_ranked(&blank, 32, 32),
+            [(CommonCursorType::Blank, 1.0)]
+        );
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_strict_detection_rejects_close_scores() {
+        let svg = load_cursor_svg(&CommonCursorType::IBeam).unwrap();
+        let image = rasterize_svg(&svg, 32).unwrap();
+        let ranked = CommonCursorType::detect_ranked(image.as_raw(), 32, 32);
+        let gap = ranked[0].1 - ranked[1].1;
+
+        assert_eq!(
+            CommonCursorType::detect_with_config(
+                image.as_raw(),
+                32,
+                32,
+                &DetectionConfig::strict(gap * 0.5)
+            ),
+            Some(CommonCursorType::IBeam)
+        );
+        assert_eq!(
+            CommonCursorType::detect_with_config(
+                image.as_raw(),
+                32,
+                32,
+                &DetectionConfig::strict(gap * 2.0)
+            ),
+            None
+        );
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_classify_outcomes() {
+        let svg = load_cursor_svg(&CommonCursorType::IBeam).unwrap();
+        let image = rasterize_svg(&svg, 32).unwrap();
+        let ranked = CommonCursorType::detect_ranked(image.as_raw(), 32, 32);
+        let gap = ranked[0].1 - ranked[1].1;
+
+        assert_eq!(
+            classify(image.as_raw(), 32, 32),
+            DetectionOutcome::Detected {
+                ty: CommonCursorType::IBeam,
+                confidence: ranked[0].1
+            }
+        );
+        let config = DetectionConfig::strict(gap * 2.0);
+        assert_eq!(
+            classify_with_config(image.as_raw(), 32, 32, &config),
+            DetectionOutcome::Ambiguous {
+                candidates: ranked[..2].to_vec()
+            }
+        );
+        let config = DetectionConfig {
+            min_score: 1.1,
+            ..DetectionConfig::default()
+        };
+        assert_eq!(
+            classify_with_config(image.as_raw(), 32, 32, &config),
+            DetectionOutcome::LowConfidence {
+                best: Some(CommonCursorType::IBeam),
+                confidence: ranked[0].1
+            }
+        );
+
+        // A bar per consumer, under one config
+        let config = DetectionConfig::strict(AMBIGUOUS_MARGIN);
+        let preview = classify_with_min_confidence(image.as_raw(), 32, 32, &config, 0.0);
+        let export = classify_with_min_confidence(image.as_raw(), 32, 32, &config, 1.1);
+        assert_eq!(preview, classify(image.as_raw(), 32, 32));
+        assert_eq!(
+            export,
+            DetectionOutcome::LowConfidence {
+                best: Some(CommonCursorType::IBeam),
+                confidence: ranked[0].1
+            }
+        );
+        let config = DetectionConfig::strict(gap * 2.0);
+        assert_eq!(
+            classify_with_min_confidence(image.as_raw(), 32, 32, &config, ranked[0].1),
+            classify_with_config(image.as_raw(), 32, 32, &config)
+        );
+        assert_eq!(
+            classify_with_min_confidence(image.as_raw(), 32, 32, &config, 1.1),
+            export
+        );
+
+        // One classification, gated per consumer
+        let outcome = classify(image.as_raw(), 32, 32);
+        assert_eq!(outcome.clone().with_min_confidence(0.0), outcome);
+        assert_eq!(outcome.clone().with_min_confidence(ranked[0].1), outcome);
+        assert_eq!(
+            outcome.with_min_confidence(1.1),
+            DetectionOutcome::LowConfidence {
+                best: Some(CommonCursorType::IBeam),
+                confidence: ranked[0].1
+            }
+        );
+        let ambiguous =
+            classify_with_config(image.as_raw(), 32, 32, &DetectionConfig::strict(gap * 2.0));
+        assert_eq!(ambiguous.clone().with_min_confidence(0.5), ambiguous);
+        assert_eq!(
+            ambiguous.with_min_confidence(1.1),
+            DetectionOutcome::LowConfidence {
+                best: Some(CommonCursorType::IBeam),
+                confidence: ranked[0].1
+            }
+        );
+        let low = DetectionOutcome::LowConfidence {
+            best: None,
+            confidence: 0.0,
+        };
+        assert_eq!(low.clone().with_min_confidence(0.0), low);
+        assert_eq!(
+            DetectionOutcome::Blank.with_min_confidence(1.1),
+            DetectionOutcome::Blank
+        );
+
+        // The arrow a badge cursor's body scores as doesn't make it ambiguous
+        let copy = rasterize_cursor(&CommonCursorType::Copy, 32).unwrap();
+        assert_eq!(
+            classify(copy.as_raw(), 32, 32),
+            DetectionOutcome::Detected {
+                ty: CommonCursorType::Copy,
+                confidence: 1.0
+            }
+        );
+
+        assert_eq!(classify(&[], 0, 0), DetectionOutcome::Hidden);
+        assert_eq!(classify(&[0; 32 * 32 * 4], 32, 32), DetectionOutcome::Blank);
+        assert_eq!(
+            classify(&[255; 4 * 4 * 4], 4, 4),
+            DetectionOutcome::LowConfidence {
+                best: None,
+                confidence: 0.0
+            }
+        );
+        assert_eq!(
+            classify(&image.as_raw()[..100], 32, 32),
+            DetectionOutcome::Error(CursorError::InvalidSize {
+                width: 32,
+                height: 32
+            })
+        );
+
+        // Trailing row padding past the bitmap is ignored, like everywhere else
+        let mut padded = image.as_raw().clone();
+        padded.resize(padded.len() + 32 * 4, 0);
+        assert_eq!(classify(&padded, 32, 32), classify(image.as_raw(), 32, 32));
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_detection_with_prior() {
+        // Stubby arrowheads score just below the threshold on their own
+        let borderline = rasterize_svg(
+            br##"<svg width="32" height="32" xmlns="http://www.w3.org/2000/svg">
+                <path d="M2 16 L5 8 L5 13 L27 13 L27 8 L30 16 L27 24 L27 19 L5 19 L5 24 Z"
+                    fill="#000000" stroke="#FFFFFF" stroke-width="1"/>
+            </svg>"##,
+            32,
+        )
+        .unwrap();
+        assert_eq!(
+            CommonCursorType::detect_with_prior(borderline.as_raw(), 32, 32, None),
+            None
+        );
+        let (detected, score) = CommonCursorType::detect_with_prior(
+            borderline.as_raw(),
+            32,
+            32,
+            Some(CommonCursorType::ResizeEW),
+        )
+        .unwrap();
+        assert_eq!(detected, CommonCursorType::ResizeEW);
+        assert!(score < MATCH_THRESHOLD);
+
+        // A clear arrow isn't held back by a different prior
+        let arrow = fixture("arrow", "classic-white", 32);
+        assert_eq!(
+            CommonCursorType::detect_with_prior(
+                arrow.as_raw(),
+                32,
+                32,
+                Some(CommonCursorType::PointingHand)
+            )
+            .map(|(cursor_type, _)| cursor_type),
+            Some(CommonCursorType::Arrow)
+        );
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_strict_detection_keeps_badge_matches() {
+        let svg = load_cursor_svg(&CommonCursorType::Copy).unwrap();
+        let image = rasterize_svg(&svg, 32).unwrap();
+
+        // The badge's arrow body scores highly too, but isn't a competing type
+        assert_eq!(
+            CommonCursorType::detect_with_config(
+                image.as_raw(),
+                32,
+                32,
+                &DetectionConfig::strict(0.4)
+            ),
+            Some(CommonCursorType::Copy)
+        );
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_translation_normalization_ignores_jitter() {
+        let svg = load_cursor_svg(&CommonCursorType::Arrow).unwrap();
+        let arrow = rasterize_svg(&svg, 32).unwrap();
+        let shifted_by = |offset: i64| {
+            let mut canvas = image::RgbaImage::new(40, 40);
+            image::imageops::overlay(&mut canvas, &arrow, offset, offset);
+            canvas
+        };
+        let (frame, jittered) = (shifted_by(2), shifted_by(3));
+
+        let normalized = normalize_translation(frame.as_raw(), 40, 40).unwrap();
+        let normalized_jittered = normalize_translation(jittered.as_raw(), 40, 40).unwrap();
+        assert_eq!(normalized, normalized_jittered);
+        assert_eq!(
+            CommonCursorType::detect_ranked(&normalized, 40, 40),
+            CommonCursorType::detect_ranked(&normalized_jittered, 40, 40)
+        );
+        assert_eq!(
+            perceptual_hash(&normalized, 40, 40),
+            perceptual_hash(&normalized_jittered, 40, 40)
+        );
+
+        let config = DetectionConfig {
+            normalize_translation: true,
+            ..DetectionConfig::default()
+        };
+        let detected = CommonCursorType::detect_with_config(frame.as_raw(), 40, 40, &config);
+        assert_eq!(detected, Some(CommonCursorType::Arrow));
+        assert_eq!(
+            CommonCursorType::detect_with_config(jittered.as_raw(), 40, 40, &config),
+            detected
+        );
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_detect_within_budget() {
+        let frame = |cursor_type: CommonCursorType| {
+            rasterize_svg(&load_cursor_svg(&cursor_type).unwrap(), 32)
+                .unwrap()
+                .into_raw()
+        };
+
+        for &cursor_type in CommonCursorType::ALL {
+            let image_data = frame(cursor_type);
+            let detected = CommonCursorType::detect_within(
+                &image_data,
+                32,
+                32,
+                std::time::Duration::from_secs(60),
+            );
+            assert_eq!(
+                detected.map(|(cursor_type, _)| cursor_type),
+                CommonCursorType::detect_from_image(&image_data, 32, 32),
+                "{:?}",
+                cursor_type
+            );
+            // With time to spare, scored the same as without a budget
+            if detected.is_some() {
+                assert_eq!(
+                    detected,
+                    CommonCursorType::detect_ranked(&image_data, 32, 32)
+                        .first()
+                        .copied(),
+                    "{:?}",
+                    cursor_type
+                );
+            }
+        }
+
+        // Out of time before the first stage, there's no guess yet
+        let arrow = frame(CommonCursorType::Arrow);
+        assert_eq!(
+            CommonCursorType::detect_within(&arrow, 32, 32, std::time::Duration::ZERO),
+            None
+        );
+    }
+
+    #[test]
+    fn test_calibrated_config_thresholds_confidence() {
+        let mut config = DetectionConfig {
+            min_score: 0.0,
+            ..DetectionConfig::default()
+        };
+        config.set_calibration(Calibration::default());
+
+        for &cursor_type in CommonCursorType::ALL {
+            let image = rasterize_cursor(&cursor_type, 32).unwrap();
+            let (data, size) = (image.as_raw(), image.width());
+            assert_eq!(
+                CommonCursorType::detect_with_config(data, size, size, &config),
+                Some(cursor_type)
+            );
+
+            // The threshold now applies to the calibrated confidence
+            let raw = CommonCursorType::detect_ranked(data, size, size)[0].1;
+            let confidence = Calibration::default().confidence(cursor_type, raw);
+            let strict = DetectionConfig {
+                min_score: confidence + 0.01,
+                ..config
+            };
+            assert_eq!(
+                CommonCursorType::detect_with_config(data, size, size, &strict),
+                None,
+                "{:?}",
+                cursor_type
+            );
+        }
+    }
+
+    #[test]
+    fn test_merge_layers_partial_configs() {
+        let os = PartialDetectionConfig {
+            min_score: Some(0.6),
+            strict_margin: Some(Some(0.1)),
+            normalize_translation: Some(true),
+            ..PartialDetectionConfig::default()
+        };
+        let app = PartialDetectionConfig {
+            strict_margin: Some(Some(0.2)),
+            calibration: Some(Some(Calibration::default())),
+            ..PartialDetectionConfig::default()
+        };
+        let user = PartialDetectionConfig {
+            min_score: Some(0.4),
+            calibration: Some(None),
+            ..PartialDetectionConfig::default()
+        };
+
+        let mut config = DetectionConfig::default();
+        for layer in [os, app, user] {
+            config.merge(layer);
+        }
+        assert_eq!(config.min_score, 0.4);
+        assert_eq!(config.strict_margin, Some(0.2));
+        assert!(config.normalize_translation);
+        assert!(config.type_mapper.is_none());
+        assert_eq!(config.calibration, None);
+
+        // An empty layer changes nothing, and a set field can be unset again
+        config.merge(PartialDetectionConfig::default());
+        assert_eq!(config.strict_margin, Some(0.2));
+        config.merge(PartialDetectionConfig {
+            strict_margin: Some(None),
+            ..PartialDetectionConfig::default()
+        });
+        assert_eq!(config.strict_margin, None);
+    }
+
+    #[test]
+    fn test_config_report_marks_overrides() {
+        let report = DetectionConfig::default().report();
+        assert!(report.contains("min_score: 0.5000\n"));
+        assert!(!report.contains("overridden"));
+        assert!(report.contains("  Eyedropper: 0.5000\n"));
+
+        let mut config = DetectionConfig::strict(0.1);
+        config.type_mapper = Some(CommonCursorType::canonical_resize);
+        config.set_calibration(Calibration::default());
+        let report = config.report();
+        assert!(report.contains("strict_margin: 0.1000 (overridden, default none)\n"));
+        assert!(report.contains("calibration: fitted (overridden, default none)\n"));
+        assert!(report.contains("normalize_translation: false\n"));
+        assert!(report
+            .contains("type_mapper: ResizeNWSE -> ResizeEW, ResizeAll -> ResizeEW, RowResize -> ResizeEW (overridden"));
+
+        // The calibrated arrow needs the raw score that maps to min_score
+        let arrow = Calibration::default()
+            .coefficients(CommonCursorType::Arrow)
+            .unwrap();
+        let needed = report
+            .lines()
+            .find_map(|line| line.strip_prefix("  Arrow: "))
+            .and_then(|rest| rest.split(' ').next())
+            .and_then(|raw| raw.parse::<f32>().ok())
+            .unwrap();
+        assert!((arrow.confidence(needed) - MATCH_THRESHOLD).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_type_mapper_keeps_raw_type() {
+        let config = DetectionConfig {
+            type_mapper: Some(CommonCursorType::canonical_resize),
+            ..DetectionConfig::default()
+        };
+
+        for (cursor_type, mapped) in [
+            (CommonCursorType::ResizeNWSE, CommonCursorType::ResizeEW),
+            (CommonCursorType::ResizeEW, CommonCursorType::ResizeEW),
+            (CommonCursorType::ResizeAll, CommonCursorType::ResizeEW),
+            (CommonCursorType::IBeam, CommonCursorType::IBeam),
+        ] {
+            let image = rasterize_cursor(&cursor_type, 32).unwrap();
+            let detection =
+                CommonCursorType::detect_mapped(image.as_raw(), 32, 32, &config).unwrap();
+            assert_eq!(
+                detection,
+                MappedDetection {
+                    raw: cursor_type,
+                    mapped
+                }
+            );
+            assert_eq!(
+                CommonCursorType::detect_with_config(image.as_raw(), 32, 32, &config),
+                Some(mapped)
+            );
+            assert_eq!(
+                CommonCursorType::detect_from_image(image.as_raw(), 32, 32),
+                Some(cursor_type)
+            );
+        }
+    }
+
+    #[test]
+    fn test_dimmed_cursor_detection() {
+        let dimmed = fixture("arrow", "dimmed-40", 32);
+        let analysis = analyze_cursor(dimmed.as_raw(), 32, 32);
+        assert_eq!(analysis.cursor_type, Some(CommonCursorType::Arrow));
+        assert!(analysis.dimmed);
+        assert!(
+            (analysis.opacity - 0.4).abs() < 0.01,
+            "{}",
+            analysis.opacity
+        );
+
+        let normal = fixture("arrow", "bundled", 32);
+        assert_eq!(
+            analyze_cursor(normal.as_raw(), 32, 32),
+            CursorAnalysis {
+                cursor_type: Some(CommonCursorType::Arrow),
+                score: CommonCursorType::detect_ranked(normal.as_raw(), 32, 32)[0].1,
+                bounds: Some(Rect::new((2, 3), (27, 27))),
+                hotspot: Some(Point::new(3, 3)),
+                opacity: 1.0,
+                dimmed: false,
+                is_clipped: false,
+                has_attached_glyph: false,
+                is_stacked: false,
+                magnified: false,
+                busy: false,
+            }
+        );
+
+        // A miss still reports how close it came
+        let mut outline = vec![0u8; 24 * 24 * 4];
+        fill_rect(&mut outline, 24, 4, 4, 20, 6);
+        fill_rect(&mut outline, 24, 4, 18, 20, 20);
+        fill_rect(&mut outline, 24, 4, 4, 6, 20);
+        fill_rect(&mut outline, 24, 18, 4, 20, 20);
+        let analysis = analyze_cursor(&outline, 24, 24);
+        assert_eq!(analysis.cursor_type, None);
+        assert_eq!(analysis.hotspot, None);
+        assert_eq!(
+            analysis.score,
+            CommonCursorType::suggest_type(&outline, 24, 24).1
+        );
+        assert!(analysis.score > 0.0 && analysis.score < MATCH_THRESHOLD);
+        assert_eq!(analysis.bounds, Some(Rect::new((4, 4), (16, 16))));
+    }
+
+    #[test]
+    fn test_suggest_type_for_unknown_cursor() {
+        // The outline isn't detected as anything but still gets a suggestion
+        let mut outline = vec![0u8; 24 * 24 * 4];
+        fill_rect(&mut outline, 24, 4, 4, 20, 6);
+        fill_rect(&mut outline, 24, 4, 18, 20, 20);
+        fill_rect(&mut outline, 24, 4, 4, 6, 20);
+        fill_rect(&mut outline, 24, 18, 4, 20, 20);
+        assert_eq!(CommonCursorType::detect_from_image(&outline, 24, 24), None);
+
+        let (suggested, score) = CommonCursorType::suggest_type(&outline, 24, 24);
+        assert!(score < MATCH_THRESHOLD);
+        assert_eq!(
+            CommonCursorType::detect_ranked(&outline, 24, 24)[0],
+            (suggested, score)
+        );
+
+        let blank = vec![0u8; 24 * 24 * 4];
+        assert_eq!(
+            CommonCursorType::suggest_type(&blank, 24, 24),
+            (CommonCursorType::Blank, 1.0)
+        );
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_masked_cursor_detection() {
+        // 24px rows take 3 mask bytes, padded to 4
+        let svg = load_cursor_svg(&CommonCursorType::Arrow).unwrap();
+        let arrow = rasterize_svg(&svg, 24).unwrap();
+        let stride = 4;
+        let mut and_mask = vec![0xFFu8; stride * 24];
+        let mut color = vec![0u8; 24 * 24 * 4];
+        for (x, y, pixel) in arrow.enumerate_pixels() {
+            if pixel[3] > OPAQUE_ALPHA_THRESHOLD {
+                and_mask[y as usize * stride + x as usize / 8] &= !(0x80 >> (x % 8));
+                let i = ((y * 24 + x) * 4) as usize;
+                color[i..i + 3].copy_from_slice(&pixel.0[..3]);
+            }
+        }
+
+        assert_eq!(
+            CommonCursorType::detect_from_masked(&color, &and_mask, 24, 24),
+            Some(CommonCursorType::Arrow)
+        );
+        // Without the row padding the mask is too short
+        assert_eq!(
+            CommonCursorType::detect_from_masked(&color, &and_mask[..3 * 24], 24, 24),
+            None
+        );
+        // Sizes whose byte count doesn't fit in a u32 are too short, not an overflow
+        assert_eq!(
+            CommonCursorType::detect_from_masked(&color, &and_mask, 65536, 65536),
+            None
+        );
+    }
+
+    #[cfg(all(feature = "profiling", feature = "svg"))]
+    #[test]
+    fn test_profiled_detection_matches_detection() {
+        for &cursor_type in CommonCursorType::ALL {
+            let image = rasterize_svg(&load_cursor_svg(&cursor_type).unwrap(), 32).unwrap();
+            let (detected, timings) = CommonCursorType::detect_profiled(image.as_raw(), 32, 32);
+
+            assert_eq!(
+                detected,
+                CommonCursorType::detect_from_image(image.as_raw(), 32, 32)
+            );
+            // Blank, badge, not-allowed, reticle and scroll cursors are
+            // recognized before any matcher runs
+            let structural = cursor_type.has_badge()
+                || matches!(
+                    cursor_type,
+                    CommonCursorType::ScrollNS
+                        | CommonCursorType::ScrollEW
+                        | CommonCursorType::NotAllowed
+                        | CommonCursorType::Reticle
+                        | CommonCursorType::Blank
+                );
+            let expected_matchers = if structural {
+                0
+            } else {
+                CommonCursorType::MATCHERS.len()
+            };
+            assert_eq!(
+                timings.matchers.len(),
+                expected_matchers,
+                "{:?}",
+                cursor_type
+            );
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_clipped_cursor_analysis() {
+        let svg = load_cursor_svg(&CommonCursorType::Arrow).unwrap();
+        let arrow = rasterize_svg(&svg, 32).unwrap();
+        assert!(!analyze_cursor(arrow.as_raw(), 32, 32).is_clipped);
+
+        // Dragged past the left edge of the screen, the arrow's left side is cut off
+        let mut clipped = image::RgbaImage::new(32, 32);
+        image::imageops::overlay(&mut clipped, &arrow, -6, 0);
+        assert!(analyze_cursor(clipped.as_raw(), 32, 32).is_clipped);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_luma_detection() {
+        for cursor_type in [CommonCursorType::Arrow, CommonCursorType::IBeam] {
+            let svg = load_cursor_svg(&cursor_type).unwrap();
+            let cursor = rasterize_svg(&svg, 32).unwrap();
+            for background in [96u8, 200] {
+                let mut frame = image::RgbaImage::from_pixel(
+                    32,
+                    32,
+                    image::Rgba([background, background, background, 255]),
+                );
+                image::imageops::overlay(&mut frame, &cursor, 0, 0);
+                let luma = image::DynamicImage::ImageRgba8(frame).to_luma8();
+
+                assert_eq!(
+                    CommonCursorType::detect_from_pixels(
+                        luma.as_raw(),
+                        32,
+                        32,
+                        CursorPixelFormat::Gray8
+                    ),
+                    Some(cursor_type),
+                    "{:?} over {}",
+                    cursor_type,
+                    background
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_resize_all_versus_axis_cross() {
+        let score_of = |cursor_type: CommonCursorType, svg: &[u8]| {
+            let image = rasterize_svg(svg, 32).unwrap();
+            CommonCursorType::detect_ranked(image.as_raw(), 32, 32)
+                .into_iter()
+                .find(|(t, _)| *t == cursor_type)
+                .map_or(0.0, |(_, score)| score)
+        };
+
+        let resize_all = load_cursor_svg(&CommonCursorType::ResizeAll).unwrap();
+        assert!(score_of(CommonCursorType::ResizeAll, &resize_all) > MATCH_THRESHOLD);
+        assert!(score_of(CommonCursorType::ResizeNWSE, &resize_all) < MATCH_THRESHOLD);
+
+        // Arrows along the axes, as on a move cursor, aren't corner-directed
+        let axis_cross = br##"<svg width="32" height="32" xmlns="http://www.w3.org/2000/svg">
+            <path d="M16 1 L21 7 L18 7 L18 14 L25 14 L25 11 L31 16 L25 21 L25 18 L18 18 L18 25 L21 25 L16 31 L11 25 L14 25 L14 18 L7 18 L7 21 L1 16 L7 11 L7 14 L14 14 L14 7 L11 7 Z" fill="#000000"/>
+        </svg>"##;
+        assert!(score_of(CommonCursorType::ResizeAll, axis_cross) < MATCH_THRESHOLD);
+    }
+
+    #[test]
+    fn test_bundled_cursors_self_test() {
+        assert_eq!(self_test(), Ok(()));
+    }
+
+    #[test]
+    fn test_dump_self_test_mismatches() {
+        let dir = std::env::temp_dir().join(format!("cursor-self-test-{}", std::process::id()));
+        let mismatches = [(CommonCursorType::IBeam, CommonCursorType::Crosshair)];
+        dump_self_test_mismatches(&dir, &mismatches).unwrap();
+
+        let dumped = image::open(dir.join("ibeam.png")).unwrap().to_rgba8();
+        assert_eq!(
+            dumped,
+            rasterize_cursor(&CommonCursorType::IBeam, 32).unwrap()
+        );
+        let report = std::fs::read_to_string(dir.join("ibeam-ranked.txt")).unwrap();
+        let mut lines = report.lines();
+        assert_eq!(lines.next(), Some("expected IBeam, detected Crosshair"));
+        assert!(lines.next().unwrap().starts_with("IBeam "));
+        assert!(lines.all(|line| line.rsplit(' ').next().unwrap().parse::<f32>().is_ok()));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_dominant_cursor_color() {
+        let color_of = |svg: &[u8]| {
+            let image = rasterize_svg(svg, 32).unwrap();
+            dominant_cursor_color(image.as_raw(), 32, 32)
+        };
+
+        // The white fill and black outline are all the arrow has, and the fill covers more
+        let arrow = load_cursor_svg(&CommonCursorType::Arrow).unwrap();
+        assert!(color_of(&arrow).is_some_and(|color| color.iter().all(|&c| c >= 240)));
+
+        // A colored badge wins over the larger black-and-white body
+        let badged = br##"<svg width="32" height="32" xmlns="http://www.w3.org/2000/svg">
+            <path d="M2 2 L2 24 L8 18 L18 18 Z" fill="#000000" stroke="#FFFFFF" stroke-width="1"/>
+            <circle cx="24" cy="24" r="5" fill="#2080F0"/>
+        </svg>"##;
+        assert_eq!(color_of(badged), Some([0x20, 0x80, 0xF0]));
+
+        assert_eq!(dominant_cursor_color(&[0u8; 16 * 16 * 4], 16, 16), None);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_dominant_cursor_color_of_bgra_capture() {
+        let badged = br##"<svg width="32" height="32" xmlns="http://www.w3.org/2000/svg">
+            <path d="M2 2 L2 24 L8 18 L18 18 Z" fill="#000000" stroke="#FFFFFF" stroke-width="1"/>
+            <circle cx="24" cy="24" r="5" fill="#2080F0"/>
+        </svg>"##;
+        let mut image = rasterize_svg(badged, 32).unwrap();
+        ChannelOrder::Bgra.reorder(&mut image);
+
+        // Read as RGBA the blue badge comes out orange
+        assert_eq!(
+            dominant_cursor_color_with(image.as_raw(), 32, 32, ChannelOrder::Bgra),
+            Some([0x20, 0x80, 0xF0])
+        );
+        assert_eq!(
+            dominant_cursor_color(image.as_raw(), 32, 32),
+            Some([0xF0, 0x80, 0x20])
+        );
+
+        // Shape detection doesn't care about the order
+        assert_eq!(
+            CommonCursorType::detect_from_image(image.as_raw(), 32, 32),
+            CommonCursorType::detect_from_image(
+                rasterize_svg(badged, 32).unwrap().as_raw(),
+                32,
+                32
+            )
+        );
+    }
+
+    #[test]
+    fn test_skeleton_matches_strokes_of_any_weight() {
+        let config = DetectionConfig {
+            skeletonize: true,
+            ..DetectionConfig::default()
+        };
+        for size in [32, 48] {
+            let mut skeletons = Vec::new();
+            for weight in ["light", "heavy"] {
+                let image = fixture("crosshair", &format!("stroke-{weight}"), size);
+                let skeleton = skeletonize(image.as_raw(), size, size).unwrap();
+
+                // One pixel wide: no 2x2 block is entirely opaque
+                let opaque = |x: u32, y: u32| skeleton[((y * size + x) * 4 + 3) as usize] > 0;
+                assert!((0..size - 1).all(|y| (0..size - 1).all(|x| {
+                    !(opaque(x, y) && opaque(x + 1, y) && opaque(x, y + 1) && opaque(x + 1, y + 1))
+                })));
+                assert_eq!(
+                    CommonCursorType::detect_with_config(image.as_raw(), size, size, &config),
+                    Some(CommonCursorType::Crosshair),
+                    "{weight} {size}px"
+                );
+                skeletons.push(CommonCursorType::detect_ranked(&skeleton, size, size)[0]);
+            }
+            assert_eq!(
+                skeletons,
+                [(CommonCursorType::Crosshair, 1.0); 2],
+                "{size}px"
+            );
+        }
+
+        // Shapes that match as they are aren't thinned
+        for cursor_type in CommonCursorType::ALL {
+            let image = rasterize_cursor(cursor_type, 32).unwrap();
+            assert_eq!(
+                CommonCursorType::detect_with_config(image.as_raw(), 32, 32, &config),
+                CommonCursorType::detect_from_image(image.as_raw(), 32, 32),
+                "{:?}",
+                cursor_type
+            );
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_skeleton_matches_heavy_ibeam() {
+        // An I-beam heavy enough to fill its box only matches once thinned
+        let config = DetectionConfig {
+            skeletonize: true,
+            ..DetectionConfig::default()
+        };
+        let heavy = rasterize_svg(
+            br##"<svg width="32" height="32" viewBox="0 0 32 32" fill="none" xmlns="http://www.w3.org/2000/svg"><path d="M10 5 H22 M16 5 V27 M10 27 H22" stroke="#000000" stroke-width="6"/></svg>"##,
+            32,
+        )
+        .unwrap();
+        assert_eq!(
+            CommonCursorType::detect_from_image(heavy.as_raw(), 32, 32),
+            None
+        );
+        assert_eq!(
+            CommonCursorType::detect_with_config(heavy.as_raw(), 32, 32, &config),
+            Some(CommonCursorType::IBeam)
+        );
+    }
+
+    #[test]
+    fn test_defringe_subpixel_cursors() {
+        let config = DetectionConfig {
+            defringe: true,
+            ..DetectionConfig::default()
+        };
+        for size in [32, 48] {
+            let fringed = fixture("arrow", "subpixel-fringe", size);
+            let defringed = defringe(fringed.as_raw(), size, size);
+            assert!(matches!(defringed, Cow::Owned(_)), "{size}");
+
+            // The red and blue edges are gone, the alpha is untouched
+            assert!(defringed
+                .chunks(4)
+                .all(|p| p[3] == 0 || p[0].abs_diff(p[2]) < 8));
+            assert!(defringed
+                .chunks(4)
+                .zip(fringed.pixels())
+                .all(|(p, original)| p[3] == original[3]));
+            assert!(dominant_cursor_color(&defringed, size, size)
+                .is_some_and(|color| color.iter().all(|&c| c >= 240)));
+
+            // Shape detection got the arrow through the fringes anyway
+            let ranked = CommonCursorType::detect_ranked(fringed.as_raw(), size, size);
+            assert_eq!(ranked[0].0, CommonCursorType::Arrow);
+            assert_eq!(
+                CommonCursorType::detect_with_config(fringed.as_raw(), size, size, &config),
+                Some(CommonCursorType::Arrow)
+            );
+            assert_eq!(
+                CommonCursorType::detect_ranked(&defringed, size, size),
+                ranked
+            );
+        }
+
+        // Colored cursors keep the anti-aliasing between their colors
+        for cursor_type in CommonCursorType::ALL {
+            let image = rasterize_cursor(cursor_type, 32).unwrap();
+            assert!(
+                matches!(defringe(image.as_raw(), 32, 32), Cow::Borrowed(_)),
+                "{cursor_type:?}"
+            );
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_small_cursors_use_small_thresholds() {
+        let buckets = SizeBuckets::default();
+        assert_eq!(buckets.for_size(16, 16), SizeThresholds::SMALL);
+        assert_eq!(buckets.for_size(32, 20), SizeThresholds::MEDIUM);
+        assert_eq!(buckets.for_size(24, 64), SizeThresholds::LARGE);
+
+        // Tuned for 32px, a 16px hand has too few pixels and a 16px disc too
+        // soft a rim to pass
+        let medium_only = DetectionConfig {
+            size_buckets: SizeBuckets {
+                small: SizeThresholds::MEDIUM,
+                ..SizeBuckets::default()
+            },
+            ..DetectionConfig::default()
+        };
+        for cursor_type in [CommonCursorType::Grab, CommonCursorType::TouchPointer] {
+            let image = rasterize_cursor(&cursor_type, 16).unwrap();
+            let data = image.as_raw();
+            assert_eq!(
+                CommonCursorType::detect_with_config(data, 16, 16, &DetectionConfig::default()),
+                Some(cursor_type)
+            );
+            assert_ne!(
+                CommonCursorType::detect_with_config(data, 16, 16, &medium_only),
+                Some(cursor_type)
+            );
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_scroll_cursors() {
+        let detect = |svg: &[u8]| {
+            let image = rasterize_svg(svg, 32).unwrap();
+            CommonCursorType::detect_from_image(image.as_raw(), 32, 32)
+        };
+
+        for cursor_type in [CommonCursorType::ScrollNS, CommonCursorType::ScrollEW] {
+            let svg = load_cursor_svg(&cursor_type).unwrap();
+            assert_eq!(detect(&svg), Some(cursor_type));
+        }
+
+        // Without the pivot dot it's just two arrowheads
+        let no_dot = br##"<svg width="24" height="24" xmlns="http://www.w3.org/2000/svg">
+            <path d="M12 1 L18 8 L6 8 Z" fill="#000000"/>
+            <path d="M12 23 L6 16 L18 16 Z" fill="#000000"/>
+        </svg>"##;
+        assert_ne!(detect(no_dot), Some(CommonCursorType::ScrollNS));
+
+        // Heads pointing at the dot instead of away from it
+        let inward = br##"<svg width="24" height="24" xmlns="http://www.w3.org/2000/svg">
+            <path d="M1 6 L8 12 L1 18 Z" fill="#000000"/>
+            <path d="M23 6 L16 12 L23 18 Z" fill="#000000"/>
+            <circle cx="12" cy="12" r="2" fill="#000000"/>
+        </svg>"##;
+        assert_ne!(detect(inward), Some(CommonCursorType::ScrollEW));
+
+        // A shaft joining the heads makes it a resize cursor
+        let resize_ew = load_cursor_svg(&CommonCursorType::ResizeEW).unwrap();
+        assert_eq!(detect(&resize_ew), Some(CommonCursorType::ResizeEW));
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_not_allowed_ignores_drag_thumbnail() {
+        let detect = |image: &image::RgbaImage| {
+            CommonCursorType::detect_from_image(image.as_raw(), image.width(), image.height())
+        };
+
+        let svg = load_cursor_svg(&CommonCursorType::NotAllowed).unwrap();
+        for size in [24, 32, 48] {
+            let image = rasterize_svg(&svg, size).unwrap();
+            assert_eq!(detect(&image), Some(CommonCursorType::NotAllowed));
+        }
+
+        for size in [32, 48] {
+            let image = fixture("not-allowed", "drag-thumbnail", size);
+            // The thumbnail touches the ring, so it's all one blob
+            let map = ComponentMap::from_rgba(image.as_raw(), size, size).unwrap();
+            assert_eq!(map.components().len(), 1);
+            assert_eq!(detect(&image), Some(CommonCursorType::NotAllowed));
+        }
+
+        // The same ring and slash over a disc of their own color is just a disc
+        let filled = br##"<svg width="24" height="24" xmlns="http://www.w3.org/2000/svg">
+            <circle cx="12" cy="12" r="10" fill="#E81123"/>
+        </svg>"##;
+        let image = rasterize_svg(filled, 32).unwrap();
+        assert_ne!(detect(&image), Some(CommonCursorType::NotAllowed));
+
+        // And a ring without the slash is something else too
+        let ring = br##"<svg width="24" height="24" xmlns="http://www.w3.org/2000/svg">
+            <circle cx="12" cy="12" r="8.5" stroke="#E81123" stroke-width="3" fill="none"/>
+        </svg>"##;
+        let image = rasterize_svg(ring, 32).unwrap();
+        assert_ne!(detect(&image), Some(CommonCursorType::NotAllowed));
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_reticle_detection() {
+        let detect = |image: &image::RgbaImage| {
+            CommonCursorType::detect_from_image(image.as_raw(), image.width(), image.height())
+        };
+
+        let svg = load_cursor_svg(&CommonCursorType::Reticle).unwrap();
+        for size in [24, 32, 48] {
+            let image = rasterize_svg(&svg, size).unwrap();
+            assert_eq!(detect(&image), Some(CommonCursorType::Reticle));
+        }
+
+        // Cross lines through the ring make it a crosshair's scope, not a reticle
+        let scope = br##"<svg width="24" height="24" xmlns="http://www.w3.org/2000/svg">
+            <circle cx="12" cy="12" r="8" stroke="#000000" stroke-width="1.5" fill="none"/>
+            <rect x="11.25" y="1" width="1.5" height="22"/>
+            <rect x="1" y="11.25" width="22" height="1.5"/>
+        </svg>"##;
+        let image = rasterize_svg(scope, 32).unwrap();
+        assert_ne!(detect(&image), Some(CommonCursorType::Reticle));
+        let crosshair = load_cursor_svg(&CommonCursorType::Crosshair).unwrap();
+        let image = rasterize_svg(&crosshair, 32).unwrap();
+        assert_eq!(detect(&image), Some(CommonCursorType::Crosshair));
+
+        // Without the center dot the ring is a spinner's, and a thick ring
+        // around a dot is no aiming reticle either
+        let ring = br##"<svg width="24" height="24" xmlns="http://www.w3.org/2000/svg">
+            <circle cx="12" cy="12" r="8" stroke="#000000" stroke-width="1.5" fill="none"/>
+        </svg>"##;
+        let image = rasterize_svg(ring, 32).unwrap();
+        assert_ne!(detect(&image), Some(CommonCursorType::Reticle));
+        let thick = br##"<svg width="24" height="24" xmlns="http://www.w3.org/2000/svg">
+            <circle cx="12" cy="12" r="7" stroke="#000000" stroke-width="5" fill="none"/>
+            <circle cx="12" cy="12" r="1.5"/>
+        </svg>"##;
+        for size in [24, 32, 48] {
+            let image = rasterize_svg(thick, size).unwrap();
+            assert_ne!(detect(&image), Some(CommonCursorType::Reticle));
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_move_cursor_detection() {
+        let detect = |image: &image::RgbaImage| {
+            CommonCursorType::detect_from_image(image.as_raw(), image.width(), image.height())
+        };
+
+        let svg = load_cursor_svg(&CommonCursorType::Move).unwrap();
+        for size in [24, 32, 48] {
+            let image = rasterize_svg(&svg, size).unwrap();
+            assert_eq!(detect(&image), Some(CommonCursorType::Move), "{}px", size);
+        }
+
+        for size in [32, 48] {
+            let image = fixture("move", "fleur-white", size);
+            assert_eq!(detect(&image), Some(CommonCursorType::Move), "{}px", size);
+        }
+
+        // Plain lines without arrowheads stay a crosshair, and the diagonal
+        // arrows of the resize cursor stay apart from the fleur's
+        for (cursor_type, size) in [
+            (CommonCursorType::Crosshair, 48),
+            (CommonCursorType::ResizeAll, 32),
+        ] {
+            let image = rasterize_svg(&load_cursor_svg(&cursor_type).unwrap(), size).unwrap();
+            assert_eq!(detect(&image), Some(cursor_type));
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_eyedropper_detection() {
+        let detect = |image: &image::RgbaImage| {
+            CommonCursorType::detect_from_image(image.as_raw(), image.width(), image.height())
+        };
+
+        let svg = load_cursor_svg(&CommonCursorType::Eyedropper).unwrap();
+        for size in [24, 32, 48] {
+            let image = rasterize_svg(&svg, size).unwrap();
+            assert_eq!(
+                detect(&image),
+                Some(CommonCursorType::Eyedropper),
+                "{}px",
+                size
+            );
+        }
+
+        for size in [32, 48] {
+            let image = fixture("eyedropper", "pipette-gray", size);
+            assert_eq!(
+                detect(&image),
+                Some(CommonCursorType::Eyedropper),
+                "{}px",
+                size
+            );
+        }
+
+        // A pencil's sharp point and a double-headed diagonal arrow lie along
+        // the same diagonal, but neither has a bulb at one end only
+        let svg32 = |body: &str| {
+            format!(
+                r#"<svg width="32" height="32" viewBox="0 0 32 32" xmlns="http://www.w3.org/2000/svg">{}</svg>"#,
+                body
+            )
+        };
+        for body in [
+            r##"<path d="M3 29 L5 22 L24 3 L29 8 L10 27 Z" fill="#FFFFFF" stroke="#000000" stroke-width="1.5"/>"##,
+            r##"<path d="M28 4 L28 14 L24.5 10.5 L10.5 24.5 L14 28 L4 28 L4 18 L7.5 21.5 L21.5 7.5 L18 4 Z" fill="#000000" stroke="#FFFFFF" stroke-width="1"/>"##,
+        ] {
+            let image = rasterize_svg(svg32(body).as_bytes(), 32).unwrap();
+            assert_ne!(
+                detect(&image),
+                Some(CommonCursorType::Eyedropper),
+                "{}",
+                body
+            );
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_row_resize_needs_exactly_one_bar() {
+        let detect = |image: &image::RgbaImage| {
+            CommonCursorType::detect_from_image(image.as_raw(), image.width(), image.height())
+        };
+        let bars = |image: &image::RgbaImage| {
+            CursorFeatures::extract(image.as_raw(), image.width(), image.height())
+                .unwrap()
+                .horizontal_bars
+        };
+
+        for (name, size) in [
+            ("bundled", 16),
+            ("bundled", 32),
+            ("classic-row", 32),
+            ("classic-row", 48),
+        ] {
+            let image = fixture("row-resize", name, size);
+            assert_eq!(bars(&image), 1, "{name}-{size}");
+            assert_eq!(
+                detect(&image),
+                Some(CommonCursorType::RowResize),
+                "{name}-{size}"
+            );
+        }
+
+        // The same arrows without the divider are a window's vertical resize
+        // cursor, and with a divider on either side a pane splitter's
+        let svg32 = |body: &str| {
+            format!(
+                r#"<svg width="32" height="32" viewBox="0 0 32 32" xmlns="http://www.w3.org/2000/svg">{}</svg>"#,
+                body
+            )
+        };
+        for (body, expected_bars) in [
+            (
+                r##"<path d="M16 2 L22 8.5 L17.5 8.5 L17.5 23.5 L22 23.5 L16 30 L10 23.5 L14.5 23.5 L14.5 8.5 L10 8.5 Z" fill="#000000" stroke="#FFFFFF" stroke-width="1"/>"##,
+                0,
+            ),
+            (
+                r##"<path d="M16 2 L22 8.5 L17.5 8.5 L17.5 12 L29 12 L29 14.5 L3 14.5 L3 12 L14.5 12 L14.5 8.5 L10 8.5 Z" fill="#000000" stroke="#FFFFFF" stroke-width="1"/><path d="M3 17.5 L29 17.5 L29 20 L17.5 20 L17.5 23.5 L22 23.5 L16 30 L10 23.5 L14.5 23.5 L14.5 20 L3 20 Z" fill="#000000" stroke="#FFFFFF" stroke-width="1"/>"##,
+                2,
+            ),
+        ] {
+            for size in [24, 32, 48] {
+                let image = rasterize_svg(svg32(body).as_bytes(), size).unwrap();
+                assert_eq!(bars(&image), expected_bars, "{}px {}", size, body);
+                assert_ne!(
+                    detect(&image),
+                    Some(CommonCursorType::RowResize),
+                    "{}px {}",
+                    size,
+                    body
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_grab_and_grabbing_by_fill_ratio() {
+        for (open, closed) in [("bundled", "bundled"), ("black-open", "black-fist")] {
+            for size in [32, 48] {
+                let (open, closed) = (
+                    fixture("grab", open, size),
+                    fixture("grabbing", closed, size),
+                );
+                let features = |image: &image::RgbaImage| {
+                    CursorFeatures::extract(image.as_raw(), size, size).unwrap()
+                };
+                // Same outline size, but the fist fills its box where the
+                // fingers of the open hand leave gaps
+                assert!(features(&closed).fill_ratio > features(&open).fill_ratio + 0.15);
+                assert_eq!(
+                    CommonCursorType::detect_from_image(open.as_raw(), size, size),
+                    Some(CommonCursorType::Grab)
+                );
+                assert_eq!(
+                    CommonCursorType::detect_from_image(closed.as_raw(), size, size),
+                    Some(CommonCursorType::Grabbing)
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_crosshair_with_attached_glyph() {
+        for name in ["cad-label", "tool-hint"] {
+            for size in [32, 48] {
+                let image = fixture("crosshair", name, size);
+                let analysis = analyze_cursor(image.as_raw(), size, size);
+                assert_eq!(
+                    analysis.cursor_type,
+                    Some(CommonCursorType::Crosshair),
+                    "{} {}px",
+                    name,
+                    size
+                );
+                assert!(analysis.has_attached_glyph, "{} {}px", name, size);
+            }
+        }
+
+        let plain =
+            rasterize_svg(&load_cursor_svg(&CommonCursorType::Crosshair).unwrap(), 32).unwrap();
+        let analysis = analyze_cursor(plain.as_raw(), 32, 32);
+        assert_eq!(analysis.cursor_type, Some(CommonCursorType::Crosshair));
+        assert!(!analysis.has_attached_glyph);
+    }
+
+    #[test]
+    fn test_arrow_with_text_readout() {
+        for size in [32, 48] {
+            let image = fixture("arrow", "coordinate-readout", size);
+            let analysis = analyze_cursor(image.as_raw(), size, size);
+            assert_eq!(
+                analysis.cursor_type,
+                Some(CommonCursorType::Arrow),
+                "{}px",
+                size
+            );
+            assert!(analysis.has_attached_glyph, "{}px", size);
+
+            let ranked = CommonCursorType::detect_ranked(image.as_raw(), size, size);
+            assert_eq!(ranked[0], (CommonCursorType::Arrow, 1.0), "{}px", size);
+        }
+
+        // A badge is a single glyph, the dots of a busy ring aren't lines of
+        // text, and neither makes a readout
+        for cursor_type in [CommonCursorType::Copy, CommonCursorType::Progress] {
+            let image = rasterize_cursor(&cursor_type, 32).unwrap();
+            let analysis = analyze_cursor(image.as_raw(), 32, 32);
+            assert_eq!(analysis.cursor_type, Some(cursor_type));
+            assert!(!analysis.has_attached_glyph);
+        }
+        let busy = fixture("progress", "win11-busy", 32);
+        let map = ComponentMap::from_rgba(busy.as_raw(), 32, 32).unwrap();
+        assert!(!has_text_readout(&map));
+
+        let plain = rasterize_cursor(&CommonCursorType::Arrow, 32).unwrap();
+        assert!(!analyze_cursor(plain.as_raw(), 32, 32).has_attached_glyph);
+    }
+
+    #[test]
+    fn test_arrow_with_detached_tooltip() {
+        for size in [32, 48] {
+            let image = fixture("arrow", "detached-tooltip", size);
+            assert_eq!(
+                CommonCursorType::detect_from_image(image.as_raw(), size, size),
+                Some(CommonCursorType::Arrow),
+                "{}px",
+                size
+            );
+
+            let stripped = strip_tooltips(image.as_raw(), size, size, (0, 0));
+            let (cursor, tooltip) = (
+                stripped.cursor_bounds.unwrap(),
+                stripped.stripped_bounds.unwrap(),
+            );
+            assert!(cursor.max_x < tooltip.min_x && cursor.max_y < tooltip.min_y);
+            assert!(stripped.image_data.chunks(4).enumerate().all(|(i, p)| {
+                let (x, y) = (i as u32 % size, i as u32 / size);
+                p[3] == 0 || x < tooltip.min_x || y < tooltip.min_y
+            }));
+            assert_eq!(
+                CommonCursorType::detect_ranked(&stripped.image_data, size, size)[0].0,
+                CommonCursorType::Arrow
+            );
+
+            // The renderer gets the same cursor, hotspot and size unchanged
+            let source =
+                CursorRenderSource::RawBitmap(image.clone(), (0.0, 0.0)).without_tooltips();
+            let CursorRenderSource::RawBitmap(bitmap, hotspot) = source else {
+                panic!("a captured bitmap stays one");
+            };
+            assert_eq!(hotspot, (0.0, 0.0));
+            assert_eq!(bitmap.as_raw(), &*stripped.image_data);
+        }
+
+        // Badges and glyphs stay, however far from the cursor they are
+        for cursor_type in CommonCursorType::ALL {
+            let image = rasterize_cursor(cursor_type, 32).unwrap();
+            let stripped = strip_tooltips(image.as_raw(), 32, 32, (0, 0));
+            assert!(
+                matches!(stripped.image_data, Cow::Borrowed(_)),
+                "{:?}",
+                cursor_type
+            );
+        }
+        let hint = fixture("crosshair", "tool-hint", 32);
+        let stripped = strip_tooltips(hint.as_raw(), 32, 32, (12, 12));
+        assert_eq!(stripped.stripped_bounds, None);
+
+        // A crosshair's arms are strokes, not tooltips, gap at the center or not
+        for size in [32, 48] {
+            let crosshair = fixture("crosshair", "center-gap", size);
+            let stripped = strip_tooltips(crosshair.as_raw(), size, size, (0, 0));
+            assert_eq!(stripped.stripped_bounds, None, "{}px", size);
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_ibeam_with_link_hint() {
+        for name in ["link-hand", "link-hand-thin"] {
+            for size in [32, 48] {
+                let image = fixture("ibeam", name, size);
+                let analysis = analyze_cursor(image.as_raw(), size, size);
+                assert_eq!(
+                    analysis.cursor_type,
+                    Some(CommonCursorType::IBeam),
+                    "{} {}px",
+                    name,
+                    size
+                );
+                assert!(analysis.has_attached_glyph, "{} {}px", name, size);
+
+                // Found by its components, so the I-beam isn't listed twice
+                let ranked = CommonCursorType::detect_ranked(image.as_raw(), size, size);
+                let ibeams = ranked
+                    .iter()
+                    .filter(|(cursor_type, _)| *cursor_type == CommonCursorType::IBeam);
+                assert_eq!(ibeams.count(), 1);
+            }
+        }
+
+        let plain = rasterize_svg(&load_cursor_svg(&CommonCursorType::IBeam).unwrap(), 32).unwrap();
+        assert!(!analyze_cursor(plain.as_raw(), 32, 32).has_attached_glyph);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_stacked_cursors() {
+        for size in [32, 48] {
+            let image = fixture("arrow", "stacked-pair", size);
+            let analysis = analyze_cursor(image.as_raw(), size, size);
+            assert_eq!(
+                analysis.cursor_type,
+                Some(CommonCursorType::Arrow),
+                "{}px",
+                size
+            );
+            assert!(analysis.is_stacked, "{}px", size);
+
+            // Ranked as the better arrow alone, not every heuristic on the pair
+            let ranked = CommonCursorType::detect_ranked(image.as_raw(), size, size);
+            assert_eq!(ranked.len(), 1);
+            assert_eq!(ranked[0].0, CommonCursorType::Arrow);
+        }
+
+        // Badges and detached details are never taken for a second cursor
+        for &cursor_type in CommonCursorType::ALL {
+            let image = rasterize_svg(&load_cursor_svg(&cursor_type).unwrap(), 32).unwrap();
+            assert!(
+                !analyze_cursor(image.as_raw(), 32, 32).is_stacked,
+                "{:?}",
+                cursor_type
+            );
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_dotted_ring_progress() {
+        for size in [32, 48] {
+            let image = fixture("progress", "win11-busy", size);
+            assert_eq!(
+                CommonCursorType::detect_from_image(image.as_raw(), size, size),
+                Some(CommonCursorType::Progress),
+                "{}px",
+                size
+            );
+        }
+
+        // Dots bunched into an arc aren't a busy ring
+        let arc = br##"<svg width="32" height="32" xmlns="http://www.w3.org/2000/svg">
+            <path d="M3 2 L3 20 L7.5 16 L10.5 23 L13 22 L10 15.5 L16 15.5 Z" fill="#FFFFFF" stroke="#000000" stroke-width="1"/>
+            <circle cx="28.5" cy="23" r="1.4" fill="#0078D4"/>
+            <circle cx="26.89" cy="26.89" r="1.4" fill="#0078D4"/>
+            <circle cx="23" cy="28.5" r="1.4" fill="#0078D4"/>
+            <circle cx="19.11" cy="26.89" r="1.4" fill="#0078D4"/>
+            <circle cx="17.5" cy="23" r="1.4" fill="#0078D4"/>
+        </svg>"##;
+        let image = rasterize_svg(arc, 32).unwrap();
+        assert_ne!(
+            CommonCursorType::detect_from_image(image.as_raw(), 32, 32),
+            Some(CommonCursorType::Progress)
+        );
+    }
+
+    #[test]
+    fn test_detailed_analysis_falls_back_to_bitmap() {
+        let fixture = fixture_path("ibeam", "bundled", 32);
+        assert_eq!(
+            analyze_cursor_image_detailed(&fixture, (0.5, 0.5)),
+            Some(CursorRenderSource::KnownType(CommonCursorType::IBeam))
+        );
+
+        // A hollow square doesn't look like any known cursor
+        let mut outline = image::RgbaImage::new(24, 24);
+        for (x, y, pixel) in outline.enumerate_pixels_mut() {
+            let on_edge = |v: u32| v == 4 || v == 5 || v == 18 || v == 19;
+            let inside = |v: u32| (4..=19).contains(&v);
+            if (on_edge(x) && inside(y)) || (on_edge(y) && inside(x)) {
+                *pixel = image::Rgba([40, 40, 40, 255]);
+            }
+        }
+        let path = std::env::temp_dir().join("cap_cursor_detailed_fallback.png");
+        outline.save(&path).unwrap();
+
+        assert_eq!(
+            analyze_cursor_image_detailed(&path, (0.25, 0.75)),
+            Some(CursorRenderSource::RawBitmap(outline, (0.25, 0.75)))
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_analyze_cursor_directory_reports_progress() {
+        let dir = std::env::temp_dir().join(format!("cursor-directory-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for (name, fixture) in [
+            ("a.png", fixture_path("arrow", "classic-white", 32)),
+            ("b.png", fixture_path("ibeam", "bundled", 32)),
+            ("c.png", fixture_path("copy", "bundled", 32)),
+            ("d.png", fixture_path("move", "bundled", 32)),
+            ("e.png", fixture_path("grab", "bundled", 48)),
+        ] {
+            std::fs::copy(fixture, dir.join(name)).unwrap();
+        }
+        std::fs::write(dir.join("notes.txt"), b"not a cursor").unwrap();
+        std::fs::write(dir.join("broken.png"), b"not a png either").unwrap();
+
+        let calls = std::sync::Mutex::new(Vec::new());
+        let analyzed = analyze_cursor_directory_with_progress(&dir, |done, total| {
+            calls.lock().unwrap().push((done, total));
+        })
+        .unwrap();
+
+        let names: Vec<_> = analyzed
+            .iter()
+            .map(|(path, cursor_type)| (path.file_name().unwrap().to_str().unwrap(), *cursor_type))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("a.png", Some(CommonCursorType::Arrow)),
+                ("b.png", Some(CommonCursorType::IBeam)),
+                ("broken.png", None),
+                ("c.png", Some(CommonCursorType::Copy)),
+                ("d.png", Some(CommonCursorType::Move)),
+                ("e.png", Some(CommonCursorType::Grab)),
+            ]
+        );
+        let calls = calls.into_inner().unwrap();
+        assert_eq!(calls, (1..=6).map(|done| (done, 6)).collect::<Vec<_>>());
+        assert_eq!(analyze_cursor_directory(&dir).unwrap(), analyzed);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(
+            analyze_cursor_directory(&dir),
+            Err(CursorError::Read { .. })
+        ));
+    }
+
+    #[cfg(feature = "svg")]
+    fn draw_arc(width: u32, radius: f32, thickness: f32, fraction: f32) -> Vec<u8> {
+        let mut image_data = vec![0u8; (width * width * 4) as usize];
+        let center = width as f32 / 2.0;
+        for y in 0..width {
+            for x in 0..width {
+                let (dx, dy) = (x as f32 + 0.5 - center, y as f32 + 0.5 - center);
+                let distance = (dx * dx + dy * dy).sqrt();
+                // Clockwise from 12 o'clock
+                let angle = dx.atan2(-dy).rem_euclid(std::f32::consts::TAU);
+                if (distance - radius).abs() <= thickness / 2.0
+                    && angle <= fraction * std::f32::consts::TAU
+                {
+                    image_data[((y * width + x) * 4 + 3) as usize] = 255;
+                }
+            }
+        }
+        image_data
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_spinner_progress() {
+        for fraction in [0.25, 0.4, 0.75, 1.0] {
+            let image_data = draw_arc(32, 11.0, 2.0, fraction);
+            let progress = detect_spinner_progress(&image_data, 32, 32).unwrap();
+            assert!(
+                (progress - fraction).abs() < 0.08,
+                "expected ~{}, got {}",
+                fraction,
+                progress
+            );
+        }
+
+        // The bundled progress cursor's badge is a complete ring
+        let svg = load_cursor_svg(&CommonCursorType::Progress).unwrap();
+        let image = rasterize_svg(&svg, 48).unwrap();
+        let progress = detect_spinner_progress(image.as_raw(), 48, 48).unwrap();
+        assert!(progress > 0.9);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_spinner_progress_rejects_other_shapes() {
+        for cursor_type in [CommonCursorType::Arrow, CommonCursorType::IBeam] {
+            let svg = load_cursor_svg(&cursor_type).unwrap();
+            let image = rasterize_svg(&svg, 32).unwrap();
+            assert_eq!(detect_spinner_progress(image.as_raw(), 32, 32), None);
+        }
+
+        // A filled disc has pixels all the way to its center
+        let mut disc = vec![0u8; 32 * 32 * 4];
+        fill_rect(&mut disc, 32, 8, 8, 24, 24);
+        assert_eq!(detect_spinner_progress(&disc, 32, 32), None);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_touch_pointer_detection() {
+        let svg = load_cursor_svg(&CommonCursorType::TouchPointer).unwrap();
+        for size in [24, 32, 48] {
+            let image = rasterize_svg(&svg, size).unwrap();
+            assert_eq!(
+                CommonCursorType::detect_from_image(image.as_raw(), size, size),
+                Some(CommonCursorType::TouchPointer),
+                "at {}px",
+                size
+            );
+        }
+
+        // A ring has the same outline but an empty center
+        let ring = draw_arc(32, 10.0, 3.0, 1.0);
+        assert_ne!(
+            CommonCursorType::detect_from_image(&ring, 32, 32),
+            Some(CommonCursorType::TouchPointer)
+        );
+    }
+
+    #[test]
+    fn test_beachball_detects_as_wait() {
+        for size in [32, 48] {
+            let image = fixture("wait", "beachball-glossy", size);
+            assert_eq!(
+                CommonCursorType::detect_from_image(image.as_raw(), size, size),
+                Some(CommonCursorType::Wait),
+                "at {size}px"
+            );
+
+            // The same disc in a single color is a touch pointer
+            let mut flat = image.clone();
+            for pixel in flat.pixels_mut() {
+                pixel.0[..3].copy_from_slice(&[128, 128, 128]);
+            }
+            assert_eq!(
+                CommonCursorType::detect_from_image(flat.as_raw(), size, size),
+                Some(CommonCursorType::TouchPointer),
+                "flat at {size}px"
+            );
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_badge_family_svgs() {
+        for cursor_type in [
+            CommonCursorType::Copy,
+            CommonCursorType::Alias,
+            CommonCursorType::Help,
+            CommonCursorType::Progress,
+            CommonCursorType::ContextMenu,
+            CommonCursorType::DownloadLink,
+        ] {
+            let svg = load_cursor_svg(&cursor_type).unwrap();
+            for size in [24, 32, 48] {
+                let image_data = rasterize_svg(&svg, size).unwrap().into_raw();
+                assert_eq!(
+                    CommonCursorType::detect_from_image(&image_data, size, size),
+                    Some(cursor_type),
+                    "{:?} at {}px",
+                    cursor_type,
+                    size
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_download_badge_is_not_a_plus() {
+        for size in [32, 48] {
+            let image = fixture("download-link", "classic-badge", size);
+            assert_eq!(
+                CommonCursorType::detect_from_image(image.as_raw(), size, size),
+                Some(CommonCursorType::DownloadLink),
+                "at {size}px"
+            );
+        }
+
+        // Both badges leave their corners empty and are symmetric left to
+        // right, only the down arrow tapers below its widest row
+        for (cursor_type, other) in [
+            (CommonCursorType::Copy, CommonCursorType::DownloadLink),
+            (CommonCursorType::DownloadLink, CommonCursorType::Copy),
+        ] {
+            let image = rasterize_cursor(&cursor_type, 32).unwrap();
+            let ranked = CommonCursorType::detect_ranked(image.as_raw(), 32, 32);
+            assert_eq!(ranked[0], (cursor_type, 1.0));
+            assert!(ranked.iter().all(|(candidate, _)| *candidate != other));
+        }
+    }
+
+    #[test]
+    fn test_cursor_names_map_to_types() {
+        for (name, expected) in [
+            ("pointer", Some(CommonCursorType::PointingHand)),
+            ("TEXT", Some(CommonCursorType::IBeam)),
+            (" col-resize ", Some(CommonCursorType::ResizeEW)),
+            ("se-resize", Some(CommonCursorType::ResizeNWSE)),
+            ("-webkit-grabbing", Some(CommonCursorType::Grabbing)),
+            ("none", Some(CommonCursorType::Blank)),
+            ("wait", Some(CommonCursorType::Wait)),
+            ("ns-resize", None),
+            // The other diagonal, which no type draws
+            ("nesw-resize", None),
+            ("ne-resize", None),
+            ("auto", None),
+            ("-webkit-", None),
+        ] {
+            assert_eq!(CommonCursorType::from_css_name(name), expected, "{name:?}");
+        }
+
+        // The bundled SVGs named after a CSS keyword are drawings of it
+        for &cursor_type in CommonCursorType::ALL {
+            let stem = cursor_type.svg_filename().trim_end_matches(".svg");
+            if let Some(named) = CommonCursorType::from_css_name(stem) {
+                assert_eq!(named, cursor_type, "{stem}");
+            }
+        }
+
+        assert_eq!(
+            CommonCursorType::from_win32_idc(32512),
+            Some(CommonCursorType::Arrow)
+        );
+        assert_eq!(
+            CommonCursorType::from_win32_idc(32649),
+            Some(CommonCursorType::PointingHand)
+        );
+        assert_eq!(
+            CommonCursorType::from_win32_idc(32642),
+            Some(CommonCursorType::ResizeNWSE)
+        );
+        // IDC_WAIT and IDC_APPSTARTING are both busy, a Wait is the beachball
+        assert_eq!(
+            CommonCursorType::from_win32_idc(32514),
+            Some(CommonCursorType::Progress)
+        );
+        // IDC_SIZENESW, IDC_UPARROW, and not an IDC_* at all
+        assert_eq!(CommonCursorType::from_win32_idc(32643), None);
+        assert_eq!(CommonCursorType::from_win32_idc(32516), None);
+        assert_eq!(CommonCursorType::from_win32_idc(0), None);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_canonical_detection() {
+        for &cursor_type in CommonCursorType::ALL {
+            let svg = load_cursor_svg(&cursor_type).unwrap();
+            // Only its colors tell the beachball from a touch pointer
+            let expected = match cursor_type {
+                CommonCursorType::Wait => CommonCursorType::TouchPointer,
+                cursor_type => cursor_type,
+            };
+            for size in [24, 32, 64] {
+                let image_data = rasterize_svg(&svg, size).unwrap().into_raw();
+                let mask = canonicalize(&image_data, size, size);
+                assert_eq!(
+                    CommonCursorType::detect_canonical(&mask),
+                    Some(expected),
+                    "{:?} at {}px",
+                    cursor_type,
+                    size
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_max_alpha_pooling_keeps_thin_stems() {
+        // A high DPI I-beam: a 2px stem with serifs, scaled down ~8x to fit
+        let size = 256;
+        let mut image_data = vec![0u8; (size * size * 4) as usize];
+        fill_rect(&mut image_data, size, 127, 18, 129, 238);
+        fill_rect(&mut image_data, size, 103, 18, 153, 20);
+        fill_rect(&mut image_data, size, 103, 236, 153, 238);
+
+        let averaged = canonicalize_with(&image_data, size, size, CanonicalPooling::Average);
+        assert_ne!(
+            CommonCursorType::detect_canonical(&averaged),
+            Some(CommonCursorType::IBeam)
+        );
+
+        let pooled = canonicalize_with(&image_data, size, size, CanonicalPooling::MaxAlpha);
+        assert!(pooled.contains(&255));
+        assert_eq!(
+            CommonCursorType::detect_canonical(&pooled),
+            Some(CommonCursorType::IBeam)
+        );
+        assert_eq!(canonicalize(&image_data, size, size), pooled);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_canonicalize_ignores_padding() {
+        let arrow = rasterize_svg(&load_cursor_svg(&CommonCursorType::Arrow).unwrap(), 32)
+            .unwrap()
+            .into_raw();
+        let mut padded = vec![0u8; 48 * 40 * 4];
+        for y in 0..32 {
+            let row = &arrow[y * 32 * 4..(y + 1) * 32 * 4];
+            let start = ((y + 5) * 48 + 11) * 4;
+            padded[start..start + row.len()].copy_from_slice(row);
+        }
+
+        assert_eq!(canonicalize(&arrow, 32, 32), canonicalize(&padded, 48, 40));
+        assert_eq!(canonicalize(&[0u8; 16 * 16 * 4], 16, 16), [0u8; 1024]);
+    }
+
+    #[test]
+    fn test_cursor_types_order_by_stable_id() {
+        let mut ids: Vec<u16> = CommonCursorType::ALL.iter().map(|t| t.id()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), CommonCursorType::ALL.len());
+
+        let mut by_ord = CommonCursorType::ALL.to_vec();
+        by_ord.sort();
+        let mut by_id = CommonCursorType::ALL.to_vec();
+        by_id.sort_by_key(|t| t.id());
+        assert_eq!(by_ord, by_id);
+        assert_eq!(CommonCursorType::Arrow.id(), 0);
+        assert!(CommonCursorType::Arrow < CommonCursorType::IBeam);
+
+        let counts: std::collections::BTreeMap<_, usize> = [
+            CommonCursorType::Grab,
+            CommonCursorType::Arrow,
+            CommonCursorType::Grab,
+        ]
+        .into_iter()
+        .fold(Default::default(), |mut counts, t| {
+            *counts.entry(t).or_default() += 1;
+            counts
+        });
+        assert_eq!(
+            counts.into_iter().collect::<Vec<_>>(),
+            [(CommonCursorType::Arrow, 1), (CommonCursorType::Grab, 2)]
+        );
+    }
+}
@@ -0,0 +1,353 @@
+//! Dumping the operating system's own cursors, to build ground-truth fixtures
+//! for the detection tests from the real thing rather than redrawn SVGs.
+
+use crate::cursor::detection::CommonCursorType;
+use crate::cursor::error::CursorError;
+use image::RgbaImage;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// File next to the dumped PNGs mapping each cursor to its hotspot
+pub const HOTSPOT_MANIFEST_FILE: &str = "hotspots.json";
+
+/// A standard system cursor captured as a bitmap, with its hotspot relative
+/// to the bitmap size like [`CommonCursorType::hotspot`]
+struct SystemCursor {
+    cursor_type: CommonCursorType,
+    image: RgbaImage,
+    hotspot: (f64, f64),
+}
+
+/// Capture the standard system cursors that correspond to a
+/// [`CommonCursorType`] and write each to `out_dir` as `<type>.png`, with the
+/// type named like its bundled SVG (`arrow.png`, `resize-ew.png`, ...), plus a
+/// [`HOTSPOT_MANIFEST_FILE`] of their hotspots. Returns the types written.
+///
+/// The cursors captured are those of the current user's cursor scheme at its
+/// native size, so running this on differently configured machines collects
+/// the variants the detector has to cope with. Supported on Windows and
+/// macOS; elsewhere it fails with [`CursorError::UnsupportedPlatform`].
+pub fn dump_system_cursors(out_dir: &Path) -> Result<Vec<CommonCursorType>, CursorError> {
+    write_cursor_dump(out_dir, &platform::system_cursors()?)
+}
+
+fn write_cursor_dump(
+    out_dir: &Path,
+    cursors: &[SystemCursor],
+) -> Result<Vec<CommonCursorType>, CursorError> {
+    let write_error = |path: &Path, message: String| CursorError::Write {
+        path: path.to_path_buf(),
+        message,
+    };
+    std::fs::create_dir_all(out_dir).map_err(|e| write_error(out_dir, e.to_string()))?;
+
+    let mut manifest = BTreeMap::new();
+    for cursor in cursors {
+        let name = cursor.cursor_type.svg_filename().trim_end_matches(".svg");
+        let path = out_dir.join(format!("{}.png", name));
+        cursor
+            .image
+            .save_with_format(&path, image::ImageFormat::Png)
+            .map_err(|e| write_error(&path, e.to_string()))?;
+        manifest.insert(name, [cursor.hotspot.0, cursor.hotspot.1]);
+    }
+
+    let path = out_dir.join(HOTSPOT_MANIFEST_FILE);
+    let json =
+        serde_json::to_string_pretty(&manifest).map_err(|e| write_error(&path, e.to_string()))?;
+    std::fs::write(&path, json).map_err(|e| write_error(&path, e.to_string()))?;
+
+    Ok(cursors.iter().map(|cursor| cursor.cursor_type).collect())
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::SystemCursor;
+    use crate::cursor::detection::CommonCursorType;
+    use crate::cursor::error::CursorError;
+    use image::RgbaImage;
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Graphics::Gdi::{
+        CreateCompatibleDC, CreateDIBSection, DeleteDC, DeleteObject, GetDC, GetObjectA, ReleaseDC,
+        SelectObject, BITMAP, BITMAPINFO, BITMAPINFOHEADER, DIB_RGB_COLORS,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        DrawIconEx, GetIconInfo, LoadCursorW, DI_NORMAL, HCURSOR, ICONINFO, IDC_APPSTARTING,
+        IDC_ARROW, IDC_CROSS, IDC_HAND, IDC_HELP, IDC_IBEAM, IDC_NO, IDC_SIZEALL, IDC_SIZENWSE,
+        IDC_SIZEWE,
+    };
+
+    /// Stock cursors and the types they're drawn as
+    const STOCK_CURSORS: [(PCWSTR, CommonCursorType); 10] = [
+        (IDC_ARROW, CommonCursorType::Arrow),
+        (IDC_IBEAM, CommonCursorType::IBeam),
+        (IDC_HAND, CommonCursorType::PointingHand),
+        (IDC_CROSS, CommonCursorType::Crosshair),
+        (IDC_SIZEWE, CommonCursorType::ResizeEW),
+        (IDC_SIZENWSE, CommonCursorType::ResizeNWSE),
+        (IDC_SIZEALL, CommonCursorType::Move),
+        (IDC_APPSTARTING, CommonCursorType::Progress),
+        (IDC_HELP, CommonCursorType::Help),
+        (IDC_NO, CommonCursorType::NotAllowed),
+    ];
+
+    pub(super) fn system_cursors() -> Result<Vec<SystemCursor>, CursorError> {
+        Ok(STOCK_CURSORS
+            .iter()
+            .filter_map(|&(id, cursor_type)| {
+                let cursor = unsafe { LoadCursorW(None, id) }.ok()?;
+                let (image, hotspot) = unsafe { capture(cursor) }?;
+                Some(SystemCursor {
+                    cursor_type,
+                    image,
+                    hotspot,
+                })
+            })
+            .collect())
+    }
+
+    /// Draw a cursor twice, over black and over white, and recover its alpha
+    /// from the difference. Legacy monochrome cursors have no alpha channel
+    /// of their own, so drawing once would lose their shape
+    unsafe fn capture(cursor: HCURSOR) -> Option<(RgbaImage, (f64, f64))> {
+        let mut icon_info = ICONINFO::default();
+        GetIconInfo(cursor, &mut icon_info).ok()?;
+
+        let bitmap_handle = if !icon_info.hbmColor.is_invalid() {
+            icon_info.hbmColor
+        } else {
+            icon_info.hbmMask
+        };
+        let mut bitmap = BITMAP::default();
+        let got_bitmap = GetObjectA(
+            bitmap_handle,
+            std::mem::size_of::<BITMAP>() as i32,
+            Some(&mut bitmap as *mut _ as *mut _),
+        ) != 0;
+        let width = bitmap.bmWidth;
+        // Monochrome cursors stack the AND and XOR masks in one bitmap
+        let height = if icon_info.hbmColor.is_invalid() {
+            bitmap.bmHeight / 2
+        } else {
+            bitmap.bmHeight
+        };
+        let hotspot = (
+            icon_info.xHotspot as f64 / width.max(1) as f64,
+            icon_info.yHotspot as f64 / height.max(1) as f64,
+        );
+        if !icon_info.hbmColor.is_invalid() {
+            let _ = DeleteObject(icon_info.hbmColor);
+        }
+        if !icon_info.hbmMask.is_invalid() {
+            let _ = DeleteObject(icon_info.hbmMask);
+        }
+        if !got_bitmap || width <= 0 || height <= 0 {
+            return None;
+        }
+
+        let over_black = draw(cursor, width, height, 0x00)?;
+        let over_white = draw(cursor, width, height, 0xFF)?;
+
+        let image = RgbaImage::from_fn(width as u32, height as u32, |x, y| {
+            let i = ((y * width as u32 + x) * 4) as usize;
+            // The DIB is BGRA
+            let black = [over_black[i + 2], over_black[i + 1], over_black[i]];
+            let white = [over_white[i + 2], over_white[i + 1], over_white[i]];
+            let alpha = 255 - (white[1] as i32 - black[1] as i32).clamp(0, 255);
+            if alpha == 0 {
+                return image::Rgba([0, 0, 0, 0]);
+            }
+
+            // Over black the color is premultiplied by the alpha
+            let color = black.map(|c| (c as i32 * 255 / alpha).min(255) as u8);
+            image::Rgba([color[0], color[1], color[2], alpha as u8])
+        });
+
+        Some((image, hotspot))
+    }
+
+    /// The cursor drawn onto a BGRA bitmap filled with `background`
+    unsafe fn draw(cursor: HCURSOR, width: i32, height: i32, background: u8) -> Option<Vec<u8>> {
+        let bitmap_info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width,
+                // Negative for a top-down DIB
+                biHeight: -height,
+                biPlanes: 1,
+                biBitCount: 32,
+                ..Default::default()
+            },
+            bmiColors: [Default::default()],
+        };
+
+        let screen_dc = GetDC(HWND::default());
+        let mem_dc = CreateCompatibleDC(screen_dc);
+        let mut bits: *mut std::ffi::c_void = std::ptr::null_mut();
+        let pixels =
+            match CreateDIBSection(mem_dc, &bitmap_info, DIB_RGB_COLORS, &mut bits, None, 0) {
+                Ok(dib) => {
+                    let len = width as usize * height as usize * 4;
+                    std::ptr::write_bytes(bits as *mut u8, background, len);
+
+                    let old_bitmap = SelectObject(mem_dc, dib);
+                    let drawn = DrawIconEx(mem_dc, 0, 0, cursor, 0, 0, 0, None, DI_NORMAL).is_ok();
+                    let pixels =
+                        drawn.then(|| std::slice::from_raw_parts(bits as *const u8, len).to_vec());
+
+                    SelectObject(mem_dc, old_bitmap);
+                    let _ = DeleteObject(dib);
+                    pixels
+                }
+                Err(_) => None,
+            };
+        let _ = DeleteDC(mem_dc);
+        ReleaseDC(HWND::default(), screen_dc);
+
+        pixels
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::SystemCursor;
+    use crate::cursor::detection::CommonCursorType;
+    use crate::cursor::error::CursorError;
+    use cocoa::base::{id, nil};
+    use cocoa::foundation::{NSPoint, NSSize, NSUInteger};
+    use objc::rc::autoreleasepool;
+    use objc::runtime::{Class, Sel};
+    use objc::{msg_send, sel, sel_impl};
+
+    /// `NSCursor` class properties and the types they're drawn as
+    const STANDARD_CURSORS: [(&str, CommonCursorType); 11] = [
+        ("arrowCursor", CommonCursorType::Arrow),
+        ("IBeamCursor", CommonCursorType::IBeam),
+        ("pointingHandCursor", CommonCursorType::PointingHand),
+        ("openHandCursor", CommonCursorType::Grab),
+        ("closedHandCursor", CommonCursorType::Grabbing),
+        ("crosshairCursor", CommonCursorType::Crosshair),
+        ("resizeLeftRightCursor", CommonCursorType::ResizeEW),
+        ("dragCopyCursor", CommonCursorType::Copy),
+        ("dragLinkCursor", CommonCursorType::Alias),
+        ("contextualMenuCursor", CommonCursorType::ContextMenu),
+        ("operationNotAllowedCursor", CommonCursorType::NotAllowed),
+    ];
+
+    pub(super) fn system_cursors() -> Result<Vec<SystemCursor>, CursorError> {
+        let class = Class::get("NSCursor")
+            .ok_or_else(|| CursorError::UnsupportedPlatform("macOS without NSCursor".into()))?;
+
+        Ok(STANDARD_CURSORS
+            .iter()
+            .filter_map(|&(property, cursor_type)| {
+                autoreleasepool(|| unsafe { capture(class, property) }).map(|(image, hotspot)| {
+                    SystemCursor {
+                        cursor_type,
+                        image,
+                        hotspot,
+                    }
+                })
+            })
+            .collect())
+    }
+
+    unsafe fn capture(class: &Class, property: &str) -> Option<(image::RgbaImage, (f64, f64))> {
+        let cursor: id = msg_send![class, performSelector: Sel::register(property)];
+        if cursor == nil {
+            return None;
+        }
+
+        let image: id = msg_send![cursor, image];
+        if image == nil {
+            return None;
+        }
+        let size: NSSize = msg_send![image, size];
+        let hotspot: NSPoint = msg_send![cursor, hotSpot];
+
+        let tiff: id = msg_send![image, TIFFRepresentation];
+        if tiff == nil {
+            return None;
+        }
+        let length: NSUInteger = msg_send![tiff, length];
+        let bytes: *const u8 = msg_send![tiff, bytes];
+        let data = std::slice::from_raw_parts(bytes, length as usize);
+
+        let bitmap = image::load_from_memory_with_format(data, image::ImageFormat::Tiff)
+            .ok()?
+            .to_rgba8();
+        // The hotspot is in points, as is the image size
+        Some((bitmap, (hotspot.x / size.width, hotspot.y / size.height)))
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+mod platform {
+    use super::SystemCursor;
+    use crate::cursor::error::CursorError;
+
+    pub(super) fn system_cursors() -> Result<Vec<SystemCursor>, CursorError> {
+        Err(CursorError::UnsupportedPlatform(
+            std::env::consts::OS.to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_writes_pngs_and_hotspot_manifest() {
+        let dir = std::env::temp_dir().join(format!("cursor-dump-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let cursor = |cursor_type: CommonCursorType, hotspot| SystemCursor {
+            cursor_type,
+            image: RgbaImage::from_pixel(4, 4, image::Rgba([0, 0, 0, 255])),
+            hotspot,
+        };
+        let written = write_cursor_dump(
+            &dir,
+            &[
+                cursor(CommonCursorType::Arrow, (0.0, 0.0)),
+                cursor(CommonCursorType::ResizeEW, (0.5, 0.5)),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            written,
+            [CommonCursorType::Arrow, CommonCursorType::ResizeEW]
+        );
+
+        assert_eq!(
+            image::open(dir.join("resize-ew.png"))
+                .unwrap()
+                .to_rgba8()
+                .dimensions(),
+            (4, 4)
+        );
+        let manifest: BTreeMap<String, [f64; 2]> = serde_json::from_str(
+            &std::fs::read_to_string(dir.join(HOTSPOT_MANIFEST_FILE)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(manifest["arrow"], [0.0, 0.0]);
+        assert_eq!(manifest["resize-ew"], [0.5, 0.5]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    #[test]
+    fn test_unsupported_platform() {
+        let dir = std::env::temp_dir().join(format!("cursor-dump-none-{}", std::process::id()));
+        assert_eq!(
+            dump_system_cursors(&dir),
+            Err(CursorError::UnsupportedPlatform(
+                std::env::consts::OS.to_string()
+            ))
+        );
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,207 @@
+use image::RgbaImage;
+
+use crate::cursor::detection::ChannelOrder;
+use crate::cursor::geometry::Point;
+
+/// Mean luminance, in 0..1, from which a background counts as light
+const LIGHT_BACKGROUND_LUMINANCE: f32 = 0.5;
+
+/// Whether cursors are drawn light or dark.
+///
+/// The bundled SVGs mix both, e.g. a white arrow next to a black I-beam, so
+/// a theme is applied by inverting the cursors drawn the other way, which
+/// keeps their fill and outline contrasting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CursorTheme {
+    /// Light fill with a dark outline, for dark content
+    #[default]
+    Light,
+    /// Dark fill with a light outline, for light content
+    Dark,
+    /// Whichever contrasts with the frame under the cursor, see
+    /// [`pick_theme_for_region`]
+    Auto,
+}
+
+impl CursorTheme {
+    /// The theme to draw a cursor at `position` with, resolving `Auto`
+    /// against the frame. Arguments are as for [`pick_theme_for_region`]
+    pub fn resolve(self, frame: &RgbaImage, position: impl Into<Point>, cursor_size: u32) -> Self {
+        self.resolve_with(frame, position, cursor_size, ChannelOrder::Rgba)
+    }
+
+    /// Like [`Self::resolve`], for a frame whose pixels are in `order`
+    pub fn resolve_with(
+        self,
+        frame: &RgbaImage,
+        position: impl Into<Point>,
+        cursor_size: u32,
+        order: ChannelOrder,
+    ) -> Self {
+        match self {
+            CursorTheme::Auto => pick_theme_for_region_with(frame, position, cursor_size, order),
+            theme => theme,
+        }
+    }
+}
+
+/// Pick the theme that stands out against the part of `frame` a cursor of
+/// `cursor_size` pixels with its top-left corner at `position` will cover:
+/// [`CursorTheme::Dark`] over light content, [`CursorTheme::Light`] over dark.
+///
+/// Only the part of the region inside the frame is sampled. A cursor entirely
+/// off the frame gets the default theme.
+pub fn pick_theme_for_region(
+    frame: &RgbaImage,
+    position: impl Into<Point>,
+    cursor_size: u32,
+) -> CursorTheme {
+    pick_theme_for_region_with(frame, position, cursor_size, ChannelOrder::Rgba)
+}
+
+/// Like [`pick_theme_for_region`], for a frame whose pixels are in `order`,
+/// e.g. a BGRA capture that was never converted
+pub fn pick_theme_for_region_with(
+    frame: &RgbaImage,
+    position: impl Into<Point>,
+    cursor_size: u32,
+    order: ChannelOrder,
+) -> CursorTheme {
+    let span = |start: i32, len: u32| {
+        let end = (start as i64 + cursor_size as i64).min(len as i64);
+        (start.max(0) as i64)..end.max(0)
+    };
+
+    let position = position.into();
+    let (mut total, mut count) = (0.0f32, 0u32);
+    for y in span(position.y, frame.height()) {
+        for x in span(position.x, frame.width()) {
+            total += luminance(&order.to_rgba(frame.get_pixel(x as u32, y as u32).0));
+            count += 1;
+        }
+    }
+
+    match count {
+        0 => CursorTheme::default(),
+        _ if total / count as f32 > LIGHT_BACKGROUND_LUMINANCE => CursorTheme::Dark,
+        _ => CursorTheme::Light,
+    }
+}
+
+/// Recolor a rasterized cursor to `theme` by inverting its colors if it's
+/// drawn the other way. `Auto` leaves it unchanged, so resolve it first
+pub fn apply_theme(cursor: &mut RgbaImage, theme: CursorTheme) {
+    // Mean luminance of the cursor itself, weighted by coverage
+    let (weighted, coverage) = cursor.pixels().fold((0.0f32, 0.0f32), |(sum, total), p| {
+        let alpha = p[3] as f32 / 255.0;
+        (sum + luminance(&p.0) * alpha, total + alpha)
+    });
+    if coverage == 0.0 {
+        return;
+    }
+
+    let is_light = weighted / coverage > LIGHT_BACKGROUND_LUMINANCE;
+    let invert = match theme {
+        CursorTheme::Light => !is_light,
+        CursorTheme::Dark => is_light,
+        CursorTheme::Auto => false,
+    };
+    if invert {
+        for pixel in cursor.pixels_mut() {
+            for channel in &mut pixel.0[..3] {
+                *channel = 255 - *channel;
+            }
+        }
+    }
+}
+
+/// Relative luminance of an sRGB pixel in 0..1, ignoring alpha
+fn luminance(pixel: &[u8; 4]) -> f32 {
+    (0.2126 * pixel[0] as f32 + 0.7152 * pixel[1] as f32 + 0.0722 * pixel[2] as f32) / 255.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursor::detection::CommonCursorType;
+    use crate::cursor::rendering::rasterize_cursor;
+
+    /// White on the left half, black on the right
+    fn split_frame() -> RgbaImage {
+        RgbaImage::from_fn(64, 32, |x, _| {
+            image::Rgba(if x < 32 {
+                [255, 255, 255, 255]
+            } else {
+                [0, 0, 0, 255]
+            })
+        })
+    }
+
+    #[test]
+    fn test_contrasts_with_background() {
+        let frame = split_frame();
+        assert_eq!(pick_theme_for_region(&frame, (4, 4), 16), CursorTheme::Dark);
+        assert_eq!(
+            pick_theme_for_region(&frame, (40, 4), 16),
+            CursorTheme::Light
+        );
+    }
+
+    #[test]
+    fn test_samples_only_the_in_bounds_part() {
+        let frame = split_frame();
+        // Mostly off the left edge, the visible part is white
+        assert_eq!(
+            pick_theme_for_region(&frame, (-28, 0), 32),
+            CursorTheme::Dark
+        );
+        // Hanging off the right edge over black
+        assert_eq!(
+            pick_theme_for_region(&frame, (56, 20), 32),
+            CursorTheme::Light
+        );
+        assert_eq!(
+            pick_theme_for_region(&frame, (-40, 0), 32),
+            CursorTheme::default()
+        );
+        assert_eq!(
+            CursorTheme::Auto.resolve(&frame, (4, 4), 16),
+            CursorTheme::Dark
+        );
+    }
+
+    #[test]
+    fn test_reads_bgra_frames() {
+        // Orange is just light enough for the dark theme, while the same
+        // bytes read as RGBA are a darker blue
+        let orange = RgbaImage::from_pixel(16, 16, image::Rgba([255, 130, 0, 255]));
+        assert_eq!(
+            pick_theme_for_region(&orange, (0, 0), 16),
+            CursorTheme::Dark
+        );
+
+        let mut bgra = orange.clone();
+        ChannelOrder::Bgra.reorder(&mut bgra);
+        assert_eq!(pick_theme_for_region(&bgra, (0, 0), 16), CursorTheme::Light);
+        assert_eq!(
+            CursorTheme::Auto.resolve_with(&bgra, (0, 0), 16, ChannelOrder::Bgra),
+            CursorTheme::Dark
+        );
+    }
+
+    #[test]
+    fn test_apply_theme_inverts_the_other_way() {
+        // The bundled arrow is white with a black outline
+        let arrow = rasterize_cursor(&CommonCursorType::Arrow, 32).unwrap();
+
+        let mut light = arrow.clone();
+        apply_theme(&mut light, CursorTheme::Light);
+        assert_eq!(light, arrow);
+
+        let mut dark = arrow.clone();
+        apply_theme(&mut dark, CursorTheme::Dark);
+        assert_ne!(dark, arrow);
+        apply_theme(&mut dark, CursorTheme::Light);
+        assert_eq!(dark, arrow);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::cursor::geometry::{Point, Size};
+use cap_project::CursorEvents;
+
+/// A recording's cursor positions over time, for looking up where the cursor
+/// was between its samples.
+///
+/// Positions are in the recording's normalized display space, 0..1 on both
+/// axes like [`CursorEvents`] stores them, and times are in seconds.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CursorTrack {
+    /// Time, x and y of each sample, ordered by time
+    samples: Vec<(f64, f64, f64)>,
+}
+
+impl CursorTrack {
+    /// Build a track from `(time_secs, x, y)` samples in any order. Samples
+    /// with a non-finite time or position are dropped
+    pub fn new(samples: impl IntoIterator<Item = (f64, f64, f64)>) -> Self {
+        let mut samples: Vec<_> = samples
+            .into_iter()
+            .filter(|&(time, x, y)| time.is_finite() && x.is_finite() && y.is_finite())
+            .collect();
+        // Stable, so samples sharing a time keep their recorded order
+        samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Self { samples }
+    }
+
+    /// Track of a recording's cursor moves
+    pub fn from_events(events: &CursorEvents) -> Self {
+        Self::new(
+            events
+                .moves
+                .iter()
+                .map(|event| (event.time_ms / 1000.0, event.x, event.y)),
+        )
+    }
+
+    /// Where the cursor is at `time_secs`, linearly interpolated between the
+    /// samples around it. Before the first sample and after the last the
+    /// cursor stays where that sample put it. `None` for an empty track or a
+    /// non-finite time
+    pub fn position_at(&self, time_secs: f64) -> Option<(f64, f64)> {
+        if !time_secs.is_finite() {
+            return None;
+        }
+
+        // First sample after `time_secs`
+        let next = self
+            .samples
+            .partition_point(|&(time, _, _)| time <= time_secs);
+        let (x, y) = match (next.checked_sub(1), self.samples.get(next)) {
+            (Some(previous), Some(&(t1, x1, y1))) => {
+                let (t0, x0, y0) = self.samples[previous];
+                let mix = (time_secs - t0) / (t1 - t0);
+                (x0 + (x1 - x0) * mix, y0 + (y1 - y0) * mix)
+            }
+            (Some(last), None) => (self.samples[last].1, self.samples[last].2),
+            (None, Some(&(_, x, y))) => (x, y),
+            (None, None) => return None,
+        };
+
+        Some((x, y))
+    }
+
+    /// [`Self::position_at`] on a frame of `frame_size`, the normalized
+    /// position scaled to it and rounded to the nearest pixel
+    pub fn pixel_position_at(&self, time_secs: f64, frame_size: impl Into<Size>) -> Option<Point> {
+        let (x, y) = self.position_at(time_secs)?;
+        let frame_size = frame_size.into();
+        Some(Point::new(
+            (x * frame_size.w as f64).round() as i32,
+            (y * frame_size.h as f64).round() as i32,
+        ))
+    }
+
+    /// How fast the cursor moves at `time_secs` on a frame of `frame_size`,
+    /// in pixels per second along each axis, for effects that scale with
+    /// speed like motion blur.
+    ///
+    /// This is the slope of [`Self::position_at`]'s interpolation: constant
+    /// between two samples and taken from the segment that starts at a
+    /// sample's time, or the one ending at the last sample for that. Samples
+    /// sharing a time don't make a segment. Zero outside the track, where
+    /// the cursor is held still, and for a track without two sample times or
+    /// a non-finite time
+    pub fn velocity_at(&self, time_secs: f64, frame_size: impl Into<Size>) -> (f32, f32) {
+        let (Some(&(first, _, _)), Some(&(last, _, _))) =
+            (self.samples.first(), self.samples.last())
+        else {
+            return (0.0, 0.0);
+        };
+        if !time_secs.is_finite() || time_secs < first || time_secs > last || first == last {
+            return (0.0, 0.0);
+        }
+
+        // The first sample after `time_secs` ends the segment, or at the last
+        // sample's time the last sample does and the segment reaches back to
+        // the latest sample before it
+        let next = self
+            .samples
+            .partition_point(|&(time, _, _)| time <= time_secs);
+        let (end, start) = match self.samples.get(next) {
+            Some(&end) => (end, self.samples[next - 1]),
+            None => {
+                let before = self.samples.partition_point(|&(time, _, _)| time < last);
+                (
+                    self.samples[self.samples.len() - 1],
+                    self.samples[before - 1],
+                )
+            }
+        };
+
+        let (t0, x0, y0) = start;
+        let (t1, x1, y1) = end;
+        let frame_size = frame_size.into();
+        let duration = t1 - t0;
+        (
+            ((x1 - x0) / duration * frame_size.w as f64) as f32,
+            ((y1 - y0) / duration * frame_size.h as f64) as f32,
+        )
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
+
+impl From<&CursorEvents> for CursorTrack {
+    fn from(events: &CursorEvents) -> Self {
+        Self::from_events(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolates_between_samples() {
+        let track = CursorTrack::new([(1.0, 0.5, 0.5), (0.0, 0.0, 0.0), (2.0, 0.5, 1.0)]);
+
+        assert_eq!(track.position_at(0.25), Some((0.125, 0.125)));
+        assert_eq!(track.position_at(1.0), Some((0.5, 0.5)));
+        assert_eq!(track.position_at(1.5), Some((0.5, 0.75)));
+    }
+
+    #[test]
+    fn test_holds_position_outside_the_track() {
+        let track = CursorTrack::new([(1.0, 0.25, 0.75), (2.0, 0.5, 0.5)]);
+
+        assert_eq!(track.position_at(0.0), Some((0.25, 0.75)));
+        assert_eq!(track.position_at(10.0), Some((0.5, 0.5)));
+        assert_eq!(track.position_at(f64::NAN), None);
+        assert_eq!(CursorTrack::default().position_at(1.0), None);
+    }
+
+    #[test]
+    fn test_velocity_follows_the_interpolated_segments() {
+        let track = CursorTrack::new([
+            (0.0, 0.0, 0.0),
+            (0.5, 0.25, 0.5),
+            (1.0, 0.25, 0.5),
+            (1.0, 0.5, 0.5),
+            (2.0, 1.0, 0.5),
+        ]);
+
+        assert_eq!(track.velocity_at(0.25, (200, 100)), (100.0, 100.0));
+        // A sample's time belongs to the segment starting there
+        assert_eq!(track.velocity_at(0.5, (200, 100)), (0.0, 0.0));
+        // The samples sharing a time are skipped, not divided by
+        assert_eq!(track.velocity_at(1.0, (200, 100)), (100.0, 0.0));
+        assert_eq!(track.velocity_at(2.0, (200, 100)), (100.0, 0.0));
+
+        assert_eq!(track.velocity_at(-1.0, (200, 100)), (0.0, 0.0));
+        assert_eq!(track.velocity_at(3.0, (200, 100)), (0.0, 0.0));
+        assert_eq!(track.velocity_at(f64::NAN, (200, 100)), (0.0, 0.0));
+        let still = CursorTrack::new([(1.0, 0.0, 0.0), (1.0, 1.0, 1.0)]);
+        assert_eq!(still.velocity_at(1.0, (200, 100)), (0.0, 0.0));
+        assert_eq!(
+            CursorTrack::default().velocity_at(0.0, (200, 100)),
+            (0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_pixel_position_scales_each_axis() {
+        let track = CursorTrack::new([(0.0, 0.25, 0.5)]);
+
+        assert_eq!(
+            track.pixel_position_at(0.0, (200, 100)),
+            Some(Point::new(50, 50))
+        );
+        assert_eq!(
+            CursorTrack::default().pixel_position_at(0.0, (200, 100)),
+            None
+        );
+    }
+}
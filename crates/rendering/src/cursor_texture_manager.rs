@@ -1,5 +1,9 @@
-use crate::cursor_svg::{analyze_cursor_image, CommonCursorType};
+use crate::cursor::assets::CursorAssetSource;
+use crate::cursor::detection::CommonCursorType;
+use crate::cursor::loading::analyze_cursor_image;
+use crate::cursor::rendering::{rasterize_cursor, rasterize_svg};
 use cap_project::XY;
+use image::{GenericImageView, RgbaImage};
 use std::collections::HashMap;
 use std::path::Path;
 
@@ -16,7 +20,8 @@ pub struct EnhancedCursorTexture {
 pub enum CursorSourceType {
     /// Original captured cursor image
     Captured,
-    /// High-quality SVG version
+    /// High-quality bundled version, rendered from its SVG or, without the
+    /// `svg` feature, scaled from its PNG fallback
     Svg,
 }
 
@@ -121,7 +126,22 @@ impl CursorTextureManager {
         Ok(())
     }
 
-    /// Load an SVG cursor texture for a specific cursor type
+    /// Load the bundled cursor texture for a specific cursor type. Without the
+    /// `svg` feature it's scaled from the nearest bundled PNG instead
+    pub fn load_bundled_cursor(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        cursor_type: CommonCursorType,
+        size: u32,
+    ) -> Result<(), String> {
+        let rgba_data = rasterize_cursor(&cursor_type, size).map_err(|e| e.to_string())?;
+        self.insert_svg_texture(device, queue, cursor_type, &rgba_data);
+        Ok(())
+    }
+
+    /// Load an SVG cursor texture for a specific cursor type. Needs the `svg`
+    /// feature, see [`Self::load_bundled_cursor`] for one that doesn't
     pub fn load_svg_cursor(
         &mut self,
         device: &wgpu::Device,
@@ -131,37 +151,20 @@ impl CursorTextureManager {
         size: u32,
     ) -> Result<(), String> {
         // Rasterize the SVG using resvg
-        let svg_data =
-            std::str::from_utf8(svg_content).map_err(|e| format!("Invalid UTF-8 in SVG: {}", e))?;
-
-        let rtree = resvg::usvg::Tree::from_str(svg_data, &resvg::usvg::Options::default())
-            .map_err(|e| format!("Failed to parse SVG: {}", e))?;
-
-        let pixmap_size = rtree.size().to_int_size();
-        let target_size = tiny_skia::IntSize::from_wh(size, size).ok_or("Invalid target size")?;
-
-        let mut pixmap = tiny_skia::Pixmap::new(target_size.width(), target_size.height())
-            .ok_or("Failed to create pixmap")?;
-
-        // Calculate scale to fit the SVG into the target size while maintaining aspect ratio
-        let scale_x = target_size.width() as f32 / pixmap_size.width() as f32;
-        let scale_y = target_size.height() as f32 / pixmap_size.height() as f32;
-        let scale = scale_x.min(scale_y);
-
-        let transform = tiny_skia::Transform::from_scale(scale, scale);
-
-        resvg::render(&rtree, transform, &mut pixmap.as_mut());
-
-        // Convert pixmap to RGBA format for wgpu
-        let rgba_data: Vec<u8> = pixmap
-            .pixels()
-            .iter()
-            .flat_map(|pixel| {
-                let [b, g, r, a] = pixel.to_array();
-                [r, g, b, a]
-            })
-            .collect();
+        let rgba_data = rasterize_svg(svg_content, size).map_err(|e| e.to_string())?;
+        self.insert_svg_texture(device, queue, cursor_type, &rgba_data);
+        Ok(())
+    }
 
+    /// Upload a square rasterized cursor as the texture for its type
+    fn insert_svg_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        cursor_type: CommonCursorType,
+        rgba_data: &RgbaImage,
+    ) {
+        let size = rgba_data.width();
         let texture_size = wgpu::Extent3d {
             width: size,
             height: size,
@@ -186,7 +189,7 @@ impl CursorTextureManager {
                 mip_level: 0,
                 origin: wgpu::Origin3d::ZERO,
             },
-            &rgba_data,
+            rgba_data,
             wgpu::ImageDataLayout {
                 offset: 0,
                 bytes_per_row: Some(4 * size),
@@ -196,14 +199,8 @@ impl CursorTextureManager {
         );
 
         // Set default hotspot for SVG cursors based on type
-        let hotspot = match cursor_type {
-            CommonCursorType::Arrow => XY::new(0.1, 0.1), // Top-left point
-            CommonCursorType::IBeam => XY::new(0.5, 0.5), // Center
-            CommonCursorType::Crosshair => XY::new(0.5, 0.5), // Center
-            CommonCursorType::PointingHand => XY::new(0.3, 0.1), // Finger tip
-            CommonCursorType::ResizeNWSE => XY::new(0.5, 0.5), // Center
-            CommonCursorType::ResizeEW => XY::new(0.5, 0.5), // Center
-        };
+        let (hotspot_x, hotspot_y) = cursor_type.hotspot();
+        let hotspot = XY::new(hotspot_x, hotspot_y);
 
         let enhanced_texture = EnhancedCursorTexture {
             inner: texture,
@@ -213,29 +210,45 @@ impl CursorTextureManager {
         };
 
         self.svg_textures.insert(cursor_type, enhanced_texture);
-        Ok(())
     }
 
-    /// Initialize all built-in SVG cursors
+    /// Initialize all built-in SVG cursors, from their bundled PNG fallbacks
+    /// in builds without the `svg` feature
     pub fn initialize_svg_cursors(
         &mut self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
     ) -> Result<(), String> {
-        use crate::cursor_svg::{load_cursor_svg, CommonCursorType};
+        // Only worth the extra rasterization while developing the matchers
+        #[cfg(debug_assertions)]
+        if let Err(mismatches) = crate::cursor::loading::self_test() {
+            log::warn!(
+                "Bundled cursors detected as the wrong type: {:?}",
+                mismatches
+            );
+        }
+
+        for &cursor_type in CommonCursorType::ALL {
+            // Use a higher resolution for SVG cursors (64x64) for better quality
+            self.load_bundled_cursor(device, queue, cursor_type, 64)?;
+        }
+
+        Ok(())
+    }
+
+    /// Initialize an SVG cursor for every type, reading the SVGs from `source`.
+    /// Needs the `svg` feature, a source's SVGs have no PNG fallbacks
+    pub fn initialize_svg_cursors_from(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        source: &dyn CursorAssetSource,
+    ) -> Result<(), String> {
+        use crate::cursor::loading::load_cursor_svg_from;
 
         // Load all the SVG cursors we have
-        let cursor_types = vec![
-            CommonCursorType::Arrow,
-            CommonCursorType::IBeam,
-            CommonCursorType::Crosshair,
-            CommonCursorType::PointingHand,
-            CommonCursorType::ResizeNWSE,
-            CommonCursorType::ResizeEW,
-        ];
-
-        for cursor_type in cursor_types {
-            if let Some(svg_content) = load_cursor_svg(&cursor_type) {
+        for &cursor_type in CommonCursorType::ALL {
+            if let Some(svg_content) = load_cursor_svg_from(source, &cursor_type) {
                 // Use a higher resolution for SVG cursors (64x64) for better quality
                 self.load_svg_cursor(device, queue, cursor_type, &svg_content, 64)?;
             } else {
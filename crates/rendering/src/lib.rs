@@ -23,9 +23,8 @@ use tokio::sync::mpsc;
 
 mod composite_frame;
 mod coord;
+pub mod cursor;
 mod cursor_interpolation;
-mod cursor_svg;
-mod cursor_svg_tests;
 mod cursor_texture_manager;
 pub mod decoder;
 mod frame_pipeline;
@@ -0,0 +1,79 @@
+//! Checks that detecting with a warm `DetectionScratch` doesn't allocate.
+//! Lives in its own test binary because it installs a counting global allocator.
+
+use cap_rendering::cursor::detection::{CommonCursorType, DetectionScratch};
+use cap_rendering::cursor::rendering::rasterize_cursor;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAllocator;
+
+thread_local! {
+    // Per thread, so the test harness allocating elsewhere isn't counted
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations_during(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn test_warm_scratch_detects_without_allocating() {
+    let mut frames = Vec::new();
+    for cursor_type in CommonCursorType::ALL {
+        for size in [24, 32, 48] {
+            let image = rasterize_cursor(cursor_type, size).unwrap();
+            // A dimmed copy goes through alpha normalization
+            let mut dimmed = image.clone();
+            dimmed.pixels_mut().for_each(|pixel| pixel[3] /= 2);
+            frames.push((image.into_raw(), size));
+            frames.push((dimmed.into_raw(), size));
+        }
+    }
+    let expected: Vec<_> = frames
+        .iter()
+        .map(|(data, size)| CommonCursorType::detect_from_image(data, *size, *size))
+        .collect();
+
+    let mut scratch = DetectionScratch::new();
+    let mut detected = Vec::with_capacity(frames.len());
+    // The first pass grows the scratch to the largest cursor
+    for (data, size) in &frames {
+        CommonCursorType::detect_from_image_in(data, *size, *size, &mut scratch);
+    }
+
+    let allocations = allocations_during(|| {
+        for (data, size) in &frames {
+            detected.push(CommonCursorType::detect_from_image_in(
+                data,
+                *size,
+                *size,
+                &mut scratch,
+            ));
+        }
+    });
+
+    assert_eq!(detected, expected);
+    assert_eq!(allocations, 0);
+}